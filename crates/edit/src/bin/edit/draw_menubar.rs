@@ -45,6 +45,22 @@ fn draw_menu_file(ctx: &mut Context, state: &mut State) {
     if ctx.menubar_menu_button(loc(LocId::FileOpen), 'O', kbmod::CTRL | vk::O) {
         state.wants_file_picker = StateFilePicker::Open;
     }
+    // Open Recent submenu: reopen a previously edited file by path.
+    if !state.settings.recent_files.is_empty() {
+        if ctx.menubar_menu_begin(loc(LocId::FileOpenRecent), 'R') {
+            for path in state.settings.recent_files.clone() {
+                if ctx.menubar_menu_button(&path, '\0', vk::NULL) {
+                    state.wants_open_path = Some(path);
+                }
+            }
+            if ctx.menubar_menu_button(loc(LocId::FileClearRecent), 'C', vk::NULL) {
+                state.settings.clear_recent();
+                let _ = state.settings.save();
+                ctx.needs_rerender();
+            }
+            ctx.menubar_menu_end();
+        }
+    }
     if state.documents.active().is_some() {
         if ctx.menubar_menu_button(loc(LocId::FileSave), 'S', kbmod::CTRL | vk::S) {
             state.wants_save = true;
@@ -55,6 +71,48 @@ fn draw_menu_file(ctx: &mut Context, state: &mut State) {
         if ctx.menubar_menu_button(loc(LocId::FileClose), 'C', kbmod::CTRL | vk::W) {
             state.wants_close = true;
         }
+
+        // Encoding submenu: reflects and reconverts the active document.
+        if ctx.menubar_menu_begin(loc(LocId::FileEncoding), 'E') {
+            use crate::settings::Encoding;
+            let doc = state.documents.active().unwrap();
+            let mut tb = doc.buffer.borrow_mut();
+            let current = tb.encoding();
+            for (enc, label) in [
+                (Encoding::Utf8, "UTF-8"),
+                (Encoding::Utf16Le, "UTF-16 LE"),
+                (Encoding::Utf16Be, "UTF-16 BE"),
+            ] {
+                if ctx.menubar_menu_checkbox(label, label.as_bytes()[0] as char, vk::NULL, current == enc)
+                    && current != enc
+                {
+                    tb.set_encoding(enc);
+                    ctx.needs_rerender();
+                }
+            }
+            ctx.menubar_menu_end();
+        }
+
+        // Line Endings submenu.
+        if ctx.menubar_menu_begin(loc(LocId::FileLineEndings), 'L') {
+            use crate::settings::LineEnding;
+            let doc = state.documents.active().unwrap();
+            let mut tb = doc.buffer.borrow_mut();
+            let current = tb.line_ending();
+            for (le, label) in [
+                (LineEnding::Lf, "LF"),
+                (LineEnding::Crlf, "CRLF"),
+                (LineEnding::Cr, "CR"),
+            ] {
+                if ctx.menubar_menu_checkbox(label, label.as_bytes()[0] as char, vk::NULL, current == le)
+                    && current != le
+                {
+                    tb.set_line_ending(le);
+                    ctx.needs_rerender();
+                }
+            }
+            ctx.menubar_menu_end();
+        }
     }
     if ctx.menubar_menu_button(loc(LocId::FileExit), 'X', kbmod::CTRL | vk::Q) {
         state.wants_exit = true;
@@ -122,6 +180,36 @@ fn draw_menu_view(ctx: &mut Context, state: &mut State) {
             tb.set_word_wrap(!word_wrap);
             ctx.needs_rerender();
         }
+
+        // Manual syntax override: force a language (or disable highlighting)
+        // regardless of what the file extension detected.
+        if ctx.menubar_menu_begin(loc(LocId::ViewSyntax), 'Y') {
+            let current = tb.syntax_override();
+            if ctx.menubar_menu_checkbox(loc(LocId::ViewSyntaxAuto), 'A', vk::NULL, current.is_none()) {
+                tb.set_syntax_override(None);
+                ctx.needs_rerender();
+            }
+            for &lang in edit::syntax::Language::ALL {
+                let checked = current == Some(lang);
+                let accel = lang.name().chars().next().unwrap_or(' ');
+                if ctx.menubar_menu_checkbox(lang.name(), accel, vk::NULL, checked) {
+                    tb.set_syntax_override(Some(lang));
+                    ctx.needs_rerender();
+                }
+            }
+            ctx.menubar_menu_end();
+        }
+
+        // Focus reader: distraction-free RSVP (one-word-at-a-time) reading.
+        if ctx.menubar_menu_checkbox(loc(LocId::ViewFocusReader), 'R', vk::NULL, state.rsvp_enabled) {
+            state.rsvp_enabled = !state.rsvp_enabled;
+            state.rsvp_word_index = 0;
+            state.rsvp_paused = false;
+            if state.rsvp_wpm == 0 {
+                state.rsvp_wpm = RSVP_DEFAULT_WPM;
+            }
+            ctx.needs_rerender();
+        }
     }
 
     ctx.menubar_menu_end();
@@ -137,6 +225,77 @@ fn draw_menu_help(ctx: &mut Context, state: &mut State) {
     ctx.menubar_menu_end();
 }
 
+/// Default reading speed (words per minute) for the focus reader.
+pub const RSVP_DEFAULT_WPM: u32 = 300;
+
+/// Draw the RSVP "focus reader": the active document is flashed one word at a
+/// time, centered on screen, advancing on a timer at `state.rsvp_wpm`. Space
+/// pauses/resumes, the arrow keys step a word at a time, and Escape exits.
+pub fn draw_focus_reader(ctx: &mut Context, state: &mut State) {
+    let Some(doc) = state.documents.active() else {
+        state.rsvp_enabled = false;
+        return;
+    };
+    let text = doc.buffer.borrow().text_contents();
+    let words: Vec<&str> = text.split_whitespace().collect();
+    if words.is_empty() {
+        state.rsvp_enabled = false;
+        return;
+    }
+
+    // Input: Escape exits, Space pauses/resumes, arrows step.
+    if ctx.consume_shortcut(vk::ESCAPE) {
+        state.rsvp_enabled = false;
+        return;
+    }
+    if ctx.consume_shortcut(vk::SPACE) {
+        state.rsvp_paused = !state.rsvp_paused;
+    }
+    if ctx.consume_shortcut(vk::LEFT) {
+        state.rsvp_paused = true;
+        state.rsvp_word_index = state.rsvp_word_index.saturating_sub(1);
+    }
+    if ctx.consume_shortcut(vk::RIGHT) {
+        state.rsvp_paused = true;
+        state.rsvp_word_index = (state.rsvp_word_index + 1).min(words.len() - 1);
+    }
+
+    let index = state.rsvp_word_index.min(words.len() - 1);
+
+    ctx.modal_begin("focus-reader", loc(LocId::ViewFocusReader));
+    {
+        ctx.block_begin("content");
+        ctx.inherit_focus();
+        ctx.attr_padding(Rect::three(2, 4, 2));
+        {
+            ctx.label("word", words[index]);
+            ctx.attr_position(Position::Center);
+
+            ctx.label(
+                "progress",
+                &arena_format!(ctx.arena(), "{} / {}", index + 1, words.len()),
+            );
+            ctx.attr_position(Position::Center);
+            ctx.attr_foreground_rgba(ctx.indexed(IndexedColor::BrightBlack));
+        }
+        ctx.block_end();
+    }
+    if ctx.modal_end() {
+        state.rsvp_enabled = false;
+    }
+
+    // Advance on the frame timer while playing, stopping at the last word.
+    if !state.rsvp_paused {
+        if index + 1 < words.len() {
+            state.rsvp_word_index = index + 1;
+            ctx.set_next_frame_timeout(60_000 / state.rsvp_wpm.max(1) as u64);
+            ctx.needs_rerender();
+        } else {
+            state.rsvp_paused = true;
+        }
+    }
+}
+
 pub fn draw_dialog_about(ctx: &mut Context, state: &mut State) {
     ctx.modal_begin("about", loc(LocId::AboutDialogTitle));
     {
@@ -203,6 +362,9 @@ pub fn draw_dialog_settings(ctx: &mut Context, state: &mut State) {
             if let Some(color) = state.settings.line_separator_color {
                 state.settings_line_separator_color_input = crate::settings::Settings::color_to_hex_pub(color);
             }
+            if let Some(name) = &state.settings.theme_name {
+                state.settings_theme_input = name.clone();
+            }
         }
         
         ctx.block_begin("content");
@@ -260,6 +422,26 @@ pub fn draw_dialog_settings(ctx: &mut Context, state: &mut State) {
             ctx.attr_overflow(Overflow::TruncateTail);
             ctx.attr_foreground_rgba(ctx.indexed(IndexedColor::BrightBlack));
 
+            // Syntax-highlighting theme section
+            ctx.label("theme-label", "Syntax theme");
+            ctx.attr_overflow(Overflow::TruncateTail);
+
+            // Theme name input field (a built-in name or a path to a theme file)
+            ctx.editline("theme-input", &mut state.settings_theme_input);
+            ctx.inherit_focus();
+            ctx.attr_intrinsic_size(Size { width: 200, height: 1 });
+
+            ctx.label(
+                "theme-hint",
+                &arena_format!(
+                    ctx.arena(),
+                    "Built-in: {}",
+                    edit::syntax::BUILTIN_THEMES.join(", ")
+                ),
+            );
+            ctx.attr_overflow(Overflow::TruncateTail);
+            ctx.attr_foreground_rgba(ctx.indexed(IndexedColor::BrightBlack));
+
             ctx.block_begin("choices");
             ctx.inherit_focus();
             ctx.attr_padding(Rect::three(1, 2, 0));
@@ -303,8 +485,25 @@ pub fn draw_dialog_settings(ctx: &mut Context, state: &mut State) {
                         state.settings.line_separator_color = Some(color);
                     }
                     
+                    // Save the chosen theme name (empty = built-in default)
+                    let theme_input = state.settings_theme_input.trim();
+                    if theme_input.is_empty() {
+                        state.settings.theme_name = None;
+                    } else {
+                        state.settings.theme_name = Some(theme_input.to_string());
+                    }
+
                     let _ = state.settings.save();
-                    
+
+                    // Resolve and apply the theme live: a built-in name, then a
+                    // theme file path, else fall back to the default theme.
+                    state.syntax_theme = match &state.settings.theme_name {
+                        Some(name) => edit::syntax::Theme::builtin(name)
+                            .or_else(|| edit::syntax::Theme::load(std::path::Path::new(name)).ok())
+                            .unwrap_or_default(),
+                        None => edit::syntax::Theme::default(),
+                    };
+
                     // Apply the colors immediately
                     state.menubar_color_bg = state.settings.titlebar_color.unwrap_or_else(|| {
                         ctx.indexed(IndexedColor::Background).oklab_blend(ctx.indexed_alpha(
@@ -337,6 +536,7 @@ pub fn draw_dialog_settings(ctx: &mut Context, state: &mut State) {
                     state.settings_selection_color_input.clear();
                     state.settings_line_number_color_input.clear();
                     state.settings_line_separator_color_input.clear();
+                    state.settings_theme_input.clear();
                     ctx.needs_rerender();
                 }
                 ctx.inherit_focus();
@@ -348,6 +548,7 @@ pub fn draw_dialog_settings(ctx: &mut Context, state: &mut State) {
                     state.settings_selection_color_input.clear();
                     state.settings_line_number_color_input.clear();
                     state.settings_line_separator_color_input.clear();
+                    state.settings_theme_input.clear();
                 }
             }
             ctx.block_end();
@@ -361,6 +562,7 @@ pub fn draw_dialog_settings(ctx: &mut Context, state: &mut State) {
         state.settings_selection_color_input.clear();
         state.settings_line_number_color_input.clear();
         state.settings_line_separator_color_input.clear();
+        state.settings_theme_input.clear();
     }
 }
 
@@ -25,6 +25,137 @@ pub struct Settings {
     /// Custom color for the separator between line numbers and text (RGBA format)
     /// If None, the default color will be used
     pub line_separator_color: Option<StraightRgba>,
+    /// Name of the syntax-highlighting theme to apply
+    /// If None, the built-in default theme is used
+    pub theme_name: Option<String>,
+    /// Most-recently-opened file paths, newest first (capped, deduplicated)
+    pub recent_files: Vec<String>,
+    /// Default character encoding for new documents
+    pub default_encoding: Encoding,
+    /// Default line-ending style for new documents
+    pub default_line_ending: LineEnding,
+}
+
+/// Maximum number of entries kept in the recent-files list.
+const RECENT_FILES_MAX: usize = 10;
+
+/// Character encoding of a document, detected on load and overridable via the
+/// menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// UTF-8 (with or without a BOM).
+    Utf8,
+    /// UTF-16, little-endian.
+    Utf16Le,
+    /// UTF-16, big-endian.
+    Utf16Be,
+}
+
+impl Encoding {
+    /// Detect the encoding of a freshly loaded buffer: a leading BOM is
+    /// authoritative, otherwise a NUL-byte heuristic distinguishes UTF-16 from
+    /// UTF-8, defaulting to UTF-8.
+    pub fn detect(bytes: &[u8]) -> Self {
+        if bytes.starts_with(&[0xEF, 0xBB, 0xBF]) {
+            Encoding::Utf8
+        } else if bytes.starts_with(&[0xFF, 0xFE]) {
+            Encoding::Utf16Le
+        } else if bytes.starts_with(&[0xFE, 0xFF]) {
+            Encoding::Utf16Be
+        } else {
+            // Without a BOM, look at NUL placement in the leading window:
+            // UTF-16 ASCII text has a NUL in every other byte.
+            let window = &bytes[..bytes.len().min(256)];
+            let even_nuls = window.iter().step_by(2).filter(|&&b| b == 0).count();
+            let odd_nuls = window.iter().skip(1).step_by(2).filter(|&&b| b == 0).count();
+            if odd_nuls > window.len() / 4 {
+                Encoding::Utf16Le
+            } else if even_nuls > window.len() / 4 {
+                Encoding::Utf16Be
+            } else {
+                Encoding::Utf8
+            }
+        }
+    }
+
+    /// Parse an encoding from its persisted name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "utf-8" => Some(Encoding::Utf8),
+            "utf-16le" => Some(Encoding::Utf16Le),
+            "utf-16be" => Some(Encoding::Utf16Be),
+            _ => None,
+        }
+    }
+
+    /// The persisted name of this encoding.
+    pub fn name(self) -> &'static str {
+        match self {
+            Encoding::Utf8 => "utf-8",
+            Encoding::Utf16Le => "utf-16le",
+            Encoding::Utf16Be => "utf-16be",
+        }
+    }
+}
+
+/// Line-ending style of a document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    /// `\n` (Unix).
+    Lf,
+    /// `\r\n` (Windows).
+    Crlf,
+    /// `\r` (classic Mac).
+    Cr,
+}
+
+impl LineEnding {
+    /// Detect the line-ending style from the first line break found.
+    pub fn detect(bytes: &[u8]) -> Self {
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'\n' => return LineEnding::Lf,
+                b'\r' => {
+                    return if bytes.get(i + 1) == Some(&b'\n') {
+                        LineEnding::Crlf
+                    } else {
+                        LineEnding::Cr
+                    };
+                }
+                _ => i += 1,
+            }
+        }
+        LineEnding::Lf
+    }
+
+    /// Parse a line ending from its persisted name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "lf" => Some(LineEnding::Lf),
+            "crlf" => Some(LineEnding::Crlf),
+            "cr" => Some(LineEnding::Cr),
+            _ => None,
+        }
+    }
+
+    /// The persisted name of this line ending.
+    pub fn name(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "lf",
+            LineEnding::Crlf => "crlf",
+            LineEnding::Cr => "cr",
+        }
+    }
+
+    /// The byte sequence written for this line ending.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::Crlf => "\r\n",
+            LineEnding::Cr => "\r",
+        }
+    }
 }
 
 impl Default for Settings {
@@ -34,10 +165,70 @@ impl Default for Settings {
             selection_color: None,
             line_number_color: None,
             line_separator_color: None,
+            theme_name: None,
+            recent_files: Vec::new(),
+            default_encoding: Encoding::Utf8,
+            default_line_ending: LineEnding::Lf,
+        }
+    }
+}
+
+/// A non-fatal problem found while parsing `settings.json`, located precisely
+/// enough for the editor to show a warning instead of silently reverting the
+/// offending field to its default.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettingsDiagnostic {
+    /// Byte offset into the source where the problem starts.
+    pub offset: usize,
+    /// 1-based line number derived from `offset`.
+    pub line: usize,
+    /// 1-based column derived from `offset`.
+    pub column: usize,
+    /// The setting key the problem concerns, or empty for whole-document errors.
+    pub key: String,
+    /// What went wrong.
+    pub message: SettingsMessage,
+}
+
+/// The kind of problem a [`SettingsDiagnostic`] reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingsMessage {
+    /// A key that this version of the editor does not recognize.
+    UnknownKey,
+    /// A color value that is neither valid hex nor a valid `oklch`/`oklab`.
+    MalformedColor,
+    /// An enum-valued key (encoding, line ending) with an unrecognized value.
+    UnknownValue,
+    /// The document as a whole was not valid JSON.
+    InvalidJson,
+}
+
+impl SettingsMessage {
+    /// A short human-readable description, in the same register as the lexer's
+    /// diagnostics.
+    pub fn text(self) -> &'static str {
+        match self {
+            SettingsMessage::UnknownKey => "unknown setting",
+            SettingsMessage::MalformedColor => "malformed color value",
+            SettingsMessage::UnknownValue => "unrecognized value",
+            SettingsMessage::InvalidJson => "invalid JSON",
         }
     }
 }
 
+/// The setting keys this version understands; anything else is reported as
+/// [`SettingsMessage::UnknownKey`].
+const KNOWN_KEYS: &[&str] = &[
+    "titlebar_color",
+    "selection_color",
+    "line_number_color",
+    "line_separator_color",
+    "theme_name",
+    "recent_files",
+    "default_encoding",
+    "default_line_ending",
+];
+
 impl Settings {
     /// Load settings from the config file
     pub fn load() -> apperr::Result<Self> {
@@ -52,45 +243,103 @@ impl Settings {
         Self::parse(&contents)
     }
 
-    /// Parse settings from JSON string
+    /// Parse settings from JSON string, discarding any diagnostics. This is the
+    /// load path: a malformed field reverts to its default.
     fn parse(json_str: &str) -> apperr::Result<Self> {
+        Ok(Self::parse_with_diagnostics(json_str).0)
+    }
+
+    /// Parse settings, additionally returning a [`SettingsDiagnostic`] for every
+    /// key that was ignored: an unknown key, a color that failed to parse, an
+    /// unrecognized enum value, or — for the whole document — invalid JSON.
+    ///
+    /// Each diagnostic points at the real location in `json_str` so the editor
+    /// can surface a warning non-fatally rather than discarding the user's
+    /// intent. Offsets come from a light top-level key scan rather than the
+    /// value parser, which does not retain source positions.
+    pub fn parse_with_diagnostics(json_str: &str) -> (Self, Vec<SettingsDiagnostic>) {
         let mut settings = Self::default();
+        let mut diags = Vec::new();
         let arena = scratch_arena(None);
 
-        match json::parse(&arena, json_str) {
-            Ok(root) => {
-                if let Some(obj) = root.as_object() {
-                    // Parse titlebar_color if present
-                    if let Some(color_str) = obj.get_str("titlebar_color") {
-                        settings.titlebar_color = Self::parse_color(color_str);
-                    }
-                    // Parse selection_color if present
-                    if let Some(color_str) = obj.get_str("selection_color") {
-                        settings.selection_color = Self::parse_color(color_str);
-                    }
-                    // Parse line_number_color if present
-                    if let Some(color_str) = obj.get_str("line_number_color") {
-                        settings.line_number_color = Self::parse_color(color_str);
-                    }
-                    // Parse line_separator_color if present
-                    if let Some(color_str) = obj.get_str("line_separator_color") {
-                        settings.line_separator_color = Self::parse_color(color_str);
-                    }
+        let offsets = scan_top_level_keys(json_str);
+        let offset_of = |key: &str| offsets.iter().find(|(k, _)| k == key).map(|(_, o)| *o);
+        let mut diag = |key: &str, message: SettingsMessage| {
+            let offset = offset_of(key).unwrap_or(0);
+            let (line, column) = line_col(json_str, offset);
+            diags.push(SettingsDiagnostic { offset, line, column, key: key.to_string(), message });
+        };
+
+        let root = match json::parse(&arena, json_str) {
+            Ok(root) => root,
+            Err(_err) => {
+                diag("", SettingsMessage::InvalidJson);
+                return (settings, diags);
+            }
+        };
+
+        let Some(obj) = root.as_object() else {
+            return (settings, diags);
+        };
+
+        // Report keys the editor does not recognize before reading the rest.
+        for (key, _) in &offsets {
+            if !KNOWN_KEYS.contains(&key.as_str()) {
+                diag(key, SettingsMessage::UnknownKey);
+            }
+        }
+
+        // Colors: a present-but-unparseable value is a diagnostic, not a
+        // silent fallback to the default.
+        for (key, slot) in [
+            ("titlebar_color", &mut settings.titlebar_color),
+            ("selection_color", &mut settings.selection_color),
+            ("line_number_color", &mut settings.line_number_color),
+            ("line_separator_color", &mut settings.line_separator_color),
+        ] {
+            if let Some(color_str) = obj.get_str(key) {
+                match Self::parse_color(color_str) {
+                    Some(color) => *slot = Some(color),
+                    None => diag(key, SettingsMessage::MalformedColor),
                 }
             }
-            Err(_err) => {
-                // Ignore parse errors and return default settings
+        }
+
+        if let Some(name) = obj.get_str("theme_name") {
+            settings.theme_name = Some(name.to_string());
+        }
+        // Parse recent_files (stored as a newline-separated list so it
+        // round-trips through the minimal string-only JSON helper)
+        if let Some(list) = obj.get_str("recent_files") {
+            settings.recent_files = list
+                .split('\n')
+                .filter(|p| !p.is_empty())
+                .map(|p| p.to_string())
+                .take(RECENT_FILES_MAX)
+                .collect();
+        }
+        if let Some(name) = obj.get_str("default_encoding") {
+            match Encoding::from_name(name) {
+                Some(enc) => settings.default_encoding = enc,
+                None => diag("default_encoding", SettingsMessage::UnknownValue),
+            }
+        }
+        if let Some(name) = obj.get_str("default_line_ending") {
+            match LineEnding::from_name(name) {
+                Some(le) => settings.default_line_ending = le,
+                None => diag("default_line_ending", SettingsMessage::UnknownValue),
             }
         }
 
-        Ok(settings)
+        (settings, diags)
     }
 
-    /// Parse color from hex string (e.g., "#RRGGBB" or "#RRGGBBAA")
+    /// Parse a color from either a `#RRGGBB[AA]` hex string or the perceptual
+    /// `oklch(L C H [/ A])` / `oklab(L a b [/ A])` function syntax.
     fn parse_color(s: &str) -> Option<StraightRgba> {
         let s = s.trim();
         if !s.starts_with('#') {
-            return None;
+            return parse_oklab_color(s);
         }
 
         let hex = &s[1..];
@@ -166,6 +415,31 @@ impl Settings {
             first = false;
         }
 
+        if let Some(name) = &self.theme_name {
+            if !first {
+                json.push_str(",\n");
+            }
+            json.push_str(&format!("  \"theme_name\": \"{}\"", name));
+            first = false;
+        }
+
+        if !self.recent_files.is_empty() {
+            if !first {
+                json.push_str(",\n");
+            }
+            let joined = self.recent_files.join("\n");
+            json.push_str(&format!("  \"recent_files\": \"{}\"", Self::json_escape(&joined)));
+            first = false;
+        }
+
+        if !first {
+            json.push_str(",\n");
+        }
+        json.push_str(&format!("  \"default_encoding\": \"{}\"", self.default_encoding.name()));
+        json.push_str(",\n");
+        json.push_str(&format!("  \"default_line_ending\": \"{}\"", self.default_line_ending.name()));
+        first = false;
+
         if !first {
             json.push('\n');
         }
@@ -187,6 +461,36 @@ impl Settings {
         }
     }
 
+    /// Escape a string for embedding in a JSON string literal (quotes,
+    /// backslashes — common in Windows paths — and newlines).
+    fn json_escape(s: &str) -> String {
+        let mut out = String::with_capacity(s.len());
+        for c in s.chars() {
+            match c {
+                '"' => out.push_str("\\\""),
+                '\\' => out.push_str("\\\\"),
+                '\n' => out.push_str("\\n"),
+                '\r' => out.push_str("\\r"),
+                '\t' => out.push_str("\\t"),
+                _ => out.push(c),
+            }
+        }
+        out
+    }
+
+    /// Record `path` as the most-recently-opened file: move it to the front,
+    /// drop any earlier duplicate, and cap the list at [`RECENT_FILES_MAX`].
+    pub fn push_recent(&mut self, path: &str) {
+        self.recent_files.retain(|p| p != path);
+        self.recent_files.insert(0, path.to_string());
+        self.recent_files.truncate(RECENT_FILES_MAX);
+    }
+
+    /// Clear the recent-files list.
+    pub fn clear_recent(&mut self) {
+        self.recent_files.clear();
+    }
+
     /// Public wrapper for color_to_hex
     pub fn color_to_hex_pub(color: StraightRgba) -> String {
         Self::color_to_hex(color)
@@ -197,6 +501,18 @@ impl Settings {
         Self::parse_color(s)
     }
 
+    /// Serialize `color` as `oklch(L C H)` (with a trailing `/ A` when not
+    /// fully opaque), the perceptual inverse of [`parse_color`]. Lets a saved
+    /// theme round-trip through the same OKLCH space users author in.
+    pub fn color_to_oklch(color: StraightRgba) -> String {
+        let (l, c, h) = rgba_to_oklch(color);
+        if color.alpha() == 255 {
+            format!("oklch({:.4} {:.4} {:.2})", l, c, h)
+        } else {
+            format!("oklch({:.4} {:.4} {:.2} / {:.4})", l, c, h, color.alpha() as f32 / 255.0)
+        }
+    }
+
     /// Get the path to the config file
     fn config_path() -> apperr::Result<PathBuf> {
         #[cfg(target_os = "windows")]
@@ -225,6 +541,179 @@ impl Settings {
     }
 }
 
+/// Parse the CSS Color 4 `oklch(...)` / `oklab(...)` function forms into a
+/// gamut-clipped [`StraightRgba`]. `L` accepts either a `0..1` number or a
+/// percentage; for `oklch` the chroma is `0..~0.4` and the hue is in degrees.
+/// An optional `/ alpha` component follows the same number-or-percentage rule.
+fn parse_oklab_color(s: &str) -> Option<StraightRgba> {
+    let (is_lch, rest) = if let Some(r) = s.strip_prefix("oklch(") {
+        (true, r)
+    } else if let Some(r) = s.strip_prefix("oklab(") {
+        (false, r)
+    } else {
+        return None;
+    };
+    let inner = rest.strip_suffix(')')?.trim();
+
+    // Split off an optional `/ alpha` component.
+    let (coords, alpha) = match inner.split_once('/') {
+        Some((c, a)) => (c, parse_ratio(a.trim())?),
+        None => (inner, 1.0),
+    };
+
+    let mut parts = coords.split_whitespace();
+    let l = parse_ratio(parts.next()?)?;
+    let x = parts.next()?.trim().parse::<f32>().ok()?;
+    let y = parts.next()?.trim().parse::<f32>().ok()?;
+    if parts.next().is_some() {
+        return None;
+    }
+
+    let (a, b) = if is_lch {
+        let hue = y.to_radians();
+        (x * hue.cos(), x * hue.sin())
+    } else {
+        (x, y)
+    };
+
+    let (r, g, bl) = oklab_to_srgb8(l, a, b);
+    let alpha = (alpha.clamp(0.0, 1.0) * 255.0).round() as u32;
+    Some(StraightRgba::from_le(r | (g << 8) | (bl << 16) | (alpha << 24)))
+}
+
+/// Parse a component that may be either a bare `0..1` ratio or a percentage.
+fn parse_ratio(s: &str) -> Option<f32> {
+    match s.strip_suffix('%') {
+        Some(p) => p.trim().parse::<f32>().ok().map(|v| v / 100.0),
+        None => s.parse::<f32>().ok(),
+    }
+}
+
+/// Convert OKLab `(L, a, b)` to gamut-clipped 8-bit sRGB channels.
+fn oklab_to_srgb8(l: f32, a: f32, b: f32) -> (u32, u32, u32) {
+    let l_ = l + 0.396_337_78 * a + 0.215_803_76 * b;
+    let m_ = l - 0.105_561_346 * a - 0.063_854_17 * b;
+    let s_ = l - 0.089_484_18 * a - 1.291_485_5 * b;
+
+    let (l3, m3, s3) = (l_ * l_ * l_, m_ * m_ * m_, s_ * s_ * s_);
+
+    let lr = 4.076_741_7 * l3 - 3.307_711_6 * m3 + 0.230_969_94 * s3;
+    let lg = -1.268_438 * l3 + 2.609_757_4 * m3 - 0.341_319_38 * s3;
+    let lb = -0.004_196_086_3 * l3 - 0.703_418_6 * m3 + 1.707_614_7 * s3;
+
+    (encode_srgb(lr), encode_srgb(lg), encode_srgb(lb))
+}
+
+/// Convert OKLab back to OKLCH, sourcing the OKLab values from an sRGB color.
+fn rgba_to_oklch(color: StraightRgba) -> (f32, f32, f32) {
+    let r = decode_srgb(color.red());
+    let g = decode_srgb(color.green());
+    let b = decode_srgb(color.blue());
+
+    let l = 0.412_221_47 * r + 0.536_332_55 * g + 0.051_445_995 * b;
+    let m = 0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b;
+    let s = 0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b;
+
+    let (l_, m_, s_) = (l.cbrt(), m.cbrt(), s.cbrt());
+
+    let ll = 0.210_454_26 * l_ + 0.793_617_8 * m_ - 0.004_072_047 * s_;
+    let aa = 1.977_998_5 * l_ - 2.428_592_2 * m_ + 0.450_593_7 * s_;
+    let bb = 0.025_904_037 * l_ + 0.782_771_77 * m_ - 0.808_675_77 * s_;
+
+    let c = (aa * aa + bb * bb).sqrt();
+    let mut h = bb.atan2(aa).to_degrees();
+    if h < 0.0 {
+        h += 360.0;
+    }
+    (ll, c, h)
+}
+
+/// Encode a linear-light channel (clipped to `[0, 1]`) as an 8-bit sRGB value.
+fn encode_srgb(c: f32) -> u32 {
+    let c = c.clamp(0.0, 1.0);
+    let v = if c <= 0.003_130_8 { 12.92 * c } else { 1.055 * c.powf(1.0 / 2.4) - 0.055 };
+    (v * 255.0).round() as u32
+}
+
+/// Scan `src` for the object keys at brace depth 1, returning each key's name
+/// and the byte offset of its opening quote.
+///
+/// This is a deliberately small, dependency-free pass over the raw text: the
+/// value parser in [`json`] does not retain source positions, so locating a key
+/// for a diagnostic means finding it here. It tracks string state (so a `{`
+/// inside a value is not mistaken for nesting) and only records keys — the
+/// token immediately before a `:` — at the top level of the root object.
+fn scan_top_level_keys(src: &str) -> Vec<(String, usize)> {
+    let bytes = src.as_bytes();
+    let mut keys = Vec::new();
+    let mut depth = 0usize;
+    let mut i = 0;
+    let mut last_string: Option<(usize, String)> = None;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => {
+                let start = i;
+                i += 1;
+                let mut value = String::new();
+                while i < bytes.len() && bytes[i] != b'"' {
+                    if bytes[i] == b'\\' && i + 1 < bytes.len() {
+                        i += 1;
+                    }
+                    value.push(bytes[i] as char);
+                    i += 1;
+                }
+                i += 1; // closing quote
+                last_string = Some((start, value));
+            }
+            b'{' | b'[' => {
+                depth += 1;
+                i += 1;
+            }
+            b'}' | b']' => {
+                depth = depth.saturating_sub(1);
+                i += 1;
+            }
+            b':' => {
+                if depth == 1 {
+                    if let Some((start, name)) = last_string.take() {
+                        keys.push((name, start));
+                    }
+                }
+                i += 1;
+            }
+            b',' => {
+                last_string = None;
+                i += 1;
+            }
+            _ => i += 1,
+        }
+    }
+
+    keys
+}
+
+/// Derive a 1-based line and column from a byte offset into `src`.
+fn line_col(src: &str, offset: usize) -> (usize, usize) {
+    let mut line = 1;
+    let mut column = 1;
+    for &b in &src.as_bytes()[..offset.min(src.len())] {
+        if b == b'\n' {
+            line += 1;
+            column = 1;
+        } else {
+            column += 1;
+        }
+    }
+    (line, column)
+}
+
+/// Decode an 8-bit sRGB value to a linear-light channel in `[0, 1]`.
+fn decode_srgb(c: u8) -> f32 {
+    let c = c as f32 / 255.0;
+    if c <= 0.040_45 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -253,6 +742,37 @@ mod tests {
         assert!(Settings::parse_color("#FF").is_none());
     }
 
+    #[test]
+    fn test_push_recent_dedup_and_cap() {
+        let mut s = Settings::default();
+        for i in 0..12 {
+            s.push_recent(&format!("/file{i}"));
+        }
+        assert_eq!(s.recent_files.len(), RECENT_FILES_MAX);
+        assert_eq!(s.recent_files[0], "/file11");
+
+        // Re-opening an existing path moves it to the front without growing.
+        s.push_recent("/file5");
+        assert_eq!(s.recent_files[0], "/file5");
+        assert_eq!(s.recent_files.len(), RECENT_FILES_MAX);
+    }
+
+    #[test]
+    fn test_encoding_detect() {
+        assert_eq!(Encoding::detect(b"\xEF\xBB\xBFhello"), Encoding::Utf8);
+        assert_eq!(Encoding::detect(b"\xFF\xFEh\0i\0"), Encoding::Utf16Le);
+        assert_eq!(Encoding::detect(b"\xFE\xFF\0h\0i"), Encoding::Utf16Be);
+        assert_eq!(Encoding::detect(b"plain ascii"), Encoding::Utf8);
+    }
+
+    #[test]
+    fn test_line_ending_detect() {
+        assert_eq!(LineEnding::detect(b"a\nb"), LineEnding::Lf);
+        assert_eq!(LineEnding::detect(b"a\r\nb"), LineEnding::Crlf);
+        assert_eq!(LineEnding::detect(b"a\rb"), LineEnding::Cr);
+        assert_eq!(LineEnding::detect(b"no breaks"), LineEnding::Lf);
+    }
+
     #[test]
     fn test_color_to_hex() {
         let color = StraightRgba::from_le(255 | (0 << 8) | (0 << 16) | (255 << 24));
@@ -261,4 +781,44 @@ mod tests {
         let color = StraightRgba::from_le(0 | (255 << 8) | (0 << 16) | (128 << 24));
         assert_eq!(Settings::color_to_hex(color), "#00FF0080");
     }
+
+    #[test]
+    fn test_oklab_parse_white_and_black() {
+        let white = Settings::parse_color_pub("oklch(1 0 0)").unwrap();
+        assert_eq!((white.red(), white.green(), white.blue()), (255, 255, 255));
+        let black = Settings::parse_color_pub("oklab(0 0 0)").unwrap();
+        assert_eq!((black.red(), black.green(), black.blue()), (0, 0, 0));
+    }
+
+    #[test]
+    fn test_oklch_roundtrip() {
+        let orig = StraightRgba::from_le(0xFF_30_80_C0);
+        let serialized = Settings::color_to_oklch(orig);
+        let back = Settings::parse_color_pub(&serialized).unwrap();
+        // Perceptual round-trip is stable to within a quantization step.
+        let near = |a: u8, b: u8| (a as i32 - b as i32).abs() <= 1;
+        assert!(near(orig.red(), back.red()));
+        assert!(near(orig.green(), back.green()));
+        assert!(near(orig.blue(), back.blue()));
+    }
+
+    #[test]
+    fn test_oklch_percentage_and_alpha() {
+        let c = Settings::parse_color_pub("oklch(100% 0 0 / 50%)").unwrap();
+        assert_eq!((c.red(), c.green(), c.blue()), (255, 255, 255));
+        assert_eq!(c.alpha(), 128);
+    }
+
+    #[test]
+    fn test_settings_diagnostics() {
+        let src = "{\n  \"titlebar_color\": \"#zzzz\",\n  \"bogus\": \"x\",\n  \"default_encoding\": \"klingon\"\n}";
+        let (_settings, diags) = Settings::parse_with_diagnostics(src);
+
+        let by_key = |k: &str| diags.iter().find(|d| d.key == k);
+        assert_eq!(by_key("titlebar_color").unwrap().message, SettingsMessage::MalformedColor);
+        assert_eq!(by_key("bogus").unwrap().message, SettingsMessage::UnknownKey);
+        assert_eq!(by_key("default_encoding").unwrap().message, SettingsMessage::UnknownValue);
+        // The offset resolves to the reported line/column.
+        assert_eq!(by_key("bogus").unwrap().line, 3);
+    }
 }
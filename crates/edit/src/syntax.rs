@@ -13,13 +13,19 @@
 //! - **Themes**: Configurable color schemes for different token types
 //! - **Lazy Evaluation**: Only highlights visible portions of the document
 
+mod codemap;
+mod grammar;
 mod lexer;
+mod render;
 mod theme;
 mod token;
 
-pub use lexer::{Lexer, LexerRegistry, Language};
-pub use theme::{Theme, TokenStyle};
-pub use token::{Token, TokenKind, TokenSpan};
+pub use codemap::{CodeMap, LineIndex, Position, Span};
+pub use grammar::{Grammar, GrammarLexer, Pattern, Rule, StringRule};
+pub use lexer::{Diagnostic, Injection, Lexer, LexerRegistry, LexerState, LexMessage, Language, Severity};
+pub use render::{AnsiRenderer, ColorDepth};
+pub use theme::{BUILTIN_THEMES, HighlightId, HighlightMap, Theme, TokenStyle};
+pub use token::{CommentKind, Modifiers, Token, TokenFlags, TokenKind, TokenSpan, tokens_to_json};
 
 use std::ops::Range;
 
@@ -27,14 +33,39 @@ use std::ops::Range;
 pub struct SyntaxHighlighter {
     /// The language being highlighted
     language: Language,
+    /// The lexer that produces tokens. Usually derived from `language`, but a
+    /// runtime [`Grammar`] supplies its own via [`with_grammar`](Self::with_grammar).
+    lexer: Box<dyn Lexer>,
     /// Cached tokens for the document
     tokens: Vec<Token>,
+    /// Byte offset at which each document line begins, parallel to
+    /// [`line_states`](Self::line_states). Lets an edit map a byte range back to
+    /// line indices and locate the unchanged tail for reuse.
+    line_starts: Vec<usize>,
+    /// The [`LexerState`] at the end of each line, one entry per line. Seeded by
+    /// a full pass and patched in place by incremental re-lexing, it is what
+    /// lets [`update`](Self::update) restart lexing from a line boundary and
+    /// stop as soon as the state re-converges with the cache.
+    line_states: Vec<LexerState>,
     /// Dirty range that needs re-highlighting
     dirty_range: Option<Range<usize>>,
     /// The theme to use for coloring
     theme: Theme,
+    /// Precomputed `kind → style` table for `theme`, rebuilt only on
+    /// [`set_theme`](Self::set_theme) so the per-token hot path in
+    /// [`get_style_at`](Self::get_style_at) is a single array index rather
+    /// than re-resolving through [`Theme::get_style`] every call.
+    highlight_map: HighlightMap,
+    /// Diagnostics from the last tokenization, kept so editor UIs can query
+    /// them without re-lexing.
+    diagnostics: Vec<Diagnostic>,
     /// Document length at last tokenization
     doc_len: usize,
+    /// Number of lines re-lexed by the most recent [`update`](Self::update):
+    /// the whole document on a full pass, or just the rippled region on an
+    /// incremental one. Exposed for diagnostics and tests of the incremental
+    /// path.
+    relexed_lines: usize,
 }
 
 impl SyntaxHighlighter {
@@ -42,10 +73,37 @@ impl SyntaxHighlighter {
     pub fn new(language: Language, theme: Theme) -> Self {
         Self {
             language,
+            lexer: LexerRegistry::get_lexer(language),
             tokens: Vec::new(),
+            line_starts: Vec::new(),
+            line_states: Vec::new(),
             dirty_range: Some(0..usize::MAX),
+            highlight_map: HighlightMap::new(&theme),
             theme,
+            diagnostics: Vec::new(),
             doc_len: 0,
+            relexed_lines: 0,
+        }
+    }
+
+    /// Create a highlighter driven by a runtime [`Grammar`] rather than a
+    /// built-in [`Language`], so a user-supplied `.json` syntax definition can
+    /// highlight a language the crate was not compiled to know about. The
+    /// reported [`language`](Self::language) is [`Language::PlainText`], since
+    /// the grammar is not one of the built-in variants.
+    pub fn with_grammar(grammar: Grammar, theme: Theme) -> Self {
+        Self {
+            language: Language::PlainText,
+            lexer: Box::new(GrammarLexer::new(grammar)),
+            tokens: Vec::new(),
+            line_starts: Vec::new(),
+            line_states: Vec::new(),
+            dirty_range: Some(0..usize::MAX),
+            highlight_map: HighlightMap::new(&theme),
+            theme,
+            diagnostics: Vec::new(),
+            doc_len: 0,
+            relexed_lines: 0,
         }
     }
 
@@ -61,20 +119,246 @@ impl SyntaxHighlighter {
 
     /// Update the highlighting for the document.
     ///
-    /// This is an incremental operation that only re-tokenizes dirty regions.
+    /// The token cache is line-aware: alongside [`tokens`](Self::tokens) the
+    /// highlighter keeps the [`LexerState`] at the end of every line. When the
+    /// previous cache is still usable, an edit re-lexes only from the first
+    /// dirty line forward and stops as soon as a re-lexed line reproduces the
+    /// end-state that was already cached for that boundary — the "no further
+    /// ripple" fixpoint — after which the unchanged tail of tokens and states
+    /// is reused with byte offsets shifted by the edit's length delta. So an
+    /// edit confined to one line re-lexes O(edited lines), not the whole file;
+    /// an edit that opens or closes a multi-line construct ripples forward only
+    /// as far as the state keeps changing.
+    ///
+    /// [`tokenize_line`](Lexer::tokenize_line) is lossless — it always emits a
+    /// token for every byte — so a half-typed string or unbalanced brace can
+    /// never wedge the cache.
     pub fn update(&mut self, text: &[u8], force: bool) {
         if !force && self.dirty_range.is_none() && text.len() == self.doc_len {
             return;
         }
 
-        // For now, we re-tokenize the entire document.
-        // Future optimization: incremental tokenization.
-        let lexer = LexerRegistry::get_lexer(self.language);
-        self.tokens = lexer.tokenize(text);
+        if force || !self.try_relex(text) {
+            let (tokens, states) = self.lexer.tokenize_lines(text);
+            self.relexed_lines = states.len();
+            self.tokens = tokens;
+            self.line_states = states;
+            self.line_starts = lexer::line_spans(text).iter().map(|s| s.start).collect();
+        }
+
+        self.splice_injections(text);
+
+        self.diagnostics = self.lexer.diagnose(text);
         self.dirty_range = None;
         self.doc_len = text.len();
     }
 
+    /// Delegate each [`Injection`] the lexer reports for `text` to that
+    /// language's own lexer, splicing its tokens (offset into document
+    /// coordinates) into [`tokens`](Self::tokens) in place of whatever the
+    /// host lexer produced for that range.
+    ///
+    /// Run unconditionally on every [`update`](Self::update) — like
+    /// [`diagnose`](Lexer::diagnose), injection discovery is a full-document
+    /// scan rather than part of the incremental line cache, so it is simplest
+    /// to recompute it over the freshly assembled host tokens each time.
+    /// Injections are applied widest-first, so a narrower range nested inside
+    /// a wider one is spliced in afterward and therefore wins.
+    fn splice_injections(&mut self, text: &[u8]) {
+        let mut injections = self.lexer.injections(text);
+        if injections.is_empty() {
+            return;
+        }
+        injections.sort_by_key(|i| std::cmp::Reverse(i.range.end.saturating_sub(i.range.start)));
+
+        for injection in injections {
+            if injection.range.start >= injection.range.end || injection.range.end > text.len() {
+                continue;
+            }
+            let child = LexerRegistry::get_lexer(injection.language);
+            let child_tokens = child.tokenize(&text[injection.range.clone()]);
+            splice_tokens(&mut self.tokens, &injection.range, child_tokens);
+        }
+    }
+
+    /// Attempt a bounded, line-based re-lex for the current edit, patching
+    /// [`tokens`](Self::tokens)/[`line_states`](Self::line_states) in place and
+    /// returning `true` on success, or `false` (leaving the cache untouched) to
+    /// fall back to a full re-lex — no usable cache, a missing edit range, or a
+    /// prefix that no longer lines up with the cache.
+    fn try_relex(&mut self, text: &[u8]) -> bool {
+        let Some(edit) = self.dirty_range.clone() else {
+            return false;
+        };
+        if self.line_states.is_empty() || edit.start > text.len() {
+            return false;
+        }
+        // Old-coordinate offsets map to new-coordinate offsets by adding `delta`.
+        let delta = text.len() as isize - self.doc_len as isize;
+
+        let new_spans = lexer::line_spans(text);
+
+        // First line touched by the edit; lexing restarts at its boundary.
+        let first = new_spans
+            .iter()
+            .position(|l| l.end > edit.start)
+            .unwrap_or(new_spans.len() - 1);
+
+        // The prefix (lines before `first`) is byte-for-byte unchanged, so its
+        // tokens, line starts, and entry state all come straight from the cache.
+        // Bail if the cache does not actually line up there.
+        if self.line_starts.get(first) != Some(&new_spans[first].start)
+            || first > self.line_states.len()
+        {
+            return false;
+        }
+        let mut state = if first == 0 {
+            LexerState::Normal
+        } else {
+            self.line_states[first - 1]
+        };
+
+        let prefix_end = new_spans[first].start;
+        let prefix_idx = self.tokens.partition_point(|t| t.span.end <= prefix_end);
+        let mut tokens = self.tokens[..prefix_idx].to_vec();
+        let mut states = self.line_states[..first].to_vec();
+
+        // Re-lex forward from `first`, stopping at the ripple fixpoint.
+        let mut li = first;
+        while li < new_spans.len() {
+            let span = new_spans[li].clone();
+            let (mut toks, exit) = self.lexer.tokenize_line(&text[span.clone()], state);
+            for t in &mut toks {
+                t.span.start += span.start;
+                t.span.end += span.start;
+            }
+            tokens.extend(toks);
+            states.push(exit);
+            state = exit;
+            li += 1;
+
+            // Once we are past the edit, check whether this line boundary lands
+            // on an old line boundary with the same end-state. If so, the rest
+            // of the document is unchanged modulo the byte delta and can be
+            // spliced in directly.
+            if span.end > edit.end {
+                let old_end = span.end as isize - delta;
+                if old_end >= 0 {
+                    let old_end = old_end as usize;
+                    if let Ok(p) = self.line_starts.binary_search(&old_end) {
+                        let state_matches = if p == 0 {
+                            exit == LexerState::Normal
+                        } else {
+                            self.line_states.get(p - 1) == Some(&exit)
+                        };
+                        if state_matches {
+                            let tail_idx = self.tokens.partition_point(|t| t.span.start < old_end);
+                            for token in &self.tokens[tail_idx..] {
+                                let mut shifted = token.clone();
+                                shifted.span.start = (token.span.start as isize + delta) as usize;
+                                shifted.span.end = (token.span.end as isize + delta) as usize;
+                                tokens.push(shifted);
+                            }
+                            states.extend_from_slice(&self.line_states[p..]);
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        self.relexed_lines = li - first;
+        self.tokens = tokens;
+        self.line_states = states;
+        self.line_starts = new_spans.iter().map(|s| s.start).collect();
+        true
+    }
+
+    /// Lex only as much of the document as is needed to cover `viewport`,
+    /// extended outward to line boundaries, instead of the whole file.
+    ///
+    /// Unlike [`update`](Self::update), this does not guarantee the entire
+    /// document is tokenized afterward: [`tokenized_end`](Self::tokenized_end)
+    /// reports the prefix `[0, end)` that is actually covered, and
+    /// [`get_style_at`]/[`get_tokens_in_range`](Self::get_tokens_in_range)
+    /// simply see no tokens past it. The cache grows as a single prefix that
+    /// only ever extends forward, reusing the cached [`LexerState`] at its
+    /// edge — so scrolling forward through a multi-megabyte file costs only
+    /// the newly revealed lines, not a re-lex from byte 0, each time the
+    /// viewport advances. A first call whose viewport starts deep past the
+    /// current frontier (e.g. opening a file already scrolled far down, or a
+    /// scrollbar drag) still has to lex everything up to that point, because
+    /// the lexer's state genuinely depends on everything before it — there is
+    /// no way to resume state we have never computed. Diagnostics and
+    /// injections are not recomputed here; call [`update`](Self::update) for
+    /// those once the whole document needs to be current.
+    pub fn update_range(&mut self, text: &[u8], viewport: Range<usize>) {
+        if self.dirty_range.is_some() || text.len() != self.doc_len {
+            self.tokens.clear();
+            self.line_states.clear();
+            self.line_starts.clear();
+            self.dirty_range = None;
+            self.doc_len = text.len();
+            self.relexed_lines = 0;
+        }
+
+        let spans = lexer::line_spans(text);
+        self.line_starts = spans.iter().map(|s| s.start).collect();
+        if spans.is_empty() {
+            return;
+        }
+
+        let viewport_end = viewport.end.min(text.len());
+        let last_line = spans
+            .iter()
+            .position(|s| s.end > viewport_end)
+            .unwrap_or(spans.len() - 1);
+
+        let resume_line = self.line_states.len();
+        if resume_line > last_line {
+            return; // Already covers the requested viewport.
+        }
+
+        let mut state = if resume_line == 0 {
+            LexerState::Normal
+        } else {
+            self.line_states[resume_line - 1]
+        };
+
+        for span in &spans[resume_line..=last_line] {
+            let (mut toks, exit) = self.lexer.tokenize_line(&text[span.clone()], state);
+            for t in &mut toks {
+                t.span.start += span.start;
+                t.span.end += span.start;
+            }
+            self.tokens.extend(toks);
+            self.line_states.push(exit);
+            state = exit;
+        }
+        self.relexed_lines = last_line + 1 - resume_line;
+    }
+
+    /// The end of the longest prefix `[0, end)` of the document that has been
+    /// tokenized so far. Always `doc_len` after [`update`](Self::update); after
+    /// [`update_range`](Self::update_range) it is only as far as the viewports
+    /// passed so far have required. A renderer can paint `[0, end)` with real
+    /// styles and anything past it as plain text until scrolling brings it
+    /// into view.
+    pub fn tokenized_end(&self) -> usize {
+        if self.line_states.len() >= self.line_starts.len() {
+            self.doc_len
+        } else {
+            self.line_starts[self.line_states.len()]
+        }
+    }
+
+    /// Whether every byte of `range` has already been tokenized, i.e. is safe
+    /// to query through [`get_style_at`]/[`get_tokens_in_range`](Self::get_tokens_in_range)
+    /// for real highlighting rather than the as-yet-unlexed default.
+    pub fn is_tokenized(&self, range: Range<usize>) -> bool {
+        range.end <= self.tokenized_end()
+    }
+
     /// Get the style for a given byte offset in the document.
     pub fn get_style_at(&self, offset: usize) -> Option<TokenStyle> {
         // Binary search for the token containing this offset
@@ -89,7 +373,18 @@ impl SyntaxHighlighter {
         });
 
         match idx {
-            Ok(i) => Some(self.theme.get_style(self.tokens[i].kind)),
+            Ok(i) => {
+                let token = &self.tokens[i];
+                // The common case — no modifiers, no scope stack — resolves
+                // through the precomputed map in one array index; only the
+                // rarer semantically-qualified tokens pay for the full
+                // Theme::get_style_for_token resolution.
+                if token.modifiers.is_empty() && token.scopes.is_empty() {
+                    Some(self.highlight_map.style(HighlightId::from_kind(token.kind)))
+                } else {
+                    Some(self.theme.get_style_for_token(token))
+                }
+            }
             Err(_) => None,
         }
     }
@@ -101,13 +396,33 @@ impl SyntaxHighlighter {
         &self.tokens[start_idx..end_idx]
     }
 
+    /// Get the diagnostics overlapping the given byte range, so an editor can
+    /// underline just the problems on screen.
+    pub fn diagnostics_in_range(&self, range: Range<usize>) -> Vec<&Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.span.start < range.end && d.span.end > range.start)
+            .collect()
+    }
+
+    /// Get the comment spans overlapping the given byte range, each paired with
+    /// its [`CommentKind`]. Consumers can build folding regions or extract
+    /// documentation from this without re-scanning the text.
+    pub fn comments_in_range(&self, range: Range<usize>) -> Vec<(Range<usize>, CommentKind)> {
+        self.get_tokens_in_range(range)
+            .iter()
+            .filter_map(|t| t.kind.comment_kind().map(|k| (t.span.clone(), k)))
+            .collect()
+    }
+
     /// Get the theme.
     pub fn theme(&self) -> &Theme {
         &self.theme
     }
 
-    /// Set a new theme.
+    /// Set a new theme, rebuilding the [`HighlightMap`] fast path to match.
     pub fn set_theme(&mut self, theme: Theme) {
+        self.highlight_map = HighlightMap::new(&theme);
         self.theme = theme;
     }
 
@@ -115,12 +430,82 @@ impl SyntaxHighlighter {
     pub fn language(&self) -> Language {
         self.language
     }
+
+    /// Number of lines the most recent [`update`](Self::update) re-lexed: the
+    /// whole document after a full pass, or only the rippled region after an
+    /// incremental one. Useful for profiling the incremental cache.
+    pub fn relexed_lines(&self) -> usize {
+        self.relexed_lines
+    }
+}
+
+/// Replace the tokens in `tokens` (sorted by span) that fall within `range`
+/// with `child_tokens`, offsetting each child span by `range.start`.
+///
+/// A host token that only partially overlaps `range` is trimmed to the
+/// portion outside it rather than dropped outright, so the non-injected gap
+/// it also covers keeps a token — e.g. the Markdown fence markers around an
+/// injected code block stay highlighted as `MarkdownCode` even though the
+/// body between them is replaced.
+fn splice_tokens(tokens: &mut Vec<Token>, range: &Range<usize>, child_tokens: Vec<Token>) {
+    let mut spliced = Vec::with_capacity(tokens.len() + child_tokens.len());
+    let mut children = Some(child_tokens.into_iter().map(|mut t| {
+        t.span.start += range.start;
+        t.span.end += range.start;
+        t
+    }));
+
+    for token in tokens.drain(..) {
+        if token.span.end <= range.start {
+            spliced.push(token);
+            continue;
+        }
+        if token.span.start >= range.end {
+            if let Some(c) = children.take() {
+                spliced.extend(c);
+            }
+            spliced.push(token);
+            continue;
+        }
+        if token.span.start < range.start {
+            let mut head = token.clone();
+            head.span.end = range.start;
+            spliced.push(head);
+        }
+        if let Some(c) = children.take() {
+            spliced.extend(c);
+        }
+        if token.span.end > range.end {
+            let mut tail = token;
+            tail.span.start = range.end;
+            spliced.push(tail);
+        }
+    }
+    if let Some(c) = children.take() {
+        spliced.extend(c);
+    }
+    *tokens = spliced;
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_get_style_at_matches_highlight_map_after_set_theme() {
+        let mut hl = SyntaxHighlighter::new(Language::Rust, Theme::default_dark());
+        hl.update(b"let x = 1;", true);
+
+        let dark_style = hl.get_style_at(0).unwrap();
+        assert_eq!(dark_style, hl.theme.get_style(TokenKind::KeywordStorage));
+
+        // Switching themes must rebuild the HighlightMap, not just `theme`.
+        hl.set_theme(Theme::default_light());
+        let light_style = hl.get_style_at(0).unwrap();
+        assert_eq!(light_style, hl.theme.get_style(TokenKind::KeywordStorage));
+        assert_ne!(dark_style, light_style);
+    }
+
     #[test]
     fn test_highlighter_basic() {
         let theme = Theme::default();
@@ -131,4 +516,112 @@ mod tests {
         
         assert!(!highlighter.tokens.is_empty());
     }
+
+    #[test]
+    fn test_incremental_update_matches_full() {
+        let mut hl = SyntaxHighlighter::new(Language::Rust, Theme::default());
+        hl.update(b"let x = 1;\nlet y = 2;\n", true);
+
+        // Edit the first line, then splice incrementally.
+        let edited = b"let xx = 1;\nlet y = 2;\n";
+        hl.mark_dirty(4..6);
+        hl.update(edited, false);
+
+        // The spliced result must equal a from-scratch tokenization.
+        let expected = LexerRegistry::get_lexer(Language::Rust).tokenize(edited);
+        assert_eq!(hl.tokens, expected);
+    }
+
+    #[test]
+    fn test_single_line_edit_relexes_bounded_lines() {
+        // A long JSON document: one `"kNN": N,` entry per line.
+        let mut doc = String::from("{\n");
+        for i in 0..200 {
+            doc.push_str(&format!("  \"k{i}\": {i},\n"));
+        }
+        doc.push_str("}\n");
+        let mut hl = SyntaxHighlighter::new(Language::Json, Theme::default());
+        hl.update(doc.as_bytes(), true);
+        assert_eq!(hl.relexed_lines, hl.line_states.len());
+
+        // Change a single value deep in the middle of the document.
+        let needle = "\"k100\": 100,";
+        let at = doc.find(needle).unwrap() + needle.len() - 1; // the trailing comma's value
+        let edited = doc.replacen("\"k100\": 100,", "\"k100\": 999,", 1);
+        hl.mark_dirty(at..at + 1);
+        hl.update(edited.as_bytes(), false);
+
+        // Only a bounded number of lines were re-lexed — not the whole file.
+        assert!(hl.relexed_lines <= 2, "re-lexed {} lines", hl.relexed_lines);
+
+        // And the spliced cache matches a from-scratch line-based tokenization.
+        let (expected, _) = LexerRegistry::get_lexer(Language::Json).tokenize_lines(edited.as_bytes());
+        assert_eq!(hl.tokens, expected);
+    }
+
+    #[test]
+    fn test_comments_in_range_classifies_doc() {
+        let mut hl = SyntaxHighlighter::new(Language::Rust, Theme::default());
+        let text = b"/// doc\n// plain\n/* block */ fn f() {}";
+        hl.update(text, true);
+
+        let comments = hl.comments_in_range(0..text.len());
+        let kinds: Vec<_> = comments.iter().map(|(_, k)| *k).collect();
+        assert_eq!(kinds, [CommentKind::Doc, CommentKind::Line, CommentKind::Block]);
+    }
+
+    #[test]
+    fn test_update_range_only_tokenizes_the_viewport() {
+        let mut doc = String::new();
+        for i in 0..200 {
+            doc.push_str(&format!("let k{i} = {i};\n"));
+        }
+        let mut hl = SyntaxHighlighter::new(Language::Rust, Theme::default());
+
+        // Only ask for the first few lines, as if rendering the top of a huge file.
+        hl.update_range(doc.as_bytes(), 0..30);
+        assert!(hl.tokenized_end() < doc.len());
+        assert!(hl.is_tokenized(0..10));
+        assert!(!hl.is_tokenized(0..doc.len()));
+        assert_eq!(hl.get_style_at(doc.len() - 2), None);
+
+        // Scrolling forward extends the cache from where it left off rather
+        // than starting over: it only re-lexes the newly revealed lines.
+        let previous_end = hl.tokenized_end();
+        hl.update_range(doc.as_bytes(), 200..260);
+        assert!(hl.relexed_lines < 20);
+        assert!(hl.tokenized_end() > previous_end);
+
+        // Asking for a viewport already covered by the cache is a no-op.
+        let covered_end = hl.tokenized_end();
+        hl.update_range(doc.as_bytes(), 0..10);
+        assert_eq!(hl.tokenized_end(), covered_end);
+    }
+
+    #[test]
+    fn test_update_range_matches_full_tokenization_once_doc_is_covered() {
+        let mut hl = SyntaxHighlighter::new(Language::Json, Theme::default());
+        let text = br#"{"a": 1, "b": [true, null]}"#;
+        hl.update_range(text, 0..text.len());
+        assert_eq!(hl.tokenized_end(), text.len());
+
+        let expected = LexerRegistry::get_lexer(Language::Json).tokenize(text);
+        assert_eq!(hl.tokens, expected);
+    }
+
+    #[test]
+    fn test_markdown_fence_injects_rust_tokens() {
+        let mut hl = SyntaxHighlighter::new(Language::Markdown, Theme::default());
+        let text = b"# Title\n```rust\nfn main() {}\n```\n";
+        hl.update(text, true);
+
+        // The fence body is tokenized as Rust (a `KeywordFunction` for `fn`)...
+        let body_start = text.windows(4).position(|w| w == b"rust").unwrap() + 5;
+        let fn_tok = hl.tokens.iter().find(|t| t.span.start == body_start).unwrap();
+        assert_eq!(fn_tok.kind, TokenKind::KeywordFunction);
+
+        // ...while the opening fence (outside the injected range) keeps its
+        // original Markdown token.
+        assert!(hl.tokens.iter().any(|t| t.kind == TokenKind::MarkdownCode && t.span.start == 7 + 1));
+    }
 }
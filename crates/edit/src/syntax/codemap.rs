@@ -0,0 +1,140 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Line/column resolution over byte-offset tokens.
+//!
+//! [`Token`](crate::syntax::Token)s carry only a byte `Range`, which keeps the
+//! tokenize loop a pure byte scan. Consumers that need human-facing positions
+//! — error messages, gutter rendering, jump-to-position — would otherwise
+//! rescan the buffer counting newlines for every lookup. A [`CodeMap`] records
+//! each line's start offset once so any byte offset resolves to `(line, col)`
+//! in `O(log lines)`.
+
+use crate::syntax::Token;
+use std::ops::Range;
+
+/// A zero-based line/column position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    /// Zero-based line number.
+    pub line: u32,
+    /// Zero-based column, measured in bytes from the line start.
+    pub col: u32,
+}
+
+/// A line/column range, the resolved form of a byte `Range`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    /// Start position (inclusive).
+    pub start: Position,
+    /// End position (exclusive).
+    pub end: Position,
+}
+
+/// An index of line-start byte offsets, built once per buffer.
+pub struct CodeMap {
+    /// Byte offset of the first byte of each line. Always starts with `0`.
+    line_starts: Vec<u32>,
+    /// Total length of the indexed buffer, so offsets past the last newline
+    /// still resolve.
+    len: u32,
+}
+
+/// A [`LineIndex`] is another name for a [`CodeMap`]; the two are kept as an
+/// alias so either vocabulary reads naturally at a call site.
+pub type LineIndex = CodeMap;
+
+impl CodeMap {
+    /// Build a code map from the source bytes in a single pass.
+    ///
+    /// `\r\n` is handled the same as `\n`: the line break is owned by the
+    /// `\n`, so the `\r` does not start a line of its own.
+    pub fn new(text: &[u8]) -> Self {
+        let mut line_starts = Vec::with_capacity(text.len() / 32 + 1);
+        line_starts.push(0);
+        for (i, &b) in text.iter().enumerate() {
+            if b == b'\n' {
+                line_starts.push(i as u32 + 1);
+            }
+        }
+        Self { line_starts, len: text.len() as u32 }
+    }
+
+    /// Resolve a byte offset to a zero-based `(line, col)` pair.
+    ///
+    /// Offsets beyond the end of the buffer clamp to the final line.
+    pub fn line_col(&self, offset: usize) -> (u32, u32) {
+        let offset = (offset as u32).min(self.len);
+        // `partition_point` finds the first line start strictly greater than
+        // `offset`; the line containing `offset` is the one before it.
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let col = offset - self.line_starts[line];
+        (line as u32, col)
+    }
+
+    /// Resolve a byte offset to a zero-based `(line, col)` pair, like
+    /// [`line_col`](Self::line_col) but named to read well where a
+    /// [`LineIndex`] is the vocabulary (`index.position(byte)`).
+    pub fn position(&self, byte: usize) -> (u32, u32) {
+        self.line_col(byte)
+    }
+
+    /// Resolve a [`Token`]'s byte span to its start and end [`Position`]s.
+    pub fn token_range(&self, tok: &Token) -> (Position, Position) {
+        let span = self.byte_range_to_span(tok.span.clone());
+        (span.start, span.end)
+    }
+
+    /// Resolve a byte range to a line/column [`Span`].
+    pub fn byte_range_to_span(&self, r: Range<usize>) -> Span {
+        let (sl, sc) = self.line_col(r.start);
+        let (el, ec) = self.line_col(r.end);
+        Span { start: Position { line: sl, col: sc }, end: Position { line: el, col: ec } }
+    }
+
+    /// Number of lines in the indexed buffer.
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_col_basic() {
+        let map = CodeMap::new(b"ab\ncde\nf");
+        assert_eq!(map.line_col(0), (0, 0));
+        assert_eq!(map.line_col(2), (0, 2)); // the '\n' itself
+        assert_eq!(map.line_col(3), (1, 0));
+        assert_eq!(map.line_col(7), (2, 0));
+    }
+
+    #[test]
+    fn test_crlf_does_not_add_a_line() {
+        let map = CodeMap::new(b"a\r\nb");
+        assert_eq!(map.line_count(), 2);
+        assert_eq!(map.line_col(3), (1, 0));
+    }
+
+    #[test]
+    fn test_token_range_start_and_end() {
+        use crate::syntax::{Token, TokenKind};
+        let map = CodeMap::new(b"let x =\n  42");
+        let tok = Token::new(TokenKind::Number, 10..12);
+        let (start, end) = map.token_range(&tok);
+        assert_eq!(start, Position { line: 1, col: 2 });
+        assert_eq!(end, Position { line: 1, col: 4 });
+        assert_eq!(map.position(0), (0, 0));
+    }
+
+    #[test]
+    fn test_span_and_clamp() {
+        let map = CodeMap::new(b"abc\ndef");
+        let span = map.byte_range_to_span(4..7);
+        assert_eq!(span.start, Position { line: 1, col: 0 });
+        assert_eq!(span.end, Position { line: 1, col: 3 });
+        assert_eq!(map.line_col(999), (1, 3));
+    }
+}
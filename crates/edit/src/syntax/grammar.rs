@@ -0,0 +1,445 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Runtime-loadable, data-driven language definitions.
+//!
+//! The built-in lexers are hand-written `impl Lexer`s compiled into the crate,
+//! and [`LanguageSpec`](crate::syntax::lexer::spec::LanguageSpec) makes the
+//! *brace-language* shape declarative but still static. A [`Grammar`] goes one
+//! step further: it is a fully serializable description — keyword sets, comment
+//! delimiters, string rules, and a list of regex-or-literal token rules — that
+//! a user can ship as a `.json` file and have highlighted without recompiling
+//! the editor, the way static highlighters ship `javascript.json`-style
+//! definitions.
+//!
+//! [`GrammarLexer`] interprets a grammar against the existing [`Lexer`] trait.
+//! At each byte position it tries the built-in comment/string handling, then
+//! the token rules longest-match-first, and finally falls back to an
+//! [`Error`](TokenKind::Error) token — so highlighting never aborts on input
+//! the grammar does not describe.
+
+use crate::syntax::lexer::{is_whitespace, Lexer};
+use crate::syntax::{Token, TokenKind};
+use std::collections::HashSet;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A serializable description of a language's lexical grammar.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Grammar {
+    /// Human-readable language name.
+    pub name: String,
+    /// File extensions (without the dot) this grammar claims.
+    pub extensions: Vec<String>,
+    /// Words that classify as [`TokenKind::Keyword`] when they match an
+    /// identifier-shaped rule exactly.
+    pub keywords: Vec<String>,
+    /// Line-comment introducer, e.g. `//`.
+    pub line_comment: Option<String>,
+    /// Block-comment `(open, close)` delimiters, e.g. `("/*", "*/")`.
+    pub block_comment: Option<(String, String)>,
+    /// String literal rules.
+    pub strings: Vec<StringRule>,
+    /// Ordered token rules; see [`GrammarLexer`] for match precedence.
+    pub rules: Vec<Rule>,
+}
+
+/// A string-literal rule: delimiters plus an optional escape character.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StringRule {
+    /// Opening delimiter.
+    pub open: String,
+    /// Closing delimiter.
+    pub close: String,
+    /// Escape character that quotes the next byte inside the string.
+    pub escape: Option<String>,
+}
+
+/// A token rule mapping a literal or simple-regex `pattern` to a [`TokenKind`].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Rule {
+    /// The pattern to match, anchored at the current byte position. See
+    /// [`Pattern`] for the supported syntax.
+    pub pattern: String,
+    /// The kind emitted for a match.
+    pub kind: TokenKind,
+}
+
+/// A [`Lexer`] driven entirely by a [`Grammar`].
+///
+/// Precompiles each rule's pattern and the keyword set once, then scans the
+/// buffer left to right. At every position it tries, in order: whitespace, a
+/// line comment, a block comment, each string rule, then the token rules —
+/// keeping the rule with the *longest* match so a more specific rule wins over
+/// a shorter prefix. An identifier-shaped match whose text is in `keywords` is
+/// reclassified to [`TokenKind::Keyword`]. When nothing matches, a single-byte
+/// [`TokenKind::Error`] token is emitted so the scan always makes progress.
+pub struct GrammarLexer {
+    grammar: Grammar,
+    keywords: HashSet<Vec<u8>>,
+    patterns: Vec<(Pattern, TokenKind)>,
+}
+
+impl GrammarLexer {
+    /// Build a lexer from a grammar, compiling its patterns and keyword set.
+    pub fn new(grammar: Grammar) -> Self {
+        let keywords = grammar.keywords.iter().map(|k| k.as_bytes().to_vec()).collect();
+        let patterns =
+            grammar.rules.iter().map(|r| (Pattern::compile(r.pattern.as_bytes()), r.kind)).collect();
+        Self { grammar, keywords, patterns }
+    }
+
+    /// The grammar this lexer interprets.
+    pub fn grammar(&self) -> &Grammar {
+        &self.grammar
+    }
+
+    /// Load a grammar from a `.json` file and build a lexer from it.
+    #[cfg(feature = "serde")]
+    pub fn from_grammar_file(path: &std::path::Path) -> std::io::Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        let grammar: Grammar = serde_json::from_str(&contents)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        Ok(Self::new(grammar))
+    }
+
+    /// Try to match a comment or string construct at `pos`, returning the token
+    /// that consumes it (always spanning to the delimiter's close, or to EOF if
+    /// the construct is unterminated).
+    fn match_construct(&self, text: &[u8], pos: usize) -> Option<Token> {
+        if let Some(lc) = &self.grammar.line_comment {
+            if text[pos..].starts_with(lc.as_bytes()) {
+                let end = text[pos..].iter().position(|&b| b == b'\n').map_or(text.len(), |n| pos + n);
+                return Some(Token::new(TokenKind::Comment, pos..end));
+            }
+        }
+        if let Some((open, close)) = &self.grammar.block_comment {
+            if text[pos..].starts_with(open.as_bytes()) {
+                let search_from = pos + open.len();
+                let end = find(text, close.as_bytes(), search_from)
+                    .map_or(text.len(), |n| n + close.len());
+                return Some(Token::new(TokenKind::Comment, pos..end));
+            }
+        }
+        for rule in &self.grammar.strings {
+            if text[pos..].starts_with(rule.open.as_bytes()) {
+                let end = scan_string(text, pos, rule);
+                return Some(Token::new(TokenKind::String, pos..end));
+            }
+        }
+        None
+    }
+}
+
+impl Lexer for GrammarLexer {
+    fn tokenize(&self, text: &[u8]) -> Vec<Token> {
+        let mut tokens = Vec::with_capacity(text.len() / 8 + 1);
+        let mut pos = 0;
+        while pos < text.len() {
+            // Whitespace runs collapse into a single token.
+            if is_whitespace(text[pos]) {
+                let start = pos;
+                while pos < text.len() && is_whitespace(text[pos]) {
+                    pos += 1;
+                }
+                tokens.push(Token::new(TokenKind::Whitespace, start..pos));
+                continue;
+            }
+
+            if let Some(token) = self.match_construct(text, pos) {
+                pos = token.span.end;
+                tokens.push(token);
+                continue;
+            }
+
+            // Longest rule match wins; ties resolve to the earlier rule.
+            let mut best: Option<(usize, TokenKind)> = None;
+            for (pattern, kind) in &self.patterns {
+                if let Some(len) = pattern.match_at(&text[pos..]) {
+                    if len > 0 && best.is_none_or(|(blen, _)| len > blen) {
+                        best = Some((len, *kind));
+                    }
+                }
+            }
+
+            match best {
+                Some((len, mut kind)) => {
+                    let slice = &text[pos..pos + len];
+                    if self.keywords.contains(slice) {
+                        kind = TokenKind::Keyword;
+                    }
+                    tokens.push(Token::new(kind, pos..pos + len));
+                    pos += len;
+                }
+                None => {
+                    // Fallback: one Error byte so the scan always advances.
+                    tokens.push(Token::new(TokenKind::Error, pos..pos + 1));
+                    pos += 1;
+                }
+            }
+        }
+        tokens
+    }
+}
+
+/// Scan a string literal starting at `pos` (whose opening delimiter has been
+/// confirmed), returning the byte offset just past its closing delimiter, or
+/// the end of input when the string is unterminated.
+fn scan_string(text: &[u8], pos: usize, rule: &StringRule) -> usize {
+    let escape = rule.escape.as_ref().and_then(|e| e.bytes().next());
+    let mut i = pos + rule.open.len();
+    while i < text.len() {
+        if Some(text[i]) == escape {
+            i += 2;
+            continue;
+        }
+        if text[i..].starts_with(rule.close.as_bytes()) {
+            return i + rule.close.len();
+        }
+        i += 1;
+    }
+    text.len()
+}
+
+/// Find `needle` in `haystack` at or after `from`, returning its start offset.
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    if needle.is_empty() || from >= haystack.len() {
+        return None;
+    }
+    haystack[from..]
+        .windows(needle.len())
+        .position(|w| w == needle)
+        .map(|n| from + n)
+}
+
+/// A compiled pattern: a sequence of quantified elements matched greedily and
+/// anchored at the start of the input slice.
+///
+/// The supported syntax is a small, dependency-free regex subset: literal
+/// bytes, `.` (any byte except newline), the escapes `\d` `\w` `\s` (and
+/// `\`-escaped literals), `[...]`/`[^...]` character classes with `a-z` ranges,
+/// and the `*` `+` `?` quantifiers. It is deliberately not a full regex engine
+/// — just enough to express the token rules a syntax grammar needs.
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    elements: Vec<Element>,
+}
+
+#[derive(Debug, Clone)]
+struct Element {
+    class: Class,
+    quant: Quant,
+}
+
+#[derive(Debug, Clone)]
+enum Class {
+    Any,
+    Literal(u8),
+    Digit,
+    Word,
+    Space,
+    Set { negate: bool, ranges: Vec<(u8, u8)> },
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Quant {
+    One,
+    ZeroOrMore,
+    OneOrMore,
+    ZeroOrOne,
+}
+
+impl Pattern {
+    /// Compile a pattern from its source bytes.
+    pub fn compile(src: &[u8]) -> Self {
+        let mut elements = Vec::new();
+        let mut i = 0;
+        while i < src.len() {
+            let (class, next) = match src[i] {
+                b'\\' if i + 1 < src.len() => {
+                    let c = match src[i + 1] {
+                        b'd' => Class::Digit,
+                        b'w' => Class::Word,
+                        b's' => Class::Space,
+                        other => Class::Literal(other),
+                    };
+                    (c, i + 2)
+                }
+                b'.' => (Class::Any, i + 1),
+                b'[' => parse_class(src, i),
+                other => (Class::Literal(other), i + 1),
+            };
+            i = next;
+            let quant = match src.get(i) {
+                Some(b'*') => {
+                    i += 1;
+                    Quant::ZeroOrMore
+                }
+                Some(b'+') => {
+                    i += 1;
+                    Quant::OneOrMore
+                }
+                Some(b'?') => {
+                    i += 1;
+                    Quant::ZeroOrOne
+                }
+                _ => Quant::One,
+            };
+            elements.push(Element { class, quant });
+        }
+        Self { elements }
+    }
+
+    /// Return the length of the longest anchored match at the start of `text`,
+    /// or `None` if the pattern does not match there.
+    pub fn match_at(&self, text: &[u8]) -> Option<usize> {
+        match_elements(&self.elements, text, 0)
+    }
+}
+
+/// Parse a `[...]` character class beginning at `src[i]` (the `[`), returning
+/// the class and the index just past the closing `]`.
+fn parse_class(src: &[u8], i: usize) -> (Class, usize) {
+    let mut j = i + 1;
+    let negate = src.get(j) == Some(&b'^');
+    if negate {
+        j += 1;
+    }
+    let mut ranges = Vec::new();
+    while j < src.len() && src[j] != b']' {
+        let lo = src[j];
+        if src.get(j + 1) == Some(&b'-') && src.get(j + 2).is_some_and(|&c| c != b']') {
+            ranges.push((lo, src[j + 2]));
+            j += 3;
+        } else {
+            ranges.push((lo, lo));
+            j += 1;
+        }
+    }
+    // Skip the closing `]` if present; an unterminated class still parses.
+    let next = if src.get(j) == Some(&b']') { j + 1 } else { j };
+    (Class::Set { negate, ranges }, next)
+}
+
+impl Class {
+    fn matches(&self, b: u8) -> bool {
+        match self {
+            Class::Any => b != b'\n',
+            Class::Literal(c) => b == *c,
+            Class::Digit => b.is_ascii_digit(),
+            Class::Word => b.is_ascii_alphanumeric() || b == b'_',
+            Class::Space => b.is_ascii_whitespace(),
+            Class::Set { negate, ranges } => {
+                let hit = ranges.iter().any(|&(lo, hi)| (lo..=hi).contains(&b));
+                hit != *negate
+            }
+        }
+    }
+}
+
+/// Greedy, backtracking match of `elements` against `text[pos..]`, returning
+/// the offset in `text` just past the match on success (the match length when
+/// `pos` is 0).
+fn match_elements(elements: &[Element], text: &[u8], pos: usize) -> Option<usize> {
+    let Some(el) = elements.first() else { return Some(pos) };
+    let rest = &elements[1..];
+    match el.quant {
+        Quant::One => {
+            if pos < text.len() && el.class.matches(text[pos]) {
+                match_elements(rest, text, pos + 1)
+            } else {
+                None
+            }
+        }
+        Quant::ZeroOrOne => {
+            if pos < text.len() && el.class.matches(text[pos]) {
+                if let Some(end) = match_elements(rest, text, pos + 1) {
+                    return Some(end);
+                }
+            }
+            match_elements(rest, text, pos)
+        }
+        Quant::ZeroOrMore | Quant::OneOrMore => {
+            let mut count = 0;
+            let mut p = pos;
+            while p < text.len() && el.class.matches(text[p]) {
+                p += 1;
+                count += 1;
+            }
+            let min = if matches!(el.quant, Quant::OneOrMore) { 1 } else { 0 };
+            // Give back characters one at a time until the rest matches.
+            while count + 1 > min {
+                if let Some(end) = match_elements(rest, text, p) {
+                    return Some(end);
+                }
+                if count == min {
+                    break;
+                }
+                p -= 1;
+                count -= 1;
+            }
+            if count >= min { match_elements(rest, text, p) } else { None }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grammar() -> Grammar {
+        Grammar {
+            name: "toy".into(),
+            extensions: vec!["toy".into()],
+            keywords: vec!["let".into(), "if".into()],
+            line_comment: Some("//".into()),
+            block_comment: Some(("/*".into(), "*/".into())),
+            strings: vec![StringRule { open: "\"".into(), close: "\"".into(), escape: Some("\\".into()) }],
+            rules: vec![
+                Rule { pattern: "[a-zA-Z_][a-zA-Z0-9_]*".into(), kind: TokenKind::Identifier },
+                Rule { pattern: "[0-9]+".into(), kind: TokenKind::Number },
+                Rule { pattern: "=".into(), kind: TokenKind::Operator },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_grammar_lexer_basic() {
+        let lexer = GrammarLexer::new(grammar());
+        let tokens = lexer.tokenize(b"let x = 42");
+        let kinds: Vec<_> = tokens.iter().map(|t| t.kind).collect();
+        assert!(kinds.contains(&TokenKind::Keyword)); // `let`
+        assert!(kinds.contains(&TokenKind::Number)); // `42`
+        assert!(kinds.contains(&TokenKind::Operator)); // `=`
+    }
+
+    #[test]
+    fn test_grammar_longest_match_and_comments() {
+        let lexer = GrammarLexer::new(grammar());
+        let tokens = lexer.tokenize(b"foo123 // tail");
+        // The identifier rule consumes `foo123` whole, not just `foo`.
+        assert_eq!(tokens[0].kind, TokenKind::Identifier);
+        assert_eq!(tokens[0].span, 0..6);
+        assert!(tokens.iter().any(|t| t.kind == TokenKind::Comment));
+    }
+
+    #[test]
+    fn test_grammar_unknown_byte_is_error() {
+        let lexer = GrammarLexer::new(grammar());
+        let tokens = lexer.tokenize(b"@");
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].kind, TokenKind::Error);
+    }
+
+    #[test]
+    fn test_pattern_quantifiers() {
+        assert_eq!(Pattern::compile(b"a+").match_at(b"aaab"), Some(3));
+        assert_eq!(Pattern::compile(b"\\d+").match_at(b"123x"), Some(3));
+        assert_eq!(Pattern::compile(b"ab?c").match_at(b"ac"), Some(2));
+        assert_eq!(Pattern::compile(b"x").match_at(b"y"), None);
+    }
+}
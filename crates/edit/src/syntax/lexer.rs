@@ -3,6 +3,12 @@
 
 //! Lexer implementations for various programming languages.
 
+mod cursor;
+mod diagnostic;
+mod dispatch;
+mod interp;
+mod keyword;
+mod spec;
 mod json;
 mod rust;
 mod python;
@@ -21,9 +27,72 @@ mod xml;
 mod shell;
 mod sql;
 mod asciidoc;
+mod wat;
+mod powershell;
 
+use crate::syntax::codemap::{CodeMap, Position};
 use crate::syntax::{Token, TokenKind};
 
+pub use diagnostic::{Diagnostic, LexMessage, Logger, Severity};
+
+use std::ops::Range;
+
+/// The open lexical context carried across a line boundary for resumable
+/// tokenization.
+///
+/// An editor stores the exit state of each line; when a line is edited it
+/// re-lexes forward from that line and stops as soon as a recomputed exit
+/// state matches the previously cached one (see [`Lexer::relex`]). The variants
+/// cover the multi-line constructs the lexers can be suspended inside.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LexerState {
+    /// Not inside any multi-line construct.
+    #[default]
+    Normal,
+    /// Inside an unterminated `/* */`-style block comment.
+    InBlockComment,
+    /// Inside a C preprocessor directive continued onto the next line with a
+    /// trailing backslash (`#define FOO \`…).
+    InPreprocessor,
+    /// Inside a non-verbatim AsciiDoc delimited block (`****`, `====`, …)
+    /// opened on an earlier line, carrying the delimiter char and its run
+    /// length so the continuation recognizes the matching closing fence.
+    /// Verbatim blocks (`----`, `....`, `++++`) are never carried in this state.
+    InDelimitedBlock { delim: u8, len: u8 },
+    /// Inside an unterminated raw/backtick string literal.
+    InRawString,
+    /// Inside an unterminated C++ raw string (`R"delim( … )delim"`), carrying
+    /// the delimiter inline so the continuation knows what closing sequence to
+    /// look for. The C++ standard caps the delimiter at 16 characters, so a
+    /// fixed buffer keeps [`LexerState`] `Copy` without a heap allocation.
+    InRawStringDelim { delim: [u8; 16], len: u8 },
+    /// Inside the body of a shell heredoc, carrying the terminator word and the
+    /// `<<-` leading-tab-stripping flag. Delimiters longer than the buffer are
+    /// truncated, which at worst ends the body one line late.
+    InHeredoc { delim: [u8; 32], len: u8, strip: bool },
+    /// Inside the body of an HTML raw-text element (`<script>`/`<style>`).
+    InRawText,
+    /// Inside an HTML `<!-- -->` comment spanning the line boundary.
+    InHtmlComment,
+    /// Inside an unterminated quoted string, carrying the opening quote byte so
+    /// the continuation can scan for the matching close.
+    InString { quote: u8 },
+    /// Inside a C# verbatim string (`@"…"`, including the interpolated `$@"…"`
+    /// form) that crossed the line boundary. `""` is an escaped quote, so the
+    /// continuation scans for a lone `"`.
+    InVerbatimString,
+    /// Inside a Python triple-quoted string (`'''`/`"""`) that crossed the line
+    /// boundary, carrying the quote byte so the continuation scans for the
+    /// matching triple close.
+    InTripleString { quote: u8 },
+    /// Inside a YAML `|`/`>` block scalar whose body is indented more than
+    /// `parent_indent` columns.
+    InBlockScalar { parent_indent: u16 },
+    /// Inside a PowerShell here-string (`@"`…`"@` / `@'`…`'@`), carrying the
+    /// opening quote byte so the continuation can scan for its terminator.
+    InHereString { quote: u8 },
+}
+
 /// Supported programming languages.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Language {
@@ -47,9 +116,37 @@ pub enum Language {
     Shell,
     Sql,
     AsciiDoc,
+    Wat,
+    PowerShell,
 }
 
 impl Language {
+    /// Every language with a registered lexer, excluding [`Language::PlainText`]
+    /// (the "no highlighting" choice), in menu-display order.
+    pub const ALL: &'static [Language] = &[
+        Language::Json,
+        Language::Rust,
+        Language::Python,
+        Language::JavaScript,
+        Language::TypeScript,
+        Language::Markdown,
+        Language::Toml,
+        Language::Yaml,
+        Language::C,
+        Language::Cpp,
+        Language::CSharp,
+        Language::Go,
+        Language::Html,
+        Language::Css,
+        Language::Java,
+        Language::Xml,
+        Language::Shell,
+        Language::Sql,
+        Language::AsciiDoc,
+        Language::Wat,
+        Language::PowerShell,
+    ];
+
     /// Try to detect the language from a file extension.
     pub fn from_extension(ext: &str) -> Self {
         match ext.to_lowercase().as_str() {
@@ -72,10 +169,46 @@ impl Language {
             "sh" | "bash" | "zsh" => Language::Shell,
             "sql" => Language::Sql,
             "adoc" | "asciidoc" | "asc" => Language::AsciiDoc,
+            "wat" | "wast" => Language::Wat,
+            "ps1" | "psm1" | "psd1" => Language::PowerShell,
             _ => Language::PlainText,
         }
     }
 
+    /// Try to map a short language tag — the info string on a Markdown/
+    /// AsciiDoc fenced code block (`rust`, `js`, `sh`, …) — to a [`Language`].
+    /// Unlike [`from_extension`](Self::from_extension) this favors the names
+    /// people actually type after a ` ``` ` fence over file extensions, and
+    /// returns `None` rather than [`Language::PlainText`] for an unrecognized
+    /// tag so callers can decide whether to fall back to leaving the block
+    /// untokenized.
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        Some(match tag.to_lowercase().as_str() {
+            "json" | "jsonc" => Language::Json,
+            "rust" | "rs" => Language::Rust,
+            "python" | "py" => Language::Python,
+            "javascript" | "js" => Language::JavaScript,
+            "typescript" | "ts" => Language::TypeScript,
+            "markdown" | "md" => Language::Markdown,
+            "toml" => Language::Toml,
+            "yaml" | "yml" => Language::Yaml,
+            "c" => Language::C,
+            "cpp" | "c++" | "cxx" => Language::Cpp,
+            "csharp" | "cs" | "c#" => Language::CSharp,
+            "go" | "golang" => Language::Go,
+            "html" => Language::Html,
+            "css" => Language::Css,
+            "java" => Language::Java,
+            "xml" => Language::Xml,
+            "shell" | "sh" | "bash" | "zsh" | "console" => Language::Shell,
+            "sql" => Language::Sql,
+            "asciidoc" | "adoc" => Language::AsciiDoc,
+            "wat" | "wasm" => Language::Wat,
+            "powershell" | "pwsh" | "ps1" => Language::PowerShell,
+            _ => return None,
+        })
+    }
+
     /// Get the display name for the language.
     pub fn name(self) -> &'static str {
         match self {
@@ -99,14 +232,267 @@ impl Language {
             Language::Shell => "Shell",
             Language::Sql => "SQL",
             Language::AsciiDoc => "AsciiDoc",
+            Language::Wat => "WebAssembly Text",
+            Language::PowerShell => "PowerShell",
         }
     }
 }
 
+/// A sub-range of a document that a host lexer wants tokenized by a
+/// different language's lexer, as reported by [`Lexer::injections`] — a
+/// fenced code block in Markdown, a `<script>`/`<style>` body in HTML, an
+/// embedded SQL string, and so on.
+#[derive(Debug, Clone)]
+pub struct Injection {
+    /// The byte range, in the host document's own coordinates, to delegate.
+    pub range: Range<usize>,
+    /// The language whose lexer should tokenize `range`.
+    pub language: Language,
+}
+
 /// A trait for language lexers.
 pub trait Lexer: Send + Sync {
     /// Tokenize the given text into a sequence of tokens.
     fn tokenize(&self, text: &[u8]) -> Vec<Token>;
+
+    /// Tokenize `text`, additionally collecting [`Diagnostic`]s for malformed
+    /// constructs (unclosed strings, block comments, tags, …).
+    ///
+    /// The default implementation calls [`tokenize`](Lexer::tokenize) and
+    /// reports no diagnostics, so lexers that have not opted in — and callers
+    /// that don't care — are unaffected.
+    fn tokenize_with_diagnostics(&self, text: &[u8]) -> (Vec<Token>, Vec<Diagnostic>) {
+        (self.tokenize(text), Vec::new())
+    }
+
+    /// Tokenize `text` as the body of an interpolation hole nested `depth`
+    /// levels deep inside an enclosing string literal.
+    ///
+    /// Lexers whose interpolation holes re-enter their own tokenizer (a
+    /// shell `"${ $(nested) }"`, a C# `$"{a:{b}}"`, a Python f-string) must
+    /// bound that recursion — a document with a few thousand nested holes is
+    /// a realistic file, not an adversarial payload, and unbounded recursion
+    /// overflows the call stack. Those lexers override this to thread `depth`
+    /// into their own recursive entry point and fall back to a flat token
+    /// once [`interp::MAX_INTERP_DEPTH`](interp) is reached. The default
+    /// ignores `depth` and calls [`tokenize`](Lexer::tokenize), which is
+    /// correct for every lexer that never recurses into itself.
+    fn tokenize_capped(&self, text: &[u8], _depth: usize) -> Vec<Token> {
+        self.tokenize(text)
+    }
+
+    /// Report sub-ranges of `text` that should be tokenized by a different
+    /// language's lexer rather than this one — see [`Injection`].
+    ///
+    /// The default reports none, which is correct both for lexers with no
+    /// embedded sub-languages and for lexers that already delegate
+    /// internally via their own registered sub-lexer table (e.g. the HTML
+    /// lexer's `with_sublexer`, which hands `<script>`/`<style>` bodies to a
+    /// sub-lexer itself) and so never need
+    /// [`SyntaxHighlighter`](crate::syntax::SyntaxHighlighter) to splice on
+    /// their behalf. Returned ranges may nest or overlap; `SyntaxHighlighter::update`
+    /// resolves that innermost-wins.
+    fn injections(&self, _text: &[u8]) -> Vec<Injection> {
+        Vec::new()
+    }
+
+    /// Lint `text` and return the problems found, independent of the token
+    /// stream — unterminated constructs, unexpected characters, and
+    /// language-specific lints (trailing commas, duplicate keys, …).
+    ///
+    /// The default reuses whatever
+    /// [`tokenize_with_diagnostics`](Lexer::tokenize_with_diagnostics) already
+    /// reports, so a lexer that only tracks malformed tokens needs no extra
+    /// work; lexers with richer validation (see [`json`](super::json)) override
+    /// this to add their own checks.
+    fn diagnose(&self, text: &[u8]) -> Vec<Diagnostic> {
+        self.tokenize_with_diagnostics(text).1
+    }
+
+    /// Tokenize `text` and return the tokens alongside a [`CodeMap`] for
+    /// resolving positions.
+    ///
+    /// The map is built in a single pass and shared across all tokens, so
+    /// mapping each token's byte range to a line/column
+    /// [`Span`](crate::syntax::Span) via
+    /// [`CodeMap::byte_range_to_span`] is `O(log lines)` rather than a fresh
+    /// newline scan per lookup. Callers that don't need positions keep using
+    /// [`tokenize`](Lexer::tokenize), which stays allocation-free.
+    fn tokenize_with_positions(&self, text: &[u8]) -> (Vec<Token>, CodeMap) {
+        (self.tokenize(text), CodeMap::new(text))
+    }
+
+    /// Tokenize `text` and pair each [`Token`] with the [`Position`] of its
+    /// first byte.
+    ///
+    /// A convenience wrapper over [`tokenize_with_positions`](Lexer::tokenize_with_positions)
+    /// for consumers — diagnostics, editor gutters, error spans — that want a
+    /// line/column next to each token rather than a shared [`CodeMap`] to query.
+    /// Positions are zero-based and advance a column per byte, resetting to
+    /// column `0` on each `\n`, so they never require a second newline scan of
+    /// the buffer.
+    fn tokenize_positioned(&self, text: &[u8]) -> Vec<(Token, Position)> {
+        let (tokens, map) = self.tokenize_with_positions(text);
+        tokens
+            .into_iter()
+            .map(|token| {
+                let (line, col) = map.line_col(token.span.start);
+                (token, Position { line, col })
+            })
+            .collect()
+    }
+
+    /// Tokenize `text`, passing every token through `map` together with its
+    /// source slice so the caller can upgrade its [`TokenKind`] — turning a
+    /// coarse `Identifier` into a `FunctionName`, `TypeName`, or `PropertyName`,
+    /// or tagging known framework types.
+    ///
+    /// The default implementation tokenizes and maps each token in isolation;
+    /// lexers whose built-in mappers need neighboring context (see
+    /// [`csharp::CSharpLexer::tokenize_semantic`](super::lexer::csharp)) expose
+    /// that as a separate entry point.
+    fn tokenize_mapped(&self, text: &[u8], map: &mut dyn FnMut(Token, &[u8]) -> Token) -> Vec<Token> {
+        self.tokenize(text)
+            .into_iter()
+            .map(|token| {
+                let slice = &text[token.span.clone()];
+                map(token, slice)
+            })
+            .collect()
+    }
+
+    /// Tokenize a single line given the [`LexerState`] carried over from the
+    /// end of the previous line, returning the line's tokens (with spans
+    /// relative to the line start) and the state at the end of the line.
+    ///
+    /// The default implementation lexes the line in isolation and reports a
+    /// `Normal` exit state, which is correct for lexers with no multi-line
+    /// constructs. Lexers that carry state across lines (block comments, raw
+    /// strings, raw-text elements) override this.
+    fn tokenize_line(&self, line: &[u8], _entry: LexerState) -> (Vec<Token>, LexerState) {
+        (self.tokenize(line), LexerState::Normal)
+    }
+
+    /// Re-lex only the lines affected by an edit.
+    ///
+    /// Lexing restarts from the state at the start of the line containing
+    /// `edit.start` and continues forward until a recomputed end-of-line state
+    /// matches the previously stored state in `prev_line_states` (a fixpoint),
+    /// so an edit inside one construct only re-lexes a handful of lines.
+    /// Returns the tokens for the re-lexed region (in document coordinates) and
+    /// the range of line indices that were re-lexed.
+    fn relex(
+        &self,
+        text: &[u8],
+        edit: Range<usize>,
+        prev_line_states: &[LexerState],
+    ) -> (Vec<Token>, Range<usize>) {
+        let lines = line_spans(text);
+        if lines.is_empty() {
+            return (Vec::new(), 0..0);
+        }
+        let first = lines
+            .iter()
+            .position(|l| l.end > edit.start)
+            .unwrap_or(lines.len() - 1);
+        let mut state = if first == 0 {
+            LexerState::Normal
+        } else {
+            prev_line_states.get(first - 1).copied().unwrap_or(LexerState::Normal)
+        };
+
+        let mut out = Vec::new();
+        let mut li = first;
+        while li < lines.len() {
+            let span = lines[li].clone();
+            let (mut toks, exit) = self.tokenize_line(&text[span.clone()], state);
+            for t in &mut toks {
+                t.span.start += span.start;
+                t.span.end += span.start;
+            }
+            out.extend(toks);
+            state = exit;
+            li += 1;
+            // Stop once the edit is behind us and the state has re-converged
+            // with the cached one: the tail of the buffer is unchanged.
+            if span.end > edit.end && prev_line_states.get(li - 1) == Some(&exit) {
+                break;
+            }
+        }
+
+        (out, first..li)
+    }
+
+    /// Tokenize the whole document line by line, returning the tokens (in
+    /// document coordinates) alongside the end-of-line [`LexerState`] for each
+    /// line. The state vector is what [`relex`](Lexer::relex) expects as its
+    /// `prev_line_states`, so a caller can seed its incremental cache with a
+    /// single full pass and re-lex only dirty lines thereafter.
+    fn tokenize_lines(&self, text: &[u8]) -> (Vec<Token>, Vec<LexerState>) {
+        let lines = line_spans(text);
+        let mut tokens = Vec::with_capacity(text.len() / 8);
+        let mut states = Vec::with_capacity(lines.len());
+        let mut state = LexerState::Normal;
+        for span in lines {
+            let (mut toks, exit) = self.tokenize_line(&text[span.clone()], state);
+            for t in &mut toks {
+                t.span.start += span.start;
+                t.span.end += span.start;
+            }
+            tokens.extend(toks);
+            states.push(exit);
+            state = exit;
+        }
+        (tokens, states)
+    }
+}
+
+/// Fill in each token's line/column [`position`](Token::position) from a single
+/// forward scan of `text`, maintaining a running `(line, col)` counter that
+/// increments the line and resets the column on every `\n`. Tokens must be in
+/// source order and contiguous, which every lexer here guarantees.
+///
+/// Only compiled under the `token-positions` feature; byte-range consumers pay
+/// nothing.
+#[cfg(feature = "token-positions")]
+pub(crate) fn attach_line_positions(tokens: &mut [Token], text: &[u8]) {
+    use crate::syntax::codemap::{Position, Span};
+    let mut line = 0u32;
+    let mut col = 0u32;
+    let mut idx = 0usize;
+    let mut advance_to = |target: usize, line: &mut u32, col: &mut u32, idx: &mut usize| {
+        while *idx < target && *idx < text.len() {
+            if text[*idx] == b'\n' {
+                *line += 1;
+                *col = 0;
+            } else {
+                *col += 1;
+            }
+            *idx += 1;
+        }
+        Position { line: *line, col: *col }
+    };
+    for t in tokens.iter_mut() {
+        let start = advance_to(t.span.start, &mut line, &mut col, &mut idx);
+        let end = advance_to(t.span.end, &mut line, &mut col, &mut idx);
+        t.position = Some(Span { start, end });
+    }
+}
+
+/// Split `text` into per-line byte ranges, each including its trailing `\n`.
+pub(crate) fn line_spans(text: &[u8]) -> Vec<Range<usize>> {
+    let mut spans = Vec::new();
+    let mut start = 0;
+    for (i, &b) in text.iter().enumerate() {
+        if b == b'\n' {
+            spans.push(start..i + 1);
+            start = i + 1;
+        }
+    }
+    if start < text.len() || spans.is_empty() {
+        spans.push(start..text.len());
+    }
+    spans
 }
 
 /// Registry for language lexers.
@@ -124,17 +510,28 @@ impl LexerRegistry {
             Language::TypeScript => Box::new(javascript::JavaScriptLexer), // Use same lexer
             Language::Toml => Box::new(toml::TomlLexer),
             Language::Yaml => Box::new(yaml::YamlLexer),
-            Language::C => Box::new(c::CLexer),
+            Language::C => Box::new(c::CLexer::new()),
             Language::Cpp => Box::new(cpp::CppLexer),
-            Language::CSharp => Box::new(csharp::CSharpLexer),
+            Language::CSharp => Box::new(csharp::CSharpLexer::new()),
             Language::Go => Box::new(go::GoLexer),
-            Language::Html => Box::new(html::HtmlLexer),
+            Language::Html => Box::new(
+                html::HtmlLexer::new()
+                    .with_sublexer("script", Box::new(javascript::JavaScriptLexer))
+                    .with_sublexer("style", Box::new(css::CssLexer)),
+            ),
             Language::Css => Box::new(css::CssLexer),
             Language::Java => Box::new(java::JavaLexer),
             Language::Xml => Box::new(xml::XmlLexer),
             Language::Shell => Box::new(shell::ShellLexer),
-            Language::Sql => Box::new(sql::SqlLexer),
-            Language::AsciiDoc => Box::new(asciidoc::AsciiDocLexer),
+            Language::Sql => Box::new(sql::SqlLexer::new()),
+            Language::AsciiDoc => {
+                let mut injections: std::collections::HashMap<&str, Box<dyn Lexer>> =
+                    std::collections::HashMap::new();
+                injections.insert("c", Box::new(c::CLexer::new()));
+                Box::new(asciidoc::AsciiDocLexer::new().with_injections(injections))
+            }
+            Language::Wat => Box::new(wat::WatLexer),
+            Language::PowerShell => Box::new(powershell::PowerShellLexer),
             Language::PlainText => Box::new(PlainTextLexer),
         }
     }
@@ -187,3 +584,228 @@ pub(crate) fn is_ident_start(b: u8) -> bool {
 pub(crate) fn is_ident_continue(b: u8) -> bool {
     is_ascii_alphanumeric(b) || b == b'_'
 }
+
+/// Decode the code point at the front of `bytes` and, if it can *start* an
+/// identifier, return its length in bytes. ASCII is classified directly;
+/// multi-byte code points use the Unicode `XID_Start` property when the
+/// `unicode-ident` feature is on, and are rejected otherwise so ASCII-only
+/// builds stay lean.
+#[inline]
+pub(crate) fn ident_start_len(bytes: &[u8]) -> Option<usize> {
+    match bytes.first().copied()? {
+        b if b < 0x80 => is_ident_start(b).then_some(1),
+        _ => unicode_ident_len(bytes, true),
+    }
+}
+
+/// Like [`ident_start_len`] but for a code point that *continues* an identifier
+/// (`XID_Continue`).
+#[inline]
+pub(crate) fn ident_continue_len(bytes: &[u8]) -> Option<usize> {
+    match bytes.first().copied()? {
+        b if b < 0x80 => is_ident_continue(b).then_some(1),
+        _ => unicode_ident_len(bytes, false),
+    }
+}
+
+/// Decode the first UTF-8 code point of `bytes` as `(char, byte_len)`, or
+/// `None` if it is not valid UTF-8.
+pub(crate) fn first_code_point(bytes: &[u8]) -> Option<(char, usize)> {
+    let len = match bytes.first().copied()? {
+        b if b < 0x80 => 1,
+        b if b >> 5 == 0b110 => 2,
+        b if b >> 4 == 0b1110 => 3,
+        b if b >> 3 == 0b11110 => 4,
+        _ => return None,
+    };
+    let ch = std::str::from_utf8(bytes.get(..len)?).ok()?.chars().next()?;
+    Some((ch, len))
+}
+
+#[cfg(feature = "unicode-ident")]
+fn unicode_ident_len(bytes: &[u8], start: bool) -> Option<usize> {
+    let (ch, len) = first_code_point(bytes)?;
+    let ok = if start {
+        unicode_ident::is_xid_start(ch)
+    } else {
+        unicode_ident::is_xid_continue(ch)
+    };
+    ok.then_some(len)
+}
+
+#[cfg(not(feature = "unicode-ident"))]
+fn unicode_ident_len(_bytes: &[u8], _start: bool) -> Option<usize> {
+    // ASCII-only build: never treat a multi-byte code point as an identifier.
+    None
+}
+
+/// Index of the first `needle` byte in `haystack`, or `None` if absent.
+///
+/// Written as a `position` scan so LLVM autovectorizes it to a SIMD-width search
+/// over the remaining slice, letting the lexers skip comment/string/whitespace
+/// bodies a word at a time instead of byte-stepping through every character.
+#[inline]
+pub(crate) fn memchr(needle: u8, haystack: &[u8]) -> Option<usize> {
+    haystack.iter().position(|&b| b == needle)
+}
+
+/// Like [`memchr`] but stops at the first byte equal to either `a` or `b`. Used
+/// to scan a quoted-string body to the next quote-or-backslash in one pass,
+/// only falling back to byte stepping when a backslash escape is hit.
+#[inline]
+pub(crate) fn memchr2(a: u8, b: u8, haystack: &[u8]) -> Option<usize> {
+    haystack.iter().position(|&x| x == a || x == b)
+}
+
+/// Index of the first byte in `haystack` that is *not* ASCII whitespace, or
+/// `None` if the whole slice is whitespace.
+#[inline]
+pub(crate) fn first_non_whitespace(haystack: &[u8]) -> Option<usize> {
+    haystack.iter().position(|&b| !is_whitespace(b))
+}
+
+/// Classify a C-family `//` line comment (slice starting at the first `/`) as a
+/// doc comment or a plain line comment. `///` and `//!` are doc comments, but a
+/// run of four or more slashes (`////`) is a plain comment, matching rustdoc and
+/// the C-family doc conventions.
+#[inline]
+pub(crate) fn c_line_comment_kind(comment: &[u8]) -> TokenKind {
+    let doc = match comment {
+        [b'/', b'/', b'/', rest @ ..] => rest.first() != Some(&b'/'),
+        [b'/', b'/', b'!', ..] => true,
+        _ => false,
+    };
+    if doc { TokenKind::DocComment } else { TokenKind::LineComment }
+}
+
+/// Classify a C-family `/* */` block comment (slice starting at the first `/`)
+/// as a doc comment or a plain block comment. `/** */` and `/*! */` are doc
+/// comments, but `/***`/`/**/` (no body) stay plain blocks.
+#[inline]
+pub(crate) fn c_block_comment_kind(comment: &[u8]) -> TokenKind {
+    let doc = match comment {
+        [b'/', b'*', b'*', rest @ ..] => !matches!(rest.first(), Some(&b'*') | Some(&b'/')),
+        [b'/', b'*', b'!', ..] => true,
+        _ => false,
+    };
+    if doc { TokenKind::DocComment } else { TokenKind::BlockComment }
+}
+
+/// Classify a `#`-style line comment (slice starting at `#`). A leading `##` —
+/// the shdoc / PowerShell comment-based-help convention — marks a doc comment;
+/// a plain `#` (including a `#!` shebang) is an ordinary line comment.
+#[inline]
+pub(crate) fn hash_line_comment_kind(comment: &[u8]) -> TokenKind {
+    if matches!(comment, [b'#', b'#', ..]) {
+        TokenKind::DocComment
+    } else {
+        TokenKind::LineComment
+    }
+}
+
+/// Classify a PowerShell `<# ... #>` block comment. Comment-based-help blocks
+/// carry dotted keywords (`.SYNOPSIS`, `.DESCRIPTION`, …, matched
+/// case-insensitively) and are treated as doc comments; any other block comment
+/// stays a plain block.
+pub(crate) fn ps_block_comment_kind(comment: &[u8]) -> TokenKind {
+    const HELP_KEYWORDS: &[&[u8]] = &[
+        b".SYNOPSIS", b".DESCRIPTION", b".PARAMETER", b".EXAMPLE", b".NOTES", b".LINK",
+        b".INPUTS", b".OUTPUTS", b".COMPONENT", b".ROLE", b".FUNCTIONALITY", b".FORWARDHELPTARGETNAME",
+    ];
+    let has_help = HELP_KEYWORDS.iter().any(|kw| {
+        comment
+            .windows(kw.len())
+            .any(|w| w.eq_ignore_ascii_case(kw))
+    });
+    if has_help { TokenKind::DocComment } else { TokenKind::BlockComment }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tiny deterministic xorshift64 PRNG so the fuzz test below is
+    /// reproducible across runs without a `rand` dependency.
+    struct Xorshift64(u64);
+
+    impl Xorshift64 {
+        fn next_u64(&mut self) -> u64 {
+            let mut x = self.0;
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            self.0 = x;
+            x
+        }
+
+        fn next_byte(&mut self) -> u8 {
+            (self.next_u64() & 0xff) as u8
+        }
+    }
+
+    /// Assert that `tokens` tile `0..len` exactly — sorted, contiguous, no
+    /// gaps or overlaps. This is the contract every [`Lexer`] must uphold so
+    /// [`SyntaxHighlighter::get_tokens_in_range`](crate::syntax::SyntaxHighlighter::get_tokens_in_range)
+    /// never turns up a hole, even over malformed or mid-edit input.
+    fn assert_contiguous(tokens: &[Token], len: usize) {
+        let mut end = 0;
+        for t in tokens {
+            assert_eq!(t.span.start, end, "gap or overlap at byte {}", t.span.start);
+            assert!(t.span.end <= len, "token span {:?} runs past end of input {len}", t.span);
+            end = t.span.end;
+        }
+        assert_eq!(end, len, "tokens only cover 0..{end}, not the whole {len}-byte input");
+    }
+
+    /// A short, syntactically rich snippet per language, used to probe
+    /// truncation at every byte boundary in
+    /// [`test_lexers_never_panic_and_cover_every_byte`].
+    fn sample_snippet(language: Language) -> &'static [u8] {
+        match language {
+            Language::Json => b"{\"a\": [1, 2.5e10, true, null], \"s\": \"esc\\n\\u00e9\"}",
+            Language::Rust => b"/// doc\nfn f<'a>(x: &'a str) -> i32 { 1_000 + 0x1F }",
+            Language::Python => b"def f(x):\n    f\"{x!r:>{w}}\"\n    '''tri'''",
+            Language::JavaScript | Language::TypeScript => b"`a${1 + `${b}`}b`",
+            Language::Markdown => b"# H\n```rust\nfn f() {}\n```\n*it* **b**",
+            Language::Toml => b"a = 2020-01-01T00:00:00Z\nb = 0x1F",
+            Language::Yaml => b"a: |\n  line\nb: >2\n  line",
+            Language::C | Language::Cpp => b"#define X \\\n1\n/* c */ \"str\\\"\"",
+            Language::CSharp => b"$\"{a:N2}\" @\"raw\"\"str\"",
+            Language::Go => b"`raw` \"str\" // c",
+            Language::Html => b"<script>var x = 1;</script><!-- c -->",
+            Language::Css => b"a { color: #fff; } /* c */",
+            Language::Java => b"/** doc */ class C { int x = 1; }",
+            Language::Xml => b"<a b=\"c\"><!-- x --></a>",
+            Language::Shell => b"x=1; echo \"$x\" <<EOF\nbody\nEOF",
+            Language::Sql => b"SELECT 'a''b' FROM t -- c",
+            Language::AsciiDoc => b"= Title\n----\ncode\n----\n{attr}",
+            Language::Wat => b"(module (func $f (result i32) i32.const 1))",
+            Language::PowerShell => b"<# .SYNOPSIS h #>\nforeach ($x in @(1,2)) { \"$x`n$($x*2)\" }",
+            Language::PlainText => b"plain text",
+        }
+    }
+
+    /// Every registered lexer, fed pseudo-random bytes and a syntactically
+    /// rich snippet truncated at every byte boundary (the shape an editor
+    /// produces mid-keystroke — a half-typed comment, an unterminated
+    /// string, a dangling interpolation hole, …), must not panic and must
+    /// emit tokens that tile the input exactly.
+    #[test]
+    fn test_lexers_never_panic_and_cover_every_byte() {
+        let mut rng = Xorshift64(0x2545_F491_4F6C_DD1D);
+
+        for language in Language::ALL.iter().copied().chain([Language::PlainText]) {
+            let lexer = LexerRegistry::get_lexer(language);
+
+            for len in [0usize, 1, 2, 5, 17, 64] {
+                let buf: Vec<u8> = (0..len).map(|_| rng.next_byte()).collect();
+                assert_contiguous(&lexer.tokenize(&buf), buf.len());
+            }
+
+            let snippet = sample_snippet(language);
+            for cut in 0..=snippet.len() {
+                assert_contiguous(&lexer.tokenize(&snippet[..cut]), cut);
+            }
+        }
+    }
+}
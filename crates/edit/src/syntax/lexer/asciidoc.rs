@@ -3,16 +3,71 @@
 
 //! High-performance AsciiDoc lexer with full language support.
 
-use crate::syntax::lexer::{Lexer, is_whitespace, is_ident_continue, is_ascii_digit};
+use std::collections::HashMap;
+
+use crate::syntax::lexer::{Lexer, LexerState, is_whitespace, is_ident_continue, is_ascii_digit};
 use crate::syntax::{Token, TokenKind};
 
-pub struct AsciiDocLexer;
+/// An AsciiDoc lexer that can delegate the body of `[source,LANG]` listing
+/// blocks to a sub-language lexer, and that suppresses inline formatting inside
+/// literal (`....`) and passthrough (`++++`) blocks.
+///
+/// Injections are registered by lowercase language name via
+/// [`with_injections`]. When none is registered for a block's language the body
+/// is emitted as plain text, as before.
+///
+/// [`with_injections`]: AsciiDocLexer::with_injections
+#[derive(Default)]
+pub struct AsciiDocLexer {
+    injections: HashMap<String, Box<dyn Lexer>>,
+}
+
+impl AsciiDocLexer {
+    /// Create an AsciiDoc lexer with no language injection.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register sub-language lexers keyed by the `[source,LANG]` language token
+    /// (case-insensitive), e.g. `"c"` or `"rust"`.
+    pub fn with_injections(mut self, map: HashMap<&str, Box<dyn Lexer>>) -> Self {
+        for (lang, lexer) in map {
+            self.injections.insert(lang.to_ascii_lowercase(), lexer);
+        }
+        self
+    }
+}
 
 impl Lexer for AsciiDocLexer {
     fn tokenize(&self, text: &[u8]) -> Vec<Token> {
+        self.run(text, LexerState::Normal).0
+    }
+
+    fn tokenize_line(&self, line: &[u8], entry: LexerState) -> (Vec<Token>, LexerState) {
+        self.run(line, entry)
+    }
+}
+
+impl AsciiDocLexer {
+    /// Tokenize `text` starting in `entry` state, returning the tokens and the
+    /// [`LexerState`] the scan ended in. The exit state carries an open
+    /// [`LexerState::InDelimitedBlock`] when `text` ends inside a non-verbatim
+    /// (`****`, `====`, …) delimited block so the next line resumes within it.
+    /// A verbatim (`----`, `....`, `++++`) block is consumed to its closing
+    /// fence or the end of `text` and is not carried: the line after an
+    /// unclosed one starts outside it.
+    fn run(&self, text: &[u8], entry: LexerState) -> (Vec<Token>, LexerState) {
         let mut tokens = Vec::with_capacity(text.len() / 8);
         let mut pos = 0;
         let mut line_start = true;
+        // The innermost open delimited block, carried across lines.
+        let mut block = match entry {
+            LexerState::InDelimitedBlock { delim, len } => Some((delim, len)),
+            _ => None,
+        };
+        // The language captured from a `[source,LANG]` attribute line, applied
+        // to the `----` listing block that immediately follows.
+        let mut pending_lang: Option<Vec<u8>> = None;
 
         while pos < text.len() {
             let start = pos;
@@ -42,8 +97,8 @@ impl Lexer for AsciiDocLexer {
                     continue;
                 }
 
-                // Block delimiters (----, ****, ====, etc.)
-                if matches!(b, b'-' | b'*' | b'=' | b'_' | b'.' | b'/') {
+                // Block delimiters (----, ****, ====, ++++, etc.)
+                if matches!(b, b'-' | b'*' | b'=' | b'_' | b'.' | b'/' | b'+') {
                     let delimiter_start = pos;
                     let delimiter_char = b;
                     let mut count = 0;
@@ -56,11 +111,71 @@ impl Lexer for AsciiDocLexer {
                         while pos < text.len() && text[pos] != b'\n' {
                             pos += 1;
                         }
+                        // Verbatim blocks (listing `----`, literal `....`,
+                        // passthrough `++++`) have their whole body consumed
+                        // here rather than inline-lexed, so `*ptr` and friends
+                        // are not mistaken for markup. An opener is a fence seen
+                        // while no block of the same char is already open.
+                        let verbatim = matches!(delimiter_char, b'-' | b'.' | b'+');
+                        if verbatim && block.is_none() {
+                            tokens.push(Token::new(TokenKind::Operator, delimiter_start..pos));
+                            if text.get(pos) == Some(&b'\n') {
+                                pos += 1;
+                            }
+                            let body_start = pos;
+                            let close = find_block_close(text, pos, delimiter_char);
+                            // Body: sub-lexed for an injected `[source,LANG]`
+                            // listing, otherwise emitted as plain text.
+                            if delimiter_char == b'-' {
+                                if let Some(lexer) = pending_lang
+                                    .as_deref()
+                                    .and_then(|l| self.injections.get(&String::from_utf8_lossy(l).to_ascii_lowercase()))
+                                {
+                                    let mut inner = lexer.tokenize(&text[body_start..close]);
+                                    for t in &mut inner {
+                                        t.span.start += body_start;
+                                        t.span.end += body_start;
+                                    }
+                                    tokens.append(&mut inner);
+                                } else if close > body_start {
+                                    tokens.push(Token::new(TokenKind::Identifier, body_start..close));
+                                }
+                            } else if close > body_start {
+                                // Literal/passthrough body is inert text.
+                                tokens.push(Token::new(TokenKind::String, body_start..close));
+                            }
+                            pos = close;
+                            // Consume the closing fence line, if present.
+                            let fence_start = pos;
+                            while pos < text.len() && text[pos] == delimiter_char {
+                                pos += 1;
+                            }
+                            while pos < text.len() && text[pos] != b'\n' {
+                                pos += 1;
+                            }
+                            if pos > fence_start {
+                                tokens.push(Token::new(TokenKind::Operator, fence_start..pos));
+                            }
+                            line_start = text.get(pos) == Some(&b'\n');
+                            if line_start {
+                                pos += 1;
+                            }
+                            pending_lang = None;
+                            continue;
+                        }
+
+                        // Non-verbatim fences (sidebar `****`, example `====`,
+                        // …) stay inline-lexed; track open/close for resumption.
+                        block = match block {
+                            Some((open, _)) if open == delimiter_char => None,
+                            _ => Some((delimiter_char, count.min(u8::MAX as usize) as u8)),
+                        };
                         tokens.push(Token::new(TokenKind::Operator, delimiter_start..pos));
                         line_start = text.get(pos) == Some(&b'\n');
                         if line_start {
                             pos += 1;
                         }
+                        pending_lang = None;
                         continue;
                     } else {
                         // Not a block delimiter, reset
@@ -68,6 +183,18 @@ impl Lexer for AsciiDocLexer {
                     }
                 }
 
+                // Block attribute list, e.g. `[source,c]` preceding a listing
+                // block. Capture the source language for the following fence.
+                if b == b'[' {
+                    if let Some(end) = memchr_line(text, pos, b']') {
+                        let inner = &text[pos + 1..end];
+                        pending_lang = parse_source_lang(inner);
+                        pos = end + 1;
+                        tokens.push(Token::new(TokenKind::Attribute, start..pos));
+                        continue;
+                    }
+                }
+
                 // Attribute entry (":name: value")
                 if b == b':' && pos + 1 < text.len() && text[pos + 1] != b':' {
                     let attr_start = pos;
@@ -268,6 +395,11 @@ impl Lexer for AsciiDocLexer {
                         pos += 1;
                         tokens.push(Token::new(TokenKind::VariableName, start..pos));
                     } else {
+                        // Not a closed reference after all — emit just the `{`
+                        // and let the main loop re-scan what follows normally,
+                        // so the unclosed tail still gets tokens instead of
+                        // being silently dropped.
+                        pos = attr_ref_start;
                         tokens.push(Token::new(TokenKind::Identifier, start..attr_ref_start));
                     }
                 }
@@ -280,6 +412,79 @@ impl Lexer for AsciiDocLexer {
             }
         }
 
-        tokens
+        let exit = match block {
+            Some((delim, len)) => LexerState::InDelimitedBlock { delim, len },
+            None => LexerState::Normal,
+        };
+        #[cfg(feature = "token-positions")]
+        crate::syntax::lexer::attach_line_positions(&mut tokens, text);
+        (tokens, exit)
+    }
+}
+
+/// Offset of the first `needle` on the line starting at `from`, or `None` if the
+/// line ends (at `\n` or EOF) without one.
+fn memchr_line(text: &[u8], from: usize, needle: u8) -> Option<usize> {
+    let mut i = from;
+    while i < text.len() && text[i] != b'\n' {
+        if text[i] == needle {
+            return Some(i);
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Extract the language token from a block-attribute list interior such as
+/// `source,c` or `source,rust,opts`, returning `None` when the list is not a
+/// `source` block.
+fn parse_source_lang(inner: &[u8]) -> Option<Vec<u8>> {
+    let mut parts = inner.split(|&c| c == b',').map(trim_ascii);
+    if parts.next()? != b"source" {
+        return None;
+    }
+    let lang = parts.next()?;
+    (!lang.is_empty()).then(|| lang.to_vec())
+}
+
+/// Trim leading and trailing ASCII whitespace from a byte slice.
+fn trim_ascii(mut s: &[u8]) -> &[u8] {
+    while let [first, rest @ ..] = s {
+        if first.is_ascii_whitespace() { s = rest } else { break }
+    }
+    while let [rest @ .., last] = s {
+        if last.is_ascii_whitespace() { s = rest } else { break }
+    }
+    s
+}
+
+/// Byte offset of the line that closes a delimited block opened with `delim`,
+/// scanning from `from`. A closing line is a run of four or more `delim`
+/// characters followed only by whitespace. Returns `text.len()` for an
+/// unterminated block.
+fn find_block_close(text: &[u8], from: usize, delim: u8) -> usize {
+    let mut line_start = from;
+    while line_start < text.len() {
+        let mut i = line_start;
+        while i < text.len() && text[i] == delim {
+            i += 1;
+        }
+        let count = i - line_start;
+        let trailing_ok = text[i..].iter().take_while(|&&c| c != b'\n').all(is_whitespace);
+        if count >= 4 && trailing_ok {
+            return line_start;
+        }
+        // Advance to the next line.
+        line_start = match memchr_line_end(text, line_start) {
+            Some(nl) => nl + 1,
+            None => return text.len(),
+        };
     }
+    text.len()
+}
+
+/// Offset of the `\n` ending the line at `from`, or `None` if the line runs to
+/// EOF.
+fn memchr_line_end(text: &[u8], from: usize) -> Option<usize> {
+    (from..text.len()).find(|&i| text[i] == b'\n')
 }
@@ -3,15 +3,157 @@
 
 //! High-performance C lexer with full language support.
 
-use crate::syntax::lexer::{Lexer, is_whitespace, is_ident_start, is_ident_continue, is_ascii_digit};
+use std::collections::{HashMap, HashSet};
+
+use crate::syntax::lexer::{Lexer, LexerState, is_whitespace, is_ident_start, is_ident_continue, is_ascii_digit, c_line_comment_kind, c_block_comment_kind};
 use crate::syntax::{Token, TokenKind};
 
-pub struct CLexer;
+/// C lexer.
+///
+/// Keyword, type, and constant classification is table-driven: identifiers are
+/// resolved with a single hash probe into a [`HashMap`] seeded with the C/C23
+/// vocabulary. Projects with their own typedefs or compiler extensions can
+/// register more entries via [`with_extra_keywords`](CLexer::with_extra_keywords)
+/// and [`with_extra_types`](CLexer::with_extra_types) without touching the
+/// scanner.
+pub struct CLexer {
+    vocabulary: HashMap<Vec<u8>, TokenKind>,
+    /// When `Some`, the lexer evaluates `#if`/`#ifdef`/… nesting against this
+    /// set of defined names and greys out code in branches that are compiled
+    /// out (see [`with_defines`](CLexer::with_defines)). `None` disables the
+    /// analysis entirely, so every branch highlights identically.
+    defines: Option<HashSet<Vec<u8>>>,
+}
+
+impl Default for CLexer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CLexer {
+    /// Create a C lexer seeded with the standard keyword/type/constant table.
+    pub fn new() -> Self {
+        let mut vocabulary = HashMap::with_capacity(64);
+        for (word, kind) in DEFAULT_VOCABULARY {
+            vocabulary.insert(word.to_vec(), *kind);
+        }
+        Self { vocabulary, defines: None }
+    }
+
+    /// Enable preprocessor-conditional dimming against `defined`: names in the
+    /// set are treated as defined macros (and as `1` in `#if` expressions),
+    /// everything else as undefined (`0`). Code inside a `#if`/`#ifdef` branch
+    /// that the defines do not select is re-tagged [`TokenKind::Inactive`] so an
+    /// editor can grey it out. The directive lines themselves stay
+    /// [`TokenKind::Macro`] even inside a dead region.
+    pub fn with_defines(mut self, defined: &[&[u8]]) -> Self {
+        self.defines = Some(defined.iter().map(|d| d.to_vec()).collect());
+        self
+    }
+
+    /// Register additional identifiers that highlight as `Keyword` (e.g.
+    /// `__init`). Later registrations override earlier ones. No builder
+    /// registers identifiers as `Boolean`.
+    pub fn with_extra_keywords(mut self, words: &[&[u8]]) -> Self {
+        for &word in words {
+            self.vocabulary.insert(word.to_vec(), TokenKind::Keyword);
+        }
+        self
+    }
+
+    /// Register additional identifiers that should highlight as `TypeName`
+    /// (e.g. GLib's `gint`/`gpointer` or kernel `u32`).
+    pub fn with_extra_types(mut self, words: &[&[u8]]) -> Self {
+        for &word in words {
+            self.vocabulary.insert(word.to_vec(), TokenKind::TypeName);
+        }
+        self
+    }
+}
+
+/// The built-in C/C23 keyword, type, and constant vocabulary.
+static DEFAULT_VOCABULARY: &[(&[u8], TokenKind)] = &[
+    // C keywords
+    (b"auto", TokenKind::Keyword), (b"break", TokenKind::Keyword),
+    (b"case", TokenKind::Keyword), (b"char", TokenKind::Keyword),
+    (b"const", TokenKind::Keyword), (b"continue", TokenKind::Keyword),
+    (b"default", TokenKind::Keyword), (b"do", TokenKind::Keyword),
+    (b"double", TokenKind::Keyword), (b"else", TokenKind::Keyword),
+    (b"enum", TokenKind::Keyword), (b"extern", TokenKind::Keyword),
+    (b"float", TokenKind::Keyword), (b"for", TokenKind::Keyword),
+    (b"goto", TokenKind::Keyword), (b"if", TokenKind::Keyword),
+    (b"inline", TokenKind::Keyword), (b"int", TokenKind::Keyword),
+    (b"long", TokenKind::Keyword), (b"register", TokenKind::Keyword),
+    (b"restrict", TokenKind::Keyword), (b"return", TokenKind::Keyword),
+    (b"short", TokenKind::Keyword), (b"signed", TokenKind::Keyword),
+    (b"sizeof", TokenKind::Keyword), (b"static", TokenKind::Keyword),
+    (b"struct", TokenKind::Keyword), (b"switch", TokenKind::Keyword),
+    (b"typedef", TokenKind::Keyword), (b"union", TokenKind::Keyword),
+    (b"unsigned", TokenKind::Keyword), (b"void", TokenKind::Keyword),
+    (b"volatile", TokenKind::Keyword), (b"while", TokenKind::Keyword),
+    (b"_Alignas", TokenKind::Keyword), (b"_Alignof", TokenKind::Keyword),
+    (b"_Atomic", TokenKind::Keyword), (b"_Bool", TokenKind::Keyword),
+    (b"_Complex", TokenKind::Keyword), (b"_Generic", TokenKind::Keyword),
+    (b"_Imaginary", TokenKind::Keyword), (b"_Noreturn", TokenKind::Keyword),
+    (b"_Static_assert", TokenKind::Keyword), (b"_Thread_local", TokenKind::Keyword),
+    // C23 keywords
+    (b"_BitInt", TokenKind::Keyword), (b"typeof", TokenKind::Keyword),
+    (b"typeof_unqual", TokenKind::Keyword), (b"_Decimal128", TokenKind::Keyword),
+    (b"_Decimal32", TokenKind::Keyword), (b"_Decimal64", TokenKind::Keyword),
+    // Common constants
+    (b"NULL", TokenKind::Boolean), (b"true", TokenKind::Boolean),
+    (b"false", TokenKind::Boolean), (b"TRUE", TokenKind::Boolean),
+    (b"FALSE", TokenKind::Boolean),
+    // Type names (common standard types)
+    (b"size_t", TokenKind::TypeName), (b"ssize_t", TokenKind::TypeName),
+    (b"ptrdiff_t", TokenKind::TypeName), (b"intptr_t", TokenKind::TypeName),
+    (b"uintptr_t", TokenKind::TypeName), (b"int8_t", TokenKind::TypeName),
+    (b"int16_t", TokenKind::TypeName), (b"int32_t", TokenKind::TypeName),
+    (b"int64_t", TokenKind::TypeName), (b"uint8_t", TokenKind::TypeName),
+    (b"uint16_t", TokenKind::TypeName), (b"uint32_t", TokenKind::TypeName),
+    (b"uint64_t", TokenKind::TypeName), (b"FILE", TokenKind::TypeName),
+    (b"DIR", TokenKind::TypeName), (b"time_t", TokenKind::TypeName),
+    (b"clock_t", TokenKind::TypeName), (b"pid_t", TokenKind::TypeName),
+];
 
 impl Lexer for CLexer {
     fn tokenize(&self, text: &[u8]) -> Vec<Token> {
+        self.run(text, LexerState::Normal).0
+    }
+
+    fn tokenize_line(&self, line: &[u8], entry: LexerState) -> (Vec<Token>, LexerState) {
+        self.run(line, entry)
+    }
+}
+
+impl CLexer {
+    /// Tokenize `text` starting in `entry` state, returning the tokens and the
+    /// [`LexerState`] the scan ended in. The exit state is `Normal` unless
+    /// `text` ends inside a block comment or a backslash-continued preprocessor
+    /// directive, in which case the next line resumes from that state.
+    fn run(&self, text: &[u8], entry: LexerState) -> (Vec<Token>, LexerState) {
         let mut tokens = Vec::with_capacity(text.len() / 8);
         let mut pos = 0;
+        let mut exit = LexerState::Normal;
+
+        // Resume a multi-line construct carried in from the previous line.
+        match entry {
+            LexerState::InBlockComment => {
+                if !scan_block_comment(text, &mut pos) {
+                    exit = LexerState::InBlockComment;
+                }
+                tokens.push(Token::new(c_block_comment_kind(&text[..pos]), 0..pos));
+            }
+            LexerState::InPreprocessor => {
+                scan_logical_line(text, &mut pos);
+                if line_continues(&text[..pos]) {
+                    exit = LexerState::InPreprocessor;
+                }
+                tokens.push(Token::new(TokenKind::Macro, 0..pos));
+            }
+            _ => {}
+        }
 
         while pos < text.len() {
             let start = pos;
@@ -32,20 +174,16 @@ impl Lexer for CLexer {
                     while pos < text.len() && text[pos] != b'\n' {
                         pos += 1;
                     }
-                    tokens.push(Token::new(TokenKind::Comment, start..pos));
+                    tokens.push(Token::new(c_line_comment_kind(&text[start..pos]), start..pos));
                 }
 
                 // Block comment
                 b'/' if pos + 1 < text.len() && text[pos + 1] == b'*' => {
                     pos += 2;
-                    while pos + 1 < text.len() {
-                        if text[pos] == b'*' && text[pos + 1] == b'/' {
-                            pos += 2;
-                            break;
-                        }
-                        pos += 1;
+                    if !scan_block_comment(text, &mut pos) {
+                        exit = LexerState::InBlockComment;
                     }
-                    tokens.push(Token::new(TokenKind::Comment, start..pos));
+                    tokens.push(Token::new(c_block_comment_kind(&text[start..pos]), start..pos));
                 }
 
                 // Preprocessor directive
@@ -58,13 +196,9 @@ impl Lexer for CLexer {
                         pos += 1;
                     }
                     // Continue to end of logical line (handles line continuation with \)
-                    while pos < text.len() {
-                        if text[pos] == b'\n' {
-                            if pos > 0 && text[pos - 1] != b'\\' {
-                                break;
-                            }
-                        }
-                        pos += 1;
+                    scan_logical_line(text, &mut pos);
+                    if line_continues(&text[start..pos]) {
+                        exit = LexerState::InPreprocessor;
                     }
                     tokens.push(Token::new(TokenKind::Macro, start..pos));
                 }
@@ -164,33 +298,7 @@ impl Lexer for CLexer {
                         pos += 1;
                     }
                     let word = &text[start..pos];
-                    let kind = match word {
-                        // C keywords
-                        b"auto" | b"break" | b"case" | b"char" | b"const" | b"continue" |
-                        b"default" | b"do" | b"double" | b"else" | b"enum" | b"extern" |
-                        b"float" | b"for" | b"goto" | b"if" | b"inline" | b"int" | b"long" |
-                        b"register" | b"restrict" | b"return" | b"short" | b"signed" |
-                        b"sizeof" | b"static" | b"struct" | b"switch" | b"typedef" |
-                        b"union" | b"unsigned" | b"void" | b"volatile" | b"while" |
-                        b"_Alignas" | b"_Alignof" | b"_Atomic" | b"_Bool" | b"_Complex" |
-                        b"_Generic" | b"_Imaginary" | b"_Noreturn" | b"_Static_assert" |
-                        b"_Thread_local" => TokenKind::Keyword,
-                        
-                        // C23 keywords
-                        b"_BitInt" | b"typeof" | b"typeof_unqual" |
-                        b"_Decimal128" | b"_Decimal32" | b"_Decimal64" => TokenKind::Keyword,
-                        
-                        // Common constants
-                        b"NULL" | b"true" | b"false" | b"TRUE" | b"FALSE" => TokenKind::Boolean,
-                        
-                        // Type names (common standard types)
-                        b"size_t" | b"ssize_t" | b"ptrdiff_t" | b"intptr_t" | b"uintptr_t" |
-                        b"int8_t" | b"int16_t" | b"int32_t" | b"int64_t" |
-                        b"uint8_t" | b"uint16_t" | b"uint32_t" | b"uint64_t" |
-                        b"FILE" | b"DIR" | b"time_t" | b"clock_t" | b"pid_t" => TokenKind::TypeName,
-                        
-                        _ => TokenKind::Identifier,
-                    };
+                    let kind = self.vocabulary.get(word).copied().unwrap_or(TokenKind::Identifier);
                     tokens.push(Token::new(kind, start..pos));
                 }
 
@@ -232,6 +340,375 @@ impl Lexer for CLexer {
             }
         }
 
-        tokens
+        if let Some(defines) = &self.defines {
+            apply_conditional_dimming(&mut tokens, text, defines);
+        }
+
+        #[cfg(feature = "token-positions")]
+        crate::syntax::lexer::attach_line_positions(&mut tokens, text);
+        (tokens, exit)
+    }
+}
+
+/// One open `#if`/`#ifdef`/… region on the conditional stack, mirroring the
+/// per-level bookkeeping used by classic C preprocessors: `active` is whether
+/// the branch currently in effect at this level is selected, `any_taken`
+/// whether some branch at this level has been selected yet, and `else_seen`
+/// guards against a second `#else`.
+struct Frame {
+    active: bool,
+    any_taken: bool,
+    else_seen: bool,
+}
+
+/// Re-tag tokens that fall inside compiled-out preprocessor branches as
+/// [`TokenKind::Inactive`]. Directive (`Macro`) tokens drive the conditional
+/// stack and are never themselves dimmed. A region is live only when every
+/// enclosing frame is active; an unbalanced `#endif` is ignored rather than
+/// underflowing the stack.
+fn apply_conditional_dimming(tokens: &mut [Token], text: &[u8], defines: &HashSet<Vec<u8>>) {
+    let mut frames: Vec<Frame> = Vec::new();
+    for token in tokens.iter_mut() {
+        if token.kind == TokenKind::Macro {
+            let slice = &text[token.span.clone()];
+            if let Some((directive, args)) = split_directive(slice) {
+                apply_directive(&mut frames, directive, args, defines);
+            }
+            // Directive lines stay Macro regardless of the region they sit in.
+            continue;
+        }
+        if !frames.iter().all(|f| f.active) {
+            token.kind = TokenKind::Inactive;
+        }
+    }
+}
+
+/// Split a `#`-directive slice into its keyword (`if`, `ifdef`, …) and the
+/// remaining argument bytes, or `None` if the slice is not a directive.
+fn split_directive(slice: &[u8]) -> Option<(&[u8], &[u8])> {
+    let mut i = 0;
+    while i < slice.len() && is_whitespace(slice[i]) {
+        i += 1;
+    }
+    if i >= slice.len() || slice[i] != b'#' {
+        return None;
+    }
+    i += 1;
+    while i < slice.len() && is_whitespace(slice[i]) {
+        i += 1;
+    }
+    let start = i;
+    while i < slice.len() && (is_ident_continue(slice[i]) || slice[i] == b'_') {
+        i += 1;
+    }
+    Some((&slice[start..i], &slice[i..]))
+}
+
+/// Update the conditional `frames` for one directive.
+fn apply_directive(frames: &mut Vec<Frame>, directive: &[u8], args: &[u8], defines: &HashSet<Vec<u8>>) {
+    match directive {
+        b"if" => {
+            let cond = eval_condition(args, defines);
+            frames.push(Frame { active: cond, any_taken: cond, else_seen: false });
+        }
+        b"ifdef" => {
+            let cond = first_ident(args).map_or(false, |name| defines.contains(name));
+            frames.push(Frame { active: cond, any_taken: cond, else_seen: false });
+        }
+        b"ifndef" => {
+            let cond = first_ident(args).map_or(true, |name| !defines.contains(name));
+            frames.push(Frame { active: cond, any_taken: cond, else_seen: false });
+        }
+        b"elif" => {
+            if let Some(frame) = frames.last_mut() {
+                if frame.any_taken || frame.else_seen {
+                    frame.active = false;
+                } else {
+                    let cond = eval_condition(args, defines);
+                    frame.active = cond;
+                    frame.any_taken = cond;
+                }
+            }
+        }
+        b"else" => {
+            if let Some(frame) = frames.last_mut() {
+                frame.active = !frame.any_taken && !frame.else_seen;
+                frame.any_taken = true;
+                frame.else_seen = true;
+            }
+        }
+        b"endif" => {
+            frames.pop();
+        }
+        _ => {}
+    }
+}
+
+/// The first identifier in `args`, skipping leading whitespace.
+fn first_ident(args: &[u8]) -> Option<&[u8]> {
+    let mut i = 0;
+    while i < args.len() && is_whitespace(args[i]) {
+        i += 1;
+    }
+    let start = i;
+    while i < args.len() && (is_ident_continue(args[i]) || args[i] == b'_') {
+        i += 1;
+    }
+    if i > start { Some(&args[start..i]) } else { None }
+}
+
+/// Evaluate a `#if`/`#elif` expression, returning whether it is truthy. The
+/// grammar covers `defined(X)`, integer literals, `!`, `&&`, `||`, and the
+/// comparison operators; unknown identifiers evaluate to `0` and defined ones
+/// to `1`.
+fn eval_condition(args: &[u8], defines: &HashSet<Vec<u8>>) -> bool {
+    let mut parser = ExprParser { bytes: args, pos: 0, defines };
+    parser.parse_or() != 0
+}
+
+/// A recursive-descent evaluator over a preprocessor conditional expression.
+struct ExprParser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    defines: &'a HashSet<Vec<u8>>,
+}
+
+impl ExprParser<'_> {
+    fn skip_ws(&mut self) {
+        while self.pos < self.bytes.len() && is_whitespace(self.bytes[self.pos]) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek2(&self) -> (u8, u8) {
+        let a = self.bytes.get(self.pos).copied().unwrap_or(0);
+        let b = self.bytes.get(self.pos + 1).copied().unwrap_or(0);
+        (a, b)
+    }
+
+    fn parse_or(&mut self) -> i64 {
+        let mut value = self.parse_and();
+        loop {
+            self.skip_ws();
+            if self.peek2() == (b'|', b'|') {
+                self.pos += 2;
+                let rhs = self.parse_and();
+                value = ((value != 0) || (rhs != 0)) as i64;
+            } else {
+                break;
+            }
+        }
+        value
+    }
+
+    fn parse_and(&mut self) -> i64 {
+        let mut value = self.parse_cmp();
+        loop {
+            self.skip_ws();
+            if self.peek2() == (b'&', b'&') {
+                self.pos += 2;
+                let rhs = self.parse_cmp();
+                value = ((value != 0) && (rhs != 0)) as i64;
+            } else {
+                break;
+            }
+        }
+        value
+    }
+
+    fn parse_cmp(&mut self) -> i64 {
+        let lhs = self.parse_unary();
+        self.skip_ws();
+        let (a, b) = self.peek2();
+        let (op, width): (&[u8], usize) = match (a, b) {
+            (b'=', b'=') => (b"==", 2),
+            (b'!', b'=') => (b"!=", 2),
+            (b'<', b'=') => (b"<=", 2),
+            (b'>', b'=') => (b">=", 2),
+            (b'<', _) => (b"<", 1),
+            (b'>', _) => (b">", 1),
+            _ => return lhs,
+        };
+        self.pos += width;
+        let rhs = self.parse_unary();
+        let result = match op {
+            b"==" => lhs == rhs,
+            b"!=" => lhs != rhs,
+            b"<=" => lhs <= rhs,
+            b">=" => lhs >= rhs,
+            b"<" => lhs < rhs,
+            _ => lhs > rhs,
+        };
+        result as i64
+    }
+
+    fn parse_unary(&mut self) -> i64 {
+        self.skip_ws();
+        if self.bytes.get(self.pos) == Some(&b'!') {
+            self.pos += 1;
+            return (self.parse_unary() == 0) as i64;
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> i64 {
+        self.skip_ws();
+        match self.bytes.get(self.pos).copied() {
+            Some(b'(') => {
+                self.pos += 1;
+                let value = self.parse_or();
+                self.skip_ws();
+                if self.bytes.get(self.pos) == Some(&b')') {
+                    self.pos += 1;
+                }
+                value
+            }
+            Some(c) if c.is_ascii_digit() => {
+                let start = self.pos;
+                while self.pos < self.bytes.len() && self.bytes[self.pos].is_ascii_digit() {
+                    self.pos += 1;
+                }
+                // Ignore integer suffixes like `UL`.
+                while self.pos < self.bytes.len()
+                    && matches!(self.bytes[self.pos], b'u' | b'U' | b'l' | b'L')
+                {
+                    self.pos += 1;
+                }
+                std::str::from_utf8(&self.bytes[start..self.pos])
+                    .ok()
+                    .and_then(|s| s.parse::<i64>().ok())
+                    .unwrap_or(0)
+            }
+            Some(c) if is_ident_start(c) => {
+                let start = self.pos;
+                while self.pos < self.bytes.len()
+                    && (is_ident_continue(self.bytes[self.pos]) || self.bytes[self.pos] == b'_')
+                {
+                    self.pos += 1;
+                }
+                let ident = &self.bytes[start..self.pos];
+                if ident == b"defined" {
+                    return self.parse_defined();
+                }
+                self.defines.contains(ident) as i64
+            }
+            _ => 0,
+        }
+    }
+
+    /// Parse the operand of a `defined` operator: either `defined NAME` or
+    /// `defined(NAME)`.
+    fn parse_defined(&mut self) -> i64 {
+        self.skip_ws();
+        let paren = self.bytes.get(self.pos) == Some(&b'(');
+        if paren {
+            self.pos += 1;
+            self.skip_ws();
+        }
+        let start = self.pos;
+        while self.pos < self.bytes.len()
+            && (is_ident_continue(self.bytes[self.pos]) || self.bytes[self.pos] == b'_')
+        {
+            self.pos += 1;
+        }
+        let name = &self.bytes[start..self.pos];
+        let defined = self.defines.contains(name);
+        if paren {
+            self.skip_ws();
+            if self.bytes.get(self.pos) == Some(&b')') {
+                self.pos += 1;
+            }
+        }
+        defined as i64
+    }
+}
+
+/// Advance `pos` past a `*/` block-comment terminator, returning `true` if one
+/// was found. On an unterminated comment `pos` lands at EOF and the result is
+/// `false` so the caller can suspend into [`LexerState::InBlockComment`].
+fn scan_block_comment(text: &[u8], pos: &mut usize) -> bool {
+    while *pos + 1 < text.len() {
+        if text[*pos] == b'*' && text[*pos + 1] == b'/' {
+            *pos += 2;
+            return true;
+        }
+        *pos += 1;
+    }
+    *pos = text.len();
+    false
+}
+
+/// Advance `pos` to the end of a preprocessor logical line: up to the first
+/// `\n` that is not escaped by a preceding backslash (a continued directive
+/// swallows the newline and keeps going).
+fn scan_logical_line(text: &[u8], pos: &mut usize) {
+    while *pos < text.len() {
+        if text[*pos] == b'\n' && (*pos == 0 || text[*pos - 1] != b'\\') {
+            break;
+        }
+        *pos += 1;
+    }
+}
+
+/// Whether a directive slice ends with a line-continuation backslash, so the
+/// next line is still part of the directive.
+fn line_continues(directive: &[u8]) -> bool {
+    match directive {
+        [.., b'\\'] => true,
+        [.., b'\\', b'\n'] => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The `TokenKind` of the token covering `needle` in `text`, or `None` if
+    /// no token's span exactly matches it (the tests below only look for
+    /// whole-identifier needles, so an exact span match is unambiguous).
+    fn kind_of(text: &[u8], tokens: &[Token], needle: &[u8]) -> Option<TokenKind> {
+        let at = text.windows(needle.len()).position(|w| w == needle)?;
+        tokens.iter().find(|t| t.span == (at..at + needle.len())).map(|t| t.kind)
+    }
+
+    #[test]
+    fn test_conditional_dimming_and_not() {
+        let text = b"#if A && !B\nlive;\n#endif\n";
+        let lexer = CLexer::new().with_defines(&[b"A"]);
+        let tokens = lexer.tokenize(text);
+
+        // `A && !B` is true (A defined, B is not), so the branch stays live.
+        assert_eq!(kind_of(text, &tokens, b"live"), Some(TokenKind::Identifier));
+
+        let text = b"#if A && !B\ndead;\n#endif\n";
+        let lexer = CLexer::new().with_defines(&[b"A", b"B"]);
+        let tokens = lexer.tokenize(text);
+
+        // With B also defined, `!B` is false, so the branch is compiled out.
+        assert_eq!(kind_of(text, &tokens, b"dead"), Some(TokenKind::Inactive));
+    }
+
+    #[test]
+    fn test_conditional_dimming_elif_after_taken_branch() {
+        let text = b"#if A\ntaken;\n#elif B\nskipped;\n#endif\n";
+        let lexer = CLexer::new().with_defines(&[b"A", b"B"]);
+        let tokens = lexer.tokenize(text);
+
+        // The `#if` branch already matched, so `#elif B` is dead even though
+        // B is defined.
+        assert_eq!(kind_of(text, &tokens, b"taken"), Some(TokenKind::Identifier));
+        assert_eq!(kind_of(text, &tokens, b"skipped"), Some(TokenKind::Inactive));
+    }
+
+    #[test]
+    fn test_conditional_dimming_unbalanced_endif() {
+        let text = b"#endif\nafter;\n";
+        let lexer = CLexer::new().with_defines(&[]);
+
+        // An `#endif` with no matching `#if` must not underflow the frame
+        // stack or otherwise panic; the trailing code stays live.
+        let tokens = lexer.tokenize(text);
+        assert_eq!(kind_of(text, &tokens, b"after"), Some(TokenKind::Identifier));
     }
 }
@@ -3,15 +3,129 @@
 
 //! High-performance C++ lexer with full language support.
 
-use crate::syntax::lexer::{Lexer, is_whitespace, is_ident_start, is_ident_continue, is_ascii_digit};
+use crate::syntax::lexer::diagnostic::{LexMessage, Logger};
+use crate::syntax::lexer::keyword::KeywordTable;
+use crate::syntax::lexer::{Diagnostic, Lexer, LexerState, is_whitespace, is_ident_start, is_ident_continue, is_ascii_digit, c_line_comment_kind, c_block_comment_kind, memchr, memchr2, first_non_whitespace};
 use crate::syntax::{Token, TokenKind};
 
 pub struct CppLexer;
 
+/// C++ reserved words, literal constants, and common STL type names, classified
+/// into the kinds the theme colors. Editing this list is the only thing needed
+/// to teach the lexer a new keyword; lookup stays O(word length) through the
+/// shared keyword hash table regardless of how long the list grows.
+static KEYWORDS: KeywordTable = KeywordTable::new(&[
+    // C++ keywords (includes all C keywords plus C++-specific)
+    (b"alignas", TokenKind::Keyword), (b"alignof", TokenKind::Keyword),
+    (b"and", TokenKind::Keyword), (b"and_eq", TokenKind::Keyword),
+    (b"asm", TokenKind::Keyword), (b"auto", TokenKind::Keyword),
+    (b"bitand", TokenKind::Keyword), (b"bitor", TokenKind::Keyword),
+    (b"bool", TokenKind::Keyword), (b"break", TokenKind::Keyword),
+    (b"case", TokenKind::Keyword), (b"catch", TokenKind::Keyword),
+    (b"char", TokenKind::Keyword), (b"char8_t", TokenKind::Keyword),
+    (b"char16_t", TokenKind::Keyword), (b"char32_t", TokenKind::Keyword),
+    (b"class", TokenKind::Keyword), (b"compl", TokenKind::Keyword),
+    (b"concept", TokenKind::Keyword), (b"const", TokenKind::Keyword),
+    (b"const_cast", TokenKind::Keyword), (b"consteval", TokenKind::Keyword),
+    (b"constexpr", TokenKind::Keyword), (b"constinit", TokenKind::Keyword),
+    (b"continue", TokenKind::Keyword), (b"co_await", TokenKind::Keyword),
+    (b"co_return", TokenKind::Keyword), (b"co_yield", TokenKind::Keyword),
+    (b"decltype", TokenKind::Keyword), (b"default", TokenKind::Keyword),
+    (b"delete", TokenKind::Keyword), (b"do", TokenKind::Keyword),
+    (b"double", TokenKind::Keyword), (b"dynamic_cast", TokenKind::Keyword),
+    (b"else", TokenKind::Keyword), (b"enum", TokenKind::Keyword),
+    (b"explicit", TokenKind::Keyword), (b"export", TokenKind::Keyword),
+    (b"extern", TokenKind::Keyword), (b"float", TokenKind::Keyword),
+    (b"for", TokenKind::Keyword), (b"friend", TokenKind::Keyword),
+    (b"goto", TokenKind::Keyword), (b"if", TokenKind::Keyword),
+    (b"inline", TokenKind::Keyword), (b"int", TokenKind::Keyword),
+    (b"long", TokenKind::Keyword), (b"mutable", TokenKind::Keyword),
+    (b"namespace", TokenKind::Keyword), (b"new", TokenKind::Keyword),
+    (b"noexcept", TokenKind::Keyword), (b"not", TokenKind::Keyword),
+    (b"not_eq", TokenKind::Keyword), (b"operator", TokenKind::Keyword),
+    (b"or", TokenKind::Keyword), (b"or_eq", TokenKind::Keyword),
+    (b"private", TokenKind::Keyword), (b"protected", TokenKind::Keyword),
+    (b"public", TokenKind::Keyword), (b"register", TokenKind::Keyword),
+    (b"reinterpret_cast", TokenKind::Keyword), (b"requires", TokenKind::Keyword),
+    (b"return", TokenKind::Keyword), (b"short", TokenKind::Keyword),
+    (b"signed", TokenKind::Keyword), (b"sizeof", TokenKind::Keyword),
+    (b"static", TokenKind::Keyword), (b"static_assert", TokenKind::Keyword),
+    (b"static_cast", TokenKind::Keyword), (b"struct", TokenKind::Keyword),
+    (b"switch", TokenKind::Keyword), (b"template", TokenKind::Keyword),
+    (b"this", TokenKind::Keyword), (b"thread_local", TokenKind::Keyword),
+    (b"throw", TokenKind::Keyword), (b"try", TokenKind::Keyword),
+    (b"typedef", TokenKind::Keyword), (b"typeid", TokenKind::Keyword),
+    (b"typename", TokenKind::Keyword), (b"union", TokenKind::Keyword),
+    (b"unsigned", TokenKind::Keyword), (b"using", TokenKind::Keyword),
+    (b"virtual", TokenKind::Keyword), (b"void", TokenKind::Keyword),
+    (b"volatile", TokenKind::Keyword), (b"wchar_t", TokenKind::Keyword),
+    (b"while", TokenKind::Keyword), (b"xor", TokenKind::Keyword),
+    (b"xor_eq", TokenKind::Keyword),
+    // Boolean and null literals
+    (b"true", TokenKind::Boolean), (b"false", TokenKind::Boolean),
+    (b"TRUE", TokenKind::Boolean), (b"FALSE", TokenKind::Boolean),
+    (b"nullptr", TokenKind::Boolean), (b"NULL", TokenKind::Boolean),
+    // Common STL types
+    (b"string", TokenKind::TypeName), (b"vector", TokenKind::TypeName),
+    (b"map", TokenKind::TypeName), (b"set", TokenKind::TypeName),
+    (b"list", TokenKind::TypeName), (b"deque", TokenKind::TypeName),
+    (b"queue", TokenKind::TypeName), (b"stack", TokenKind::TypeName),
+    (b"array", TokenKind::TypeName), (b"pair", TokenKind::TypeName),
+    (b"tuple", TokenKind::TypeName), (b"optional", TokenKind::TypeName),
+    (b"variant", TokenKind::TypeName), (b"any", TokenKind::TypeName),
+    (b"function", TokenKind::TypeName), (b"shared_ptr", TokenKind::TypeName),
+    (b"unique_ptr", TokenKind::TypeName), (b"weak_ptr", TokenKind::TypeName),
+    (b"size_t", TokenKind::TypeName), (b"ptrdiff_t", TokenKind::TypeName),
+    (b"nullptr_t", TokenKind::TypeName),
+]);
+
 impl Lexer for CppLexer {
     fn tokenize(&self, text: &[u8]) -> Vec<Token> {
+        // An empty logger allocates nothing until something is reported, so the
+        // diagnostic-free path pays no extra cost.
+        self.run(text, LexerState::Normal, &mut Logger::new()).0
+    }
+
+    fn tokenize_with_diagnostics(&self, text: &[u8]) -> (Vec<Token>, Vec<Diagnostic>) {
+        let mut log = Logger::new();
+        let (tokens, _) = self.run(text, LexerState::Normal, &mut log);
+        (tokens, log.into_diagnostics())
+    }
+
+    fn tokenize_line(&self, line: &[u8], entry: LexerState) -> (Vec<Token>, LexerState) {
+        self.run(line, entry, &mut Logger::new())
+    }
+}
+
+impl CppLexer {
+    /// Tokenize `text` starting in `entry` state, recording a [`Diagnostic`]
+    /// into `log` at the opener of any construct that runs to end-of-input
+    /// unclosed. Returns the tokens and the [`LexerState`] the scan ended in:
+    /// `Normal` unless `text` ends inside a block comment or raw string, in
+    /// which case the next line resumes from that state.
+    fn run(&self, text: &[u8], entry: LexerState, log: &mut Logger) -> (Vec<Token>, LexerState) {
         let mut tokens = Vec::with_capacity(text.len() / 8);
         let mut pos = 0;
+        let mut exit = LexerState::Normal;
+
+        // Resume a multi-line construct carried in from the previous line.
+        match entry {
+            LexerState::InBlockComment => {
+                let closed = scan_block_comment(text, &mut pos);
+                if !closed {
+                    exit = LexerState::InBlockComment;
+                }
+                tokens.push(Token::new(c_block_comment_kind(&text[..pos]), 0..pos));
+            }
+            LexerState::InRawStringDelim { delim, len } => {
+                let delimiter = &delim[..len as usize];
+                if !scan_raw_string_tail(text, &mut pos, delimiter) {
+                    exit = LexerState::InRawStringDelim { delim, len };
+                }
+                tokens.push(Token::new(TokenKind::String, 0..pos));
+            }
+            _ => {}
+        }
 
         while pos < text.len() {
             let start = pos;
@@ -20,32 +134,25 @@ impl Lexer for CppLexer {
             match b {
                 // Whitespace
                 b' ' | b'\t' | b'\n' | b'\r' => {
-                    while pos < text.len() && is_whitespace(text[pos]) {
-                        pos += 1;
-                    }
+                    pos += first_non_whitespace(&text[pos..]).unwrap_or(text.len() - pos);
                     tokens.push(Token::new(TokenKind::Whitespace, start..pos));
                 }
 
                 // Line comment
                 b'/' if pos + 1 < text.len() && text[pos + 1] == b'/' => {
                     pos += 2;
-                    while pos < text.len() && text[pos] != b'\n' {
-                        pos += 1;
-                    }
-                    tokens.push(Token::new(TokenKind::Comment, start..pos));
+                    pos += memchr(b'\n', &text[pos..]).unwrap_or(text.len() - pos);
+                    tokens.push(Token::new(c_line_comment_kind(&text[start..pos]), start..pos));
                 }
 
                 // Block comment
                 b'/' if pos + 1 < text.len() && text[pos + 1] == b'*' => {
                     pos += 2;
-                    while pos + 1 < text.len() {
-                        if text[pos] == b'*' && text[pos + 1] == b'/' {
-                            pos += 2;
-                            break;
-                        }
-                        pos += 1;
+                    if !scan_block_comment(text, &mut pos) {
+                        exit = LexerState::InBlockComment;
+                        log.report(LexMessage::UnclosedBlockComment, start..pos);
                     }
-                    tokens.push(Token::new(TokenKind::Comment, start..pos));
+                    tokens.push(Token::new(c_block_comment_kind(&text[start..pos]), start..pos));
                 }
 
                 // Preprocessor directive
@@ -72,7 +179,9 @@ impl Lexer for CppLexer {
                 // Raw string literal (C++11)
                 b'R' if pos + 2 < text.len() && text[pos + 1] == b'"' && text[pos + 2] == b'(' => {
                     pos += 3;
-                    // Find delimiter
+                    // The delimiter is the (possibly empty) text between `R"` and
+                    // `(`; here it is always empty because the guard matched
+                    // `R"(`. Kept general so the closing scan is delimiter-aware.
                     let delim_start = pos;
                     while pos < text.len() && text[pos] != b')' {
                         pos += 1;
@@ -81,23 +190,10 @@ impl Lexer for CppLexer {
                     if pos < text.len() {
                         pos += 1; // Skip ')'
                     }
-                    
-                    // Find closing sequence: )delimiter"
-                    while pos < text.len() {
-                        if text[pos] == b')' {
-                            let mut match_pos = 0;
-                            let mut temp_pos = pos + 1;
-                            while match_pos < delimiter.len() && temp_pos < text.len() 
-                                  && text[temp_pos] == delimiter[match_pos] {
-                                match_pos += 1;
-                                temp_pos += 1;
-                            }
-                            if match_pos == delimiter.len() && temp_pos < text.len() && text[temp_pos] == b'"' {
-                                pos = temp_pos + 1;
-                                break;
-                            }
-                        }
-                        pos += 1;
+
+                    if !scan_raw_string_tail(text, &mut pos, delimiter) {
+                        exit = pack_raw_string_state(delimiter);
+                        log.report(LexMessage::UnterminatedRawString, start..pos);
                     }
                     tokens.push(Token::new(TokenKind::String, start..pos));
                 }
@@ -105,17 +201,9 @@ impl Lexer for CppLexer {
                 // String literal
                 b'"' => {
                     pos += 1;
-                    let mut escaped = false;
-                    while pos < text.len() {
-                        if escaped {
-                            escaped = false;
-                        } else if text[pos] == b'\\' {
-                            escaped = true;
-                        } else if text[pos] == b'"' {
-                            pos += 1;
-                            break;
-                        }
-                        pos += 1;
+                    let closed = scan_quoted(text, &mut pos, b'"');
+                    if !closed {
+                        log.report(LexMessage::UnclosedStringLiteral, start..pos);
                     }
                     tokens.push(Token::new(TokenKind::String, start..pos));
                 }
@@ -123,17 +211,9 @@ impl Lexer for CppLexer {
                 // Character literal
                 b'\'' => {
                     pos += 1;
-                    let mut escaped = false;
-                    while pos < text.len() {
-                        if escaped {
-                            escaped = false;
-                        } else if text[pos] == b'\\' {
-                            escaped = true;
-                        } else if text[pos] == b'\'' {
-                            pos += 1;
-                            break;
-                        }
-                        pos += 1;
+                    let closed = scan_quoted(text, &mut pos, b'\'');
+                    if !closed {
+                        log.report(LexMessage::UnclosedStringLiteral, start..pos);
                     }
                     tokens.push(Token::new(TokenKind::Char, start..pos));
                 }
@@ -197,40 +277,7 @@ impl Lexer for CppLexer {
                         pos += 1;
                     }
                     let word = &text[start..pos];
-                    let kind = match word {
-                        // C++ keywords (includes all C keywords plus C++-specific)
-                        b"alignas" | b"alignof" | b"and" | b"and_eq" | b"asm" | b"auto" |
-                        b"bitand" | b"bitor" | b"bool" | b"break" | b"case" | b"catch" |
-                        b"char" | b"char8_t" | b"char16_t" | b"char32_t" | b"class" |
-                        b"compl" | b"concept" | b"const" | b"const_cast" | b"consteval" |
-                        b"constexpr" | b"constinit" | b"continue" | b"co_await" | b"co_return" |
-                        b"co_yield" | b"decltype" | b"default" | b"delete" | b"do" | b"double" |
-                        b"dynamic_cast" | b"else" | b"enum" | b"explicit" | b"export" |
-                        b"extern" | b"float" | b"for" | b"friend" | b"goto" |
-                        b"if" | b"inline" | b"int" | b"long" | b"mutable" | b"namespace" |
-                        b"new" | b"noexcept" | b"not" | b"not_eq" | b"operator" |
-                        b"or" | b"or_eq" | b"private" | b"protected" | b"public" | b"register" |
-                        b"reinterpret_cast" | b"requires" | b"return" | b"short" | b"signed" |
-                        b"sizeof" | b"static" | b"static_assert" | b"static_cast" | b"struct" |
-                        b"switch" | b"template" | b"this" | b"thread_local" | b"throw" |
-                        b"try" | b"typedef" | b"typeid" | b"typename" | b"union" |
-                        b"unsigned" | b"using" | b"virtual" | b"void" | b"volatile" |
-                        b"wchar_t" | b"while" | b"xor" | b"xor_eq" => TokenKind::Keyword,
-                        
-                        // Boolean literals
-                        b"true" | b"false" | b"TRUE" | b"FALSE" => TokenKind::Boolean,
-                        
-                        // nullptr
-                        b"nullptr" | b"NULL" => TokenKind::Boolean,
-                        
-                        // Common STL types
-                        b"string" | b"vector" | b"map" | b"set" | b"list" | b"deque" |
-                        b"queue" | b"stack" | b"array" | b"pair" | b"tuple" | b"optional" |
-                        b"variant" | b"any" | b"function" | b"shared_ptr" | b"unique_ptr" |
-                        b"weak_ptr" | b"size_t" | b"ptrdiff_t" | b"nullptr_t" => TokenKind::TypeName,
-                        
-                        _ => TokenKind::Identifier,
-                    };
+                    let kind = KEYWORDS.lookup(word).unwrap_or(TokenKind::Identifier);
                     tokens.push(Token::new(kind, start..pos));
                 }
 
@@ -267,12 +314,80 @@ impl Lexer for CppLexer {
 
                 // Unknown character
                 _ => {
+                    log.report(LexMessage::UnexpectedCharacter(b), start..start + 1);
                     pos += 1;
                     tokens.push(Token::new(TokenKind::Error, start..pos));
                 }
             }
         }
 
-        tokens
+        (tokens, exit)
+    }
+}
+
+/// Advance `pos` to just past a `*/` block-comment terminator, returning `true`
+/// if one was found. On an unterminated comment `pos` lands at EOF and the
+/// result is `false`, so the caller can suspend into [`LexerState::InBlockComment`].
+fn scan_block_comment(text: &[u8], pos: &mut usize) -> bool {
+    while *pos + 1 < text.len() {
+        if text[*pos] == b'*' && text[*pos + 1] == b'/' {
+            *pos += 2;
+            return true;
+        }
+        *pos += 1;
+    }
+    *pos = text.len();
+    false
+}
+
+/// Advance `pos` to just past the `)delimiter"` that closes a C++ raw string,
+/// returning `true` if it was found. On an unterminated literal `pos` lands at
+/// EOF and the result is `false`.
+fn scan_raw_string_tail(text: &[u8], pos: &mut usize, delimiter: &[u8]) -> bool {
+    while *pos < text.len() {
+        if text[*pos] == b')' {
+            let mut match_pos = 0;
+            let mut temp_pos = *pos + 1;
+            while match_pos < delimiter.len()
+                && temp_pos < text.len()
+                && text[temp_pos] == delimiter[match_pos]
+            {
+                match_pos += 1;
+                temp_pos += 1;
+            }
+            if match_pos == delimiter.len() && temp_pos < text.len() && text[temp_pos] == b'"' {
+                *pos = temp_pos + 1;
+                return true;
+            }
+        }
+        *pos += 1;
+    }
+    false
+}
+
+/// Pack a raw-string `delimiter` into the fixed buffer carried by
+/// [`LexerState::InRawStringDelim`], truncating at the 16-byte standard limit.
+fn pack_raw_string_state(delimiter: &[u8]) -> LexerState {
+    let mut delim = [0u8; 16];
+    let len = delimiter.len().min(delim.len());
+    delim[..len].copy_from_slice(&delimiter[..len]);
+    LexerState::InRawStringDelim { delim, len: len as u8 }
+}
+
+/// Advance `pos` (positioned just past the opening `quote`) to the end of a
+/// backslash-escaped string/char body, using [`memchr2`] to jump to the next
+/// quote-or-backslash instead of inspecting every byte. Returns `true` if the
+/// closing `quote` was found; on an unterminated literal `pos` lands at EOF.
+fn scan_quoted(text: &[u8], pos: &mut usize, quote: u8) -> bool {
+    while let Some(off) = memchr2(quote, b'\\', &text[*pos..]) {
+        *pos += off;
+        if text[*pos] == quote {
+            *pos += 1;
+            return true;
+        }
+        // Backslash: skip it and the escaped byte (clamped at EOF).
+        *pos = (*pos + 2).min(text.len());
     }
+    *pos = text.len();
+    false
 }
@@ -3,15 +3,116 @@
 
 //! High-performance C# lexer with full language support.
 
-use crate::syntax::lexer::{Lexer, is_whitespace, is_ident_start, is_ident_continue, is_ascii_digit};
+use crate::syntax::lexer::diagnostic::{LexMessage, Logger};
+use crate::syntax::lexer::interp::{InterpConfig, tokenize_interpolated};
+use crate::syntax::lexer::{Diagnostic, Lexer, LexerState, is_whitespace, is_ident_start, is_ident_continue, is_ascii_digit, c_line_comment_kind, c_block_comment_kind};
 use crate::syntax::{Token, TokenKind};
 
-pub struct CSharpLexer;
+/// C# lexer.
+///
+/// By default `$"…"` interpolated strings are split so the `{expr}` holes are
+/// re-lexed as embedded C# code. Callers that want the legacy single
+/// [`TokenKind::String`] span per interpolated string — e.g. a coarse minimap —
+/// can opt out with [`CSharpLexer::non_recursing`].
+pub struct CSharpLexer {
+    /// Whether interpolation holes are recursively lexed as code.
+    recurse_interpolation: bool,
+}
+
+impl CSharpLexer {
+    /// A lexer that recurses into interpolation holes (the default).
+    pub const fn new() -> Self {
+        Self { recurse_interpolation: true }
+    }
+
+    /// A lexer that emits each interpolated string as one flat
+    /// [`TokenKind::String`] token instead of splitting out its holes.
+    pub const fn non_recursing() -> Self {
+        Self { recurse_interpolation: false }
+    }
+}
+
+impl Default for CSharpLexer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CSharpLexer {
+    /// Tokenize `text` and reclassify bare [`TokenKind::Identifier`]s into the
+    /// finer [`TokenKind::FunctionName`], [`TokenKind::TypeName`], and
+    /// [`TokenKind::PropertyName`] kinds using cheap local lookahead/lookbehind
+    /// over the token vector:
+    ///
+    /// * an identifier immediately followed by `(` is a `FunctionName`;
+    /// * an uppercase-initial identifier in type position — after `new`, `:`,
+    ///   or `<` — is a `TypeName`;
+    /// * an identifier preceded by `.` is a `PropertyName`.
+    ///
+    /// This keeps the core scanner free of heuristics while still producing
+    /// richer highlighting; downstream tools can layer their own classification
+    /// through the generic [`tokenize_mapped`](Lexer::tokenize_mapped) hook.
+    pub fn tokenize_semantic(&self, text: &[u8]) -> Vec<Token> {
+        let mut tokens = self.tokenize(text);
+        reclassify_identifiers(&mut tokens, text);
+        tokens
+    }
+}
 
 impl Lexer for CSharpLexer {
     fn tokenize(&self, text: &[u8]) -> Vec<Token> {
+        // An empty logger allocates nothing until something is reported, so the
+        // diagnostic-free path pays no extra cost.
+        self.run(text, LexerState::Normal, &mut Logger::new(), 0).0
+    }
+
+    fn tokenize_with_diagnostics(&self, text: &[u8]) -> (Vec<Token>, Vec<Diagnostic>) {
+        let mut log = Logger::new();
+        let (tokens, _) = self.run(text, LexerState::Normal, &mut log, 0);
+        (tokens, log.into_diagnostics())
+    }
+
+    fn tokenize_line(&self, line: &[u8], entry: LexerState) -> (Vec<Token>, LexerState) {
+        self.run(line, entry, &mut Logger::new(), 0)
+    }
+
+    fn tokenize_capped(&self, text: &[u8], depth: usize) -> Vec<Token> {
+        self.run(text, LexerState::Normal, &mut Logger::new(), depth).0
+    }
+}
+
+impl CSharpLexer {
+    /// Tokenize `text` starting in `entry` state, recording a [`Diagnostic`]
+    /// into `log` at the opener of any construct that runs to end-of-input
+    /// unclosed. Returns the tokens and the [`LexerState`] the scan ended in.
+    /// The exit state is `Normal` unless `text` ends inside a block comment or a
+    /// verbatim string, in which case the next line resumes from that state.
+    ///
+    /// `depth` is how many enclosing `$"..."` interpolation holes this call is
+    /// already nested inside — see [`tokenize_interpolated`]'s own `depth`
+    /// parameter for how it bounds the recursion.
+    fn run(&self, text: &[u8], entry: LexerState, log: &mut Logger, depth: usize) -> (Vec<Token>, LexerState) {
         let mut tokens = Vec::with_capacity(text.len() / 8);
         let mut pos = 0;
+        let mut exit = LexerState::Normal;
+
+        // Resume a multi-line construct carried in from the previous line.
+        match entry {
+            LexerState::InBlockComment => {
+                let closed = scan_block_comment(text, &mut pos);
+                if !closed {
+                    exit = LexerState::InBlockComment;
+                }
+                tokens.push(Token::new(c_block_comment_kind(&text[..pos]), 0..pos));
+            }
+            LexerState::InVerbatimString => {
+                if !scan_verbatim_string(text, &mut pos) {
+                    exit = LexerState::InVerbatimString;
+                }
+                tokens.push(Token::new(TokenKind::String, 0..pos));
+            }
+            _ => {}
+        }
 
         while pos < text.len() {
             let start = pos;
@@ -32,20 +133,17 @@ impl Lexer for CSharpLexer {
                     while pos < text.len() && text[pos] != b'\n' {
                         pos += 1;
                     }
-                    tokens.push(Token::new(TokenKind::Comment, start..pos));
+                    tokens.push(Token::new(c_line_comment_kind(&text[start..pos]), start..pos));
                 }
 
                 // Block comment
                 b'/' if pos + 1 < text.len() && text[pos + 1] == b'*' => {
                     pos += 2;
-                    while pos + 1 < text.len() {
-                        if text[pos] == b'*' && text[pos + 1] == b'/' {
-                            pos += 2;
-                            break;
-                        }
-                        pos += 1;
+                    if !scan_block_comment(text, &mut pos) {
+                        exit = LexerState::InBlockComment;
+                        log.report(LexMessage::UnclosedBlockComment, start..pos);
                     }
-                    tokens.push(Token::new(TokenKind::Comment, start..pos));
+                    tokens.push(Token::new(c_block_comment_kind(&text[start..pos]), start..pos));
                 }
 
                 // Preprocessor directive
@@ -73,86 +171,44 @@ impl Lexer for CSharpLexer {
                 // Verbatim string (@"...")
                 b'@' if pos + 1 < text.len() && text[pos + 1] == b'"' => {
                     pos += 2;
-                    while pos < text.len() {
-                        if text[pos] == b'"' {
-                            pos += 1;
-                            // Check for escaped quote ("")
-                            if pos < text.len() && text[pos] == b'"' {
-                                pos += 1;
-                            } else {
-                                break;
-                            }
-                        } else {
-                            pos += 1;
-                        }
+                    if !scan_verbatim_string(text, &mut pos) {
+                        exit = LexerState::InVerbatimString;
+                        log.report(LexMessage::UnclosedStringLiteral, start..pos);
                     }
                     tokens.push(Token::new(TokenKind::String, start..pos));
                 }
 
-                // Interpolated string ($"..." or $@"...")
-                b'$' if pos + 1 < text.len() && (text[pos + 1] == b'"' || 
-                        (pos + 2 < text.len() && text[pos + 1] == b'@' && text[pos + 2] == b'"')) => {
-                    pos += 1;
-                    let verbatim = if text[pos] == b'@' {
-                        pos += 1;
-                        true
+                // Interpolated string ($"...") — `{expr}` holes are lexed as
+                // embedded code rather than swallowed into the string span.
+                b'$' if pos + 1 < text.len() && text[pos + 1] == b'"' => {
+                    if self.recurse_interpolation {
+                        let cfg = InterpConfig { quote: b'"', escape: true, dollar: false, format_specifier: true };
+                        let before = tokens.len();
+                        pos = tokenize_interpolated(self, text, pos + 1, &cfg, &mut tokens, depth);
+                        // Fold the leading `$` into the first literal run.
+                        tokens[before].span.start = start;
                     } else {
-                        false
-                    };
-                    pos += 1; // Skip opening "
-                    
-                    let mut brace_depth = 0;
-                    let mut escaped = false;
-                    while pos < text.len() {
-                        if verbatim {
-                            if text[pos] == b'"' {
-                                pos += 1;
-                                if pos < text.len() && text[pos] == b'"' {
-                                    pos += 1; // Escaped quote
-                                } else {
-                                    break;
-                                }
-                            } else if text[pos] == b'{' {
-                                if pos + 1 < text.len() && text[pos + 1] == b'{' {
-                                    pos += 2; // Escaped brace
-                                } else {
-                                    brace_depth += 1;
-                                    pos += 1;
-                                }
-                            } else if text[pos] == b'}' {
-                                if brace_depth > 0 {
-                                    brace_depth -= 1;
-                                }
-                                pos += 1;
-                            } else {
-                                pos += 1;
-                            }
-                        } else {
-                            if escaped {
-                                escaped = false;
-                                pos += 1;
-                            } else if text[pos] == b'\\' {
-                                escaped = true;
-                                pos += 1;
-                            } else if text[pos] == b'"' && brace_depth == 0 {
-                                pos += 1;
-                                break;
-                            } else if text[pos] == b'{' {
-                                if pos + 1 < text.len() && text[pos + 1] == b'{' {
-                                    pos += 2; // Escaped brace
-                                } else {
-                                    brace_depth += 1;
-                                    pos += 1;
-                                }
-                            } else if text[pos] == b'}' {
-                                if brace_depth > 0 {
-                                    brace_depth -= 1;
-                                }
-                                pos += 1;
-                            } else {
-                                pos += 1;
-                            }
-                        }
+                        // Opt-out fast path: one flat String span.
+                        pos += 2;
+                        scan_quoted(text, &mut pos, b'"');
+                    }
+                    // The scan ran to EOF without a closing quote.
+                    if pos == text.len() && text.last() != Some(&b'"') {
+                        log.report(LexMessage::UnclosedStringLiteral, start..pos);
+                    }
+                    if !self.recurse_interpolation {
+                        tokens.push(Token::new(TokenKind::String, start..pos));
+                    }
+                }
+
+                // Verbatim interpolated string ($@"...") — kept as one opaque
+                // span, since `""` escaping and newlines make hole-splitting
+                // error-prone here.
+                b'$' if pos + 2 < text.len() && text[pos + 1] == b'@' && text[pos + 2] == b'"' => {
+                    pos += 3; // Skip `$@"`
+                    if !scan_verbatim_string(text, &mut pos) {
+                        exit = LexerState::InVerbatimString;
+                        log.report(LexMessage::UnclosedStringLiteral, start..pos);
                     }
                     tokens.push(Token::new(TokenKind::String, start..pos));
                 }
@@ -160,17 +216,8 @@ impl Lexer for CSharpLexer {
                 // Regular string literal
                 b'"' => {
                     pos += 1;
-                    let mut escaped = false;
-                    while pos < text.len() {
-                        if escaped {
-                            escaped = false;
-                        } else if text[pos] == b'\\' {
-                            escaped = true;
-                        } else if text[pos] == b'"' {
-                            pos += 1;
-                            break;
-                        }
-                        pos += 1;
+                    if !scan_quoted(text, &mut pos, b'"') {
+                        log.report(LexMessage::UnclosedStringLiteral, start..pos);
                     }
                     tokens.push(Token::new(TokenKind::String, start..pos));
                 }
@@ -178,17 +225,8 @@ impl Lexer for CSharpLexer {
                 // Character literal
                 b'\'' => {
                     pos += 1;
-                    let mut escaped = false;
-                    while pos < text.len() {
-                        if escaped {
-                            escaped = false;
-                        } else if text[pos] == b'\\' {
-                            escaped = true;
-                        } else if text[pos] == b'\'' {
-                            pos += 1;
-                            break;
-                        }
-                        pos += 1;
+                    if !scan_quoted(text, &mut pos, b'\'') {
+                        log.report(LexMessage::UnclosedStringLiteral, start..pos);
                     }
                     tokens.push(Token::new(TokenKind::Char, start..pos));
                 }
@@ -248,41 +286,7 @@ impl Lexer for CSharpLexer {
                         pos += 1;
                     }
                     let word = &text[start..pos];
-                    let kind = match word {
-                        // C# keywords
-                        b"abstract" | b"as" | b"base" | b"bool" | b"break" | b"byte" |
-                        b"case" | b"catch" | b"char" | b"checked" | b"class" | b"const" |
-                        b"continue" | b"decimal" | b"default" | b"delegate" | b"do" |
-                        b"double" | b"else" | b"enum" | b"event" | b"explicit" | b"extern" |
-                        b"finally" | b"fixed" | b"float" | b"for" | b"foreach" |
-                        b"goto" | b"if" | b"implicit" | b"in" | b"int" | b"interface" |
-                        b"internal" | b"is" | b"lock" | b"long" | b"namespace" | b"new" |
-                        b"object" | b"operator" | b"out" | b"override" | b"params" |
-                        b"private" | b"protected" | b"public" | b"readonly" | b"ref" |
-                        b"return" | b"sbyte" | b"sealed" | b"short" | b"sizeof" | b"stackalloc" |
-                        b"static" | b"string" | b"struct" | b"switch" | b"this" | b"throw" |
-                        b"try" | b"typeof" | b"uint" | b"ulong" | b"unchecked" |
-                        b"unsafe" | b"ushort" | b"using" | b"virtual" | b"void" | b"volatile" |
-                        b"while" => TokenKind::Keyword,
-                        
-                        // Contextual keywords
-                        b"add" | b"alias" | b"ascending" | b"async" | b"await" | b"by" |
-                        b"descending" | b"dynamic" | b"equals" | b"from" | b"get" | b"global" |
-                        b"group" | b"into" | b"join" | b"let" | b"nameof" | b"on" | b"orderby" |
-                        b"partial" | b"remove" | b"select" | b"set" | b"value" | b"var" |
-                        b"when" | b"where" | b"yield" => TokenKind::Keyword,
-                        
-                        // C# 9.0+ keywords
-                        b"record" | b"init" | b"with" | b"nint" | b"nuint" => TokenKind::Keyword,
-                        
-                        // Boolean literals
-                        b"true" | b"false" => TokenKind::Boolean,
-                        
-                        // Null
-                        b"null" => TokenKind::Boolean,
-                        
-                        _ => TokenKind::Identifier,
-                    };
+                    let kind = classify_word(word);
                     tokens.push(Token::new(kind, start..pos));
                 }
 
@@ -320,12 +324,146 @@ impl Lexer for CSharpLexer {
 
                 // Unknown character
                 _ => {
+                    log.report(LexMessage::UnexpectedCharacter(b), start..start + 1);
                     pos += 1;
                     tokens.push(Token::new(TokenKind::Error, start..pos));
                 }
             }
         }
 
-        tokens
+        (tokens, exit)
+    }
+}
+
+/// Advance `pos` past a `*/` block-comment terminator, returning `true` if one
+/// was found. On an unterminated comment `pos` lands at EOF and the result is
+/// `false` so the caller can suspend into [`LexerState::InBlockComment`].
+fn scan_block_comment(text: &[u8], pos: &mut usize) -> bool {
+    while *pos + 1 < text.len() {
+        if text[*pos] == b'*' && text[*pos + 1] == b'/' {
+            *pos += 2;
+            return true;
+        }
+        *pos += 1;
+    }
+    *pos = text.len();
+    false
+}
+
+/// Advance `pos` (positioned just past the opening `@"`) past the closing `"` of
+/// a verbatim string, treating `""` as an escaped quote. Returns `true` if the
+/// close was found; on an unterminated literal `pos` lands at EOF and the result
+/// is `false`.
+fn scan_verbatim_string(text: &[u8], pos: &mut usize) -> bool {
+    while *pos < text.len() {
+        if text[*pos] == b'"' {
+            *pos += 1;
+            if *pos < text.len() && text[*pos] == b'"' {
+                *pos += 1; // Escaped quote
+            } else {
+                return true;
+            }
+        } else {
+            *pos += 1;
+        }
+    }
+    false
+}
+
+/// Upgrade bare [`TokenKind::Identifier`] tokens in place using neighboring
+/// tokens (skipping whitespace and comments) for context. See
+/// [`CSharpLexer::tokenize_semantic`] for the rules applied.
+fn reclassify_identifiers(tokens: &mut [Token], text: &[u8]) {
+    let non_trivia = |i: usize| !tokens[i].kind.is_trivia();
+    let prev = |i: usize| (0..i).rev().find(|&j| non_trivia(j));
+    let next = |i: usize| (i + 1..tokens.len()).find(|&j| non_trivia(j));
+
+    let mut upgrades: Vec<(usize, TokenKind)> = Vec::new();
+    for i in 0..tokens.len() {
+        if tokens[i].kind != TokenKind::Identifier {
+            continue;
+        }
+        let slice = &text[tokens[i].span.clone()];
+        let next_slice = next(i).map(|j| &text[tokens[j].span.clone()]);
+        let prev_slice = prev(i).map(|j| &text[tokens[j].span.clone()]);
+
+        // A call: `name(`.
+        if next_slice == Some(&b"("[..]) {
+            upgrades.push((i, TokenKind::FunctionName));
+            continue;
+        }
+        // A member access: `.name`.
+        if prev_slice == Some(&b"."[..]) {
+            upgrades.push((i, TokenKind::PropertyName));
+            continue;
+        }
+        // A type in `new T`, `: T`, or `<T`, recognized by its uppercase lead.
+        let uppercase = slice.first().is_some_and(u8::is_ascii_uppercase);
+        let type_position = matches!(prev_slice, Some(p) if p == b"new" || p == b":" || p == b"<");
+        if uppercase && type_position {
+            upgrades.push((i, TokenKind::TypeName));
+        }
+    }
+    for (i, kind) in upgrades {
+        tokens[i].kind = kind;
+    }
+}
+
+/// Advance `pos` (positioned just past the opening `quote`) past the end of a
+/// backslash-escaped string/char body. Returns `true` if the closing `quote`
+/// was found; on an unterminated literal `pos` lands at EOF and the result is
+/// `false`.
+fn scan_quoted(text: &[u8], pos: &mut usize, quote: u8) -> bool {
+    let mut escaped = false;
+    while *pos < text.len() {
+        if escaped {
+            escaped = false;
+        } else if text[*pos] == b'\\' {
+            escaped = true;
+        } else if text[*pos] == quote {
+            *pos += 1;
+            return true;
+        }
+        *pos += 1;
+    }
+    false
+}
+
+/// Classify an identifier slice as a keyword, literal, or plain identifier.
+fn classify_word(word: &[u8]) -> TokenKind {
+    match word {
+        // C# keywords
+        b"abstract" | b"as" | b"base" | b"bool" | b"break" | b"byte" |
+        b"case" | b"catch" | b"char" | b"checked" | b"class" | b"const" |
+        b"continue" | b"decimal" | b"default" | b"delegate" | b"do" |
+        b"double" | b"else" | b"enum" | b"event" | b"explicit" | b"extern" |
+        b"finally" | b"fixed" | b"float" | b"for" | b"foreach" |
+        b"goto" | b"if" | b"implicit" | b"in" | b"int" | b"interface" |
+        b"internal" | b"is" | b"lock" | b"long" | b"namespace" | b"new" |
+        b"object" | b"operator" | b"out" | b"override" | b"params" |
+        b"private" | b"protected" | b"public" | b"readonly" | b"ref" |
+        b"return" | b"sbyte" | b"sealed" | b"short" | b"sizeof" | b"stackalloc" |
+        b"static" | b"string" | b"struct" | b"switch" | b"this" | b"throw" |
+        b"try" | b"typeof" | b"uint" | b"ulong" | b"unchecked" |
+        b"unsafe" | b"ushort" | b"using" | b"virtual" | b"void" | b"volatile" |
+        b"while" => TokenKind::Keyword,
+
+        // Contextual keywords
+        b"add" | b"alias" | b"ascending" | b"async" | b"await" | b"by" |
+        b"descending" | b"dynamic" | b"equals" | b"from" | b"get" | b"global" |
+        b"group" | b"into" | b"join" | b"let" | b"nameof" | b"on" | b"orderby" |
+        b"partial" | b"remove" | b"select" | b"set" | b"value" | b"var" |
+        b"when" | b"where" | b"yield" => TokenKind::Keyword,
+
+        // C# 9.0+ keywords
+        b"record" | b"init" | b"with" | b"nint" | b"nuint" => TokenKind::Keyword,
+
+        // Boolean literals
+        b"true" | b"false" => TokenKind::Boolean,
+
+        // Null
+        b"null" => TokenKind::Boolean,
+
+        _ => TokenKind::Identifier,
     }
 }
@@ -3,15 +3,50 @@
 
 //! High-performance CSS lexer with full language support.
 
-use crate::syntax::lexer::{Lexer, is_whitespace, is_ident_start, is_ident_continue};
+use crate::syntax::lexer::diagnostic::{LexMessage, Logger};
+use crate::syntax::lexer::{Diagnostic, Lexer, LexerState, is_whitespace, is_ident_start, is_ident_continue, ident_start_len, ident_continue_len, first_code_point};
 use crate::syntax::{Token, TokenKind};
 
 pub struct CssLexer;
 
 impl Lexer for CssLexer {
     fn tokenize(&self, text: &[u8]) -> Vec<Token> {
+        // An empty logger allocates nothing until something is reported.
+        self.run(text, LexerState::Normal, &mut Logger::new()).0
+    }
+
+    fn tokenize_with_diagnostics(&self, text: &[u8]) -> (Vec<Token>, Vec<Diagnostic>) {
+        let mut log = Logger::new();
+        let (tokens, _) = self.run(text, LexerState::Normal, &mut log);
+        (tokens, log.into_diagnostics())
+    }
+
+    fn tokenize_line(&self, line: &[u8], entry: LexerState) -> (Vec<Token>, LexerState) {
+        self.run(line, entry, &mut Logger::new())
+    }
+}
+
+impl CssLexer {
+    /// Tokenize `text` starting in `entry` state, recording a diagnostic into
+    /// `log` for a `/* */` block comment that reaches end-of-input unclosed
+    /// ([`UnclosedBlockComment`](LexMessage::UnclosedBlockComment)) or a quoted
+    /// string that does the same
+    /// ([`UnclosedStringLiteral`](LexMessage::UnclosedStringLiteral)). Returns
+    /// the tokens and the [`LexerState`] the scan ended in — `InBlockComment`
+    /// when `text` ends inside an unclosed `/* */`, else `Normal`.
+    fn run(&self, text: &[u8], entry: LexerState, log: &mut Logger) -> (Vec<Token>, LexerState) {
         let mut tokens = Vec::with_capacity(text.len() / 8);
         let mut pos = 0;
+        let mut exit = LexerState::Normal;
+
+        // Resume a `/* */` block comment carried over from the previous line.
+        if entry == LexerState::InBlockComment {
+            let closed = scan_css_block_close(text, &mut pos);
+            if !closed {
+                exit = LexerState::InBlockComment;
+            }
+            tokens.push(Token::new(TokenKind::Comment, 0..pos));
+        }
 
         while pos < text.len() {
             let start = pos;
@@ -29,12 +64,9 @@ impl Lexer for CssLexer {
                 // Block comment
                 b'/' if pos + 1 < text.len() && text[pos + 1] == b'*' => {
                     pos += 2;
-                    while pos + 1 < text.len() {
-                        if text[pos] == b'*' && text[pos + 1] == b'/' {
-                            pos += 2;
-                            break;
-                        }
-                        pos += 1;
+                    if !scan_css_block_close(text, &mut pos) {
+                        exit = LexerState::InBlockComment;
+                        log.report(LexMessage::UnclosedBlockComment, start..pos);
                     }
                     tokens.push(Token::new(TokenKind::Comment, start..pos));
                 }
@@ -71,6 +103,7 @@ impl Lexer for CssLexer {
                     let quote = b;
                     pos += 1;
                     let mut escaped = false;
+                    let mut closed = false;
                     while pos < text.len() {
                         if escaped {
                             escaped = false;
@@ -78,10 +111,16 @@ impl Lexer for CssLexer {
                             escaped = true;
                         } else if text[pos] == quote {
                             pos += 1;
+                            closed = true;
                             break;
+                        } else if text[pos] == b'\n' {
+                            break; // Unterminated string
                         }
                         pos += 1;
                     }
+                    if !closed {
+                        log.report(LexMessage::UnclosedStringLiteral, start..pos);
+                    }
                     tokens.push(Token::new(TokenKind::String, start..pos));
                 }
 
@@ -104,12 +143,23 @@ impl Lexer for CssLexer {
                     tokens.push(Token::new(TokenKind::Number, start..pos));
                 }
 
-                // Identifier (properties, values, selectors)
-                _ if is_ident_start(b) || b == b'-' => {
-                    while pos < text.len() && (is_ident_continue(text[pos]) || text[pos] == b'-') {
+                // Identifier (properties, values, selectors). Unicode-aware so
+                // custom properties and content values with non-ASCII names lex
+                // as a single identifier when the `unicode-ident` feature is on.
+                _ if ident_start_len(&text[pos..]).is_some() || b == b'-' => {
+                    if b == b'-' {
                         pos += 1;
                     }
-                    
+                    loop {
+                        if pos < text.len() && text[pos] == b'-' {
+                            pos += 1;
+                        } else if let Some(len) = ident_continue_len(&text[pos..]) {
+                            pos += len;
+                        } else {
+                            break;
+                        }
+                    }
+
                     let word = &text[start..pos];
                     let kind = match word {
                         // CSS keywords and values
@@ -141,14 +191,31 @@ impl Lexer for CssLexer {
                     tokens.push(Token::new(TokenKind::Operator, start..pos));
                 }
 
-                // Other characters
+                // Other characters: advance by a whole code point so a stray
+                // multi-byte sequence stays a single token.
                 _ => {
-                    pos += 1;
+                    pos += first_code_point(&text[pos..]).map_or(1, |(_, len)| len);
                     tokens.push(Token::new(TokenKind::Operator, start..pos));
                 }
             }
         }
 
-        tokens
+        (tokens, exit)
+    }
+}
+
+/// Advance `pos` to just past a `*/` block-comment terminator, returning `true`
+/// if one was found. On an unterminated comment `pos` lands at EOF and the
+/// result is `false`, so the caller can suspend into
+/// [`LexerState::InBlockComment`].
+fn scan_css_block_close(text: &[u8], pos: &mut usize) -> bool {
+    while *pos + 1 < text.len() {
+        if text[*pos] == b'*' && text[*pos + 1] == b'/' {
+            *pos += 2;
+            return true;
+        }
+        *pos += 1;
     }
+    *pos = text.len();
+    false
 }
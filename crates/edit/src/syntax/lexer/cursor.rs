@@ -0,0 +1,87 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! A UTF-8 aware cursor shared by the byte-scanning lexers.
+//!
+//! The older lexers dispatch on `bytes[pos] as char`, which mangles any
+//! multi-byte UTF-8 sequence — a comment, string body, or identifier
+//! containing non-ASCII text decodes to a run of replacement bytes and falls
+//! through to [`TokenKind::Error`](crate::syntax::TokenKind::Error) with
+//! misaligned spans. [`Cursor`] walks a `&str` by real `char` boundaries while
+//! still reporting byte offsets, so spans stay compatible with the rest of the
+//! pipeline and non-ASCII input tokenizes correctly.
+
+/// A forward cursor over a `&str`, advancing by UTF-8 `char` boundaries and
+/// reporting positions as byte offsets.
+pub(crate) struct Cursor<'a> {
+    text: &'a str,
+    off: usize,
+}
+
+impl<'a> Cursor<'a> {
+    /// Create a cursor over `text`, positioned at the start.
+    pub(crate) fn new(text: &'a str) -> Self {
+        Self { text, off: 0 }
+    }
+
+    /// The current byte offset.
+    #[inline]
+    pub(crate) fn offset(&self) -> usize {
+        self.off
+    }
+
+    /// Jump to an absolute byte offset. The offset must lie on a `char`
+    /// boundary (it always does when it comes from a byte-level sub-scan that
+    /// began and ended on boundaries).
+    #[inline]
+    pub(crate) fn seek(&mut self, off: usize) {
+        self.off = off.min(self.text.len());
+    }
+
+    /// The character at the cursor without advancing.
+    #[inline]
+    pub(crate) fn peek(&self) -> Option<char> {
+        self.text[self.off..].chars().next()
+    }
+
+    /// The character one past the cursor without advancing.
+    #[inline]
+    pub(crate) fn peek2(&self) -> Option<char> {
+        let mut it = self.text[self.off..].chars();
+        it.next();
+        it.next()
+    }
+
+    /// Consume and return the character at the cursor.
+    #[inline]
+    pub(crate) fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.off += c.len_utf8();
+        Some(c)
+    }
+
+    /// Advance while `pred` holds, returning the byte offset reached.
+    #[inline]
+    pub(crate) fn eat_while(&mut self, mut pred: impl FnMut(char) -> bool) -> usize {
+        while let Some(c) = self.peek() {
+            if !pred(c) {
+                break;
+            }
+            self.off += c.len_utf8();
+        }
+        self.off
+    }
+}
+
+/// Whether `c` can start an identifier: an underscore or any Unicode letter.
+#[inline]
+pub(crate) fn is_ident_start(c: char) -> bool {
+    c == '_' || c.is_alphabetic()
+}
+
+/// Whether `c` can continue an identifier: an underscore or any Unicode
+/// alphanumeric.
+#[inline]
+pub(crate) fn is_ident_continue(c: char) -> bool {
+    c == '_' || c.is_alphanumeric()
+}
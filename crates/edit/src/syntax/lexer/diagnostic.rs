@@ -0,0 +1,106 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Structured diagnostics for malformed tokens.
+//!
+//! The fast [`tokenize`](super::Lexer::tokenize) path always produces a token
+//! for every byte, even when a construct such as a string or block comment is
+//! never closed — it just runs the token to end-of-input. That keeps
+//! highlighting lossless, but it throws away the fact that something was
+//! wrong. Lexers that opt into [`tokenize_with_diagnostics`] push a
+//! [`Diagnostic`] into a [`Logger`] sink at the exact span where a terminator
+//! was expected, so an editor can render squiggles without re-parsing.
+
+use std::ops::Range;
+
+/// How serious a [`Diagnostic`] is. An editor renders errors and warnings
+/// differently (red vs. yellow squiggles); lint-style problems that do not make
+/// the document unparseable are [`Warning`](Severity::Warning).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// A hard problem: the construct is malformed.
+    Error,
+    /// A lint: the document parses, but the construct is questionable or
+    /// non-standard (e.g. a trailing comma, or a comment in strict JSON).
+    Warning,
+}
+
+/// A single problem discovered while lexing, tagged with the source span it
+/// covers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// What went wrong.
+    pub message: LexMessage,
+    /// How serious the problem is (derived from `message`).
+    pub severity: Severity,
+    /// The byte range of the offending construct, from its opener to where the
+    /// lexer gave up (usually end-of-input).
+    pub span: Range<usize>,
+}
+
+/// The kind of problem a [`Diagnostic`] reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LexMessage {
+    /// A `"`, `` ` ``, or `'` literal ran to end-of-input without closing.
+    UnclosedStringLiteral,
+    /// A `/* */`/`<!-- -->` block comment ran to end-of-input without closing.
+    UnclosedBlockComment,
+    /// A C++ raw string (`R"delim( ... )delim"`) ran to end-of-input without its
+    /// closing `)delim"` sequence.
+    UnterminatedRawString,
+    /// A byte that does not begin any valid token in this language.
+    UnexpectedCharacter(u8),
+    /// A tag (`<...>`) ran to end-of-input without a closing `>`.
+    UnterminatedTag,
+    /// A here-string (`@"`…`"@` / `@'`…`'@`) ran to end-of-input unclosed.
+    UnterminatedHereString,
+    /// A braced variable (`${ ... }`) ran to end-of-input without its `}`.
+    UnterminatedBracedVariable,
+    /// A `,` directly before a closing `}`/`]` — illegal in strict JSON.
+    TrailingComma,
+    /// An object key that repeats one already seen in the same object.
+    DuplicateKey,
+    /// A `//` or `/* */` comment, which is only legal in JSONC, not strict JSON.
+    CommentInStrictJson,
+}
+
+impl LexMessage {
+    /// The [`Severity`] this kind of problem carries. The malformed-construct
+    /// cases are errors; the JSON lint cases (which still parse) are warnings.
+    pub fn severity(self) -> Severity {
+        match self {
+            LexMessage::TrailingComma
+            | LexMessage::DuplicateKey
+            | LexMessage::CommentInStrictJson => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+/// A sink that lexers push diagnostics into while tokenizing.
+///
+/// This is a thin wrapper over a `Vec` rather than a trait object so the hot
+/// path stays a plain push; the indirection exists only to give the recording
+/// sites a readable name (`logger.report(...)`).
+#[derive(Debug, Default)]
+pub struct Logger {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Logger {
+    /// Create an empty logger.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a diagnostic at the given span.
+    #[inline]
+    pub fn report(&mut self, message: LexMessage, span: Range<usize>) {
+        self.diagnostics.push(Diagnostic { message, severity: message.severity(), span });
+    }
+
+    /// Consume the logger and return the collected diagnostics.
+    pub fn into_diagnostics(self) -> Vec<Diagnostic> {
+        self.diagnostics
+    }
+}
@@ -0,0 +1,71 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! A 256-entry byte-handler dispatch table for table-driven lexers.
+//!
+//! A classic lexer runs a large `match text[pos]` on every byte, which the
+//! compiler lowers to a cascade of comparisons for the sparse punctuation arms.
+//! A [`DispatchTable`] replaces that first-byte decision with a single array
+//! load and indirect call: each of the 256 slots points at the handler for
+//! that byte class (whitespace run, identifier start, digit, quote, …). The
+//! table is built once per language as a `static`, so the cost is paid at
+//! program start rather than on the hot path.
+//!
+//! Handlers share the lexer's working state through [`LexState`]; each one
+//! consumes one token's worth of bytes, advances [`LexState::pos`], and pushes
+//! onto [`LexState::tokens`].
+
+use crate::syntax::lexer::Logger;
+use crate::syntax::Token;
+
+/// The mutable state threaded through every byte handler.
+pub(crate) struct LexState<'a> {
+    /// The source being tokenized.
+    pub text: &'a [u8],
+    /// The current byte offset; a handler advances this past what it consumes.
+    pub pos: usize,
+    /// Tokens produced so far.
+    pub tokens: Vec<Token>,
+    /// Diagnostics sink for malformed constructs.
+    pub log: &'a mut Logger,
+}
+
+/// A handler for the byte at [`LexState::pos`]. It must advance `pos` by at
+/// least one so the driver loop always makes progress.
+pub(crate) type ByteHandler = fn(&mut LexState<'_>);
+
+/// A fixed map from each of the 256 possible leading bytes to its handler.
+pub(crate) struct DispatchTable([ByteHandler; 256]);
+
+impl DispatchTable {
+    /// Build a table whose every slot is `default`, to be overridden with
+    /// [`set`](Self::set)/[`set_range`](Self::set_range).
+    pub(crate) const fn new(default: ByteHandler) -> Self {
+        DispatchTable([default; 256])
+    }
+
+    /// Point `byte`'s slot at `handler`.
+    pub(crate) const fn set(mut self, byte: u8, handler: ByteHandler) -> Self {
+        self.0[byte as usize] = handler;
+        self
+    }
+
+    /// Point every slot in `lo..=hi` at `handler`.
+    pub(crate) const fn set_range(mut self, lo: u8, hi: u8, handler: ByteHandler) -> Self {
+        let mut b = lo;
+        loop {
+            self.0[b as usize] = handler;
+            if b == hi {
+                break;
+            }
+            b += 1;
+        }
+        self
+    }
+
+    /// The handler for `byte` — a single array load.
+    #[inline]
+    pub(crate) fn dispatch(&self, byte: u8) -> ByteHandler {
+        self.0[byte as usize]
+    }
+}
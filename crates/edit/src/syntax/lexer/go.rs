@@ -3,15 +3,124 @@
 
 //! High-performance Go lexer with full language support.
 
-use crate::syntax::lexer::{Lexer, is_whitespace, is_ident_start, is_ident_continue, is_ascii_digit};
+use crate::syntax::lexer::diagnostic::{LexMessage, Logger};
+use crate::syntax::lexer::keyword::KeywordTable;
+use crate::syntax::lexer::{Diagnostic, Lexer, LexerState, is_whitespace, is_ident_start, is_ident_continue, is_ascii_digit, c_line_comment_kind, c_block_comment_kind};
 use crate::syntax::{Token, TokenKind};
 
 pub struct GoLexer;
 
+/// Go's reserved words, predeclared types, built-in functions and constants,
+/// classified into the token kinds the theme colors. Editing this list is the
+/// only thing needed to teach the lexer a new keyword.
+static KEYWORDS: KeywordTable = KeywordTable::new(&[
+    // Keywords
+    (b"break", TokenKind::Keyword), (b"case", TokenKind::Keyword),
+    (b"chan", TokenKind::Keyword), (b"const", TokenKind::Keyword),
+    (b"continue", TokenKind::Keyword), (b"default", TokenKind::Keyword),
+    (b"defer", TokenKind::Keyword), (b"else", TokenKind::Keyword),
+    (b"fallthrough", TokenKind::Keyword), (b"for", TokenKind::Keyword),
+    (b"func", TokenKind::Keyword), (b"go", TokenKind::Keyword),
+    (b"goto", TokenKind::Keyword), (b"if", TokenKind::Keyword),
+    (b"import", TokenKind::Keyword), (b"interface", TokenKind::Keyword),
+    (b"map", TokenKind::Keyword), (b"package", TokenKind::Keyword),
+    (b"range", TokenKind::Keyword), (b"return", TokenKind::Keyword),
+    (b"select", TokenKind::Keyword), (b"struct", TokenKind::Keyword),
+    (b"switch", TokenKind::Keyword), (b"type", TokenKind::Keyword),
+    (b"var", TokenKind::Keyword), (b"iota", TokenKind::Keyword),
+    // Boolean and nil literals
+    (b"true", TokenKind::Boolean), (b"false", TokenKind::Boolean),
+    (b"nil", TokenKind::Boolean),
+    // Built-in types
+    (b"bool", TokenKind::TypeName), (b"byte", TokenKind::TypeName),
+    (b"complex64", TokenKind::TypeName), (b"complex128", TokenKind::TypeName),
+    (b"error", TokenKind::TypeName), (b"float32", TokenKind::TypeName),
+    (b"float64", TokenKind::TypeName), (b"int", TokenKind::TypeName),
+    (b"int8", TokenKind::TypeName), (b"int16", TokenKind::TypeName),
+    (b"int32", TokenKind::TypeName), (b"int64", TokenKind::TypeName),
+    (b"rune", TokenKind::TypeName), (b"string", TokenKind::TypeName),
+    (b"uint", TokenKind::TypeName), (b"uint8", TokenKind::TypeName),
+    (b"uint16", TokenKind::TypeName), (b"uint32", TokenKind::TypeName),
+    (b"uint64", TokenKind::TypeName), (b"uintptr", TokenKind::TypeName),
+    // Built-in functions
+    (b"append", TokenKind::FunctionName), (b"cap", TokenKind::FunctionName),
+    (b"close", TokenKind::FunctionName), (b"complex", TokenKind::FunctionName),
+    (b"copy", TokenKind::FunctionName), (b"delete", TokenKind::FunctionName),
+    (b"imag", TokenKind::FunctionName), (b"len", TokenKind::FunctionName),
+    (b"make", TokenKind::FunctionName), (b"new", TokenKind::FunctionName),
+    (b"panic", TokenKind::FunctionName), (b"print", TokenKind::FunctionName),
+    (b"println", TokenKind::FunctionName), (b"real", TokenKind::FunctionName),
+    (b"recover", TokenKind::FunctionName),
+]);
+
+/// Classify a scanned identifier, returning its keyword kind or `None` for a
+/// plain identifier. O(word length) via the shared keyword hash table.
+pub(crate) fn lookup_keyword(word: &[u8]) -> Option<TokenKind> {
+    KEYWORDS.lookup(word)
+}
+
 impl Lexer for GoLexer {
     fn tokenize(&self, text: &[u8]) -> Vec<Token> {
+        // An empty logger allocates nothing until something is reported, so
+        // the diagnostic-free path pays no extra cost.
+        let mut log = Logger::new();
+        self.run(text, LexerState::Normal, &mut log).0
+    }
+
+    fn tokenize_with_diagnostics(&self, text: &[u8]) -> (Vec<Token>, Vec<Diagnostic>) {
+        let mut log = Logger::new();
+        let (tokens, _) = self.run(text, LexerState::Normal, &mut log);
+        (tokens, log.into_diagnostics())
+    }
+
+    fn tokenize_line(&self, line: &[u8], entry: LexerState) -> (Vec<Token>, LexerState) {
+        let mut log = Logger::new();
+        self.run(line, entry, &mut log)
+    }
+}
+
+impl GoLexer {
+    /// Scan `text` into tokens starting in `entry` state, recording diagnostics
+    /// for unterminated literals and comments into `log`. Returns the tokens
+    /// and the [`LexerState`] the scan ended in (`Normal` unless `text` ends
+    /// inside a block comment or raw string).
+    fn run(&self, text: &[u8], entry: LexerState, log: &mut Logger) -> (Vec<Token>, LexerState) {
         let mut tokens = Vec::with_capacity(text.len() / 8);
         let mut pos = 0;
+        let mut exit = LexerState::Normal;
+
+        // Resume a multi-line construct carried in from the previous line.
+        match entry {
+            LexerState::InBlockComment => {
+                let mut closed = false;
+                while pos + 1 < text.len() {
+                    if text[pos] == b'*' && text[pos + 1] == b'/' {
+                        pos += 2;
+                        closed = true;
+                        break;
+                    }
+                    pos += 1;
+                }
+                if !closed {
+                    pos = text.len();
+                    exit = LexerState::InBlockComment;
+                }
+                tokens.push(Token::new(TokenKind::BlockComment, 0..pos));
+            }
+            LexerState::InRawString => {
+                while pos < text.len() && text[pos] != b'`' {
+                    pos += 1;
+                }
+                if pos < text.len() {
+                    pos += 1;
+                } else {
+                    exit = LexerState::InRawString;
+                }
+                tokens.push(Token::new(TokenKind::String, 0..pos));
+            }
+            _ => {}
+        }
+
 
         while pos < text.len() {
             let start = pos;
@@ -32,20 +141,27 @@ impl Lexer for GoLexer {
                     while pos < text.len() && text[pos] != b'\n' {
                         pos += 1;
                     }
-                    tokens.push(Token::new(TokenKind::Comment, start..pos));
+                    tokens.push(Token::new(c_line_comment_kind(&text[start..pos]), start..pos));
                 }
 
                 // Block comment
                 b'/' if pos + 1 < text.len() && text[pos + 1] == b'*' => {
                     pos += 2;
+                    let mut closed = false;
                     while pos + 1 < text.len() {
                         if text[pos] == b'*' && text[pos + 1] == b'/' {
                             pos += 2;
+                            closed = true;
                             break;
                         }
                         pos += 1;
                     }
-                    tokens.push(Token::new(TokenKind::Comment, start..pos));
+                    if !closed {
+                        pos = text.len();
+                        exit = LexerState::InBlockComment;
+                        log.report(LexMessage::UnclosedBlockComment, start..pos);
+                    }
+                    tokens.push(Token::new(c_block_comment_kind(&text[start..pos]), start..pos));
                 }
 
                 // Raw string literal (`...`)
@@ -56,6 +172,9 @@ impl Lexer for GoLexer {
                     }
                     if pos < text.len() {
                         pos += 1; // Skip closing backtick
+                    } else {
+                        exit = LexerState::InRawString;
+                        log.report(LexMessage::UnclosedStringLiteral, start..pos);
                     }
                     tokens.push(Token::new(TokenKind::String, start..pos));
                 }
@@ -64,6 +183,7 @@ impl Lexer for GoLexer {
                 b'"' => {
                     pos += 1;
                     let mut escaped = false;
+                    let mut closed = false;
                     while pos < text.len() {
                         if escaped {
                             escaped = false;
@@ -71,10 +191,14 @@ impl Lexer for GoLexer {
                             escaped = true;
                         } else if text[pos] == b'"' {
                             pos += 1;
+                            closed = true;
                             break;
                         }
                         pos += 1;
                     }
+                    if !closed {
+                        log.report(LexMessage::UnclosedStringLiteral, start..pos);
+                    }
                     tokens.push(Token::new(TokenKind::String, start..pos));
                 }
 
@@ -82,6 +206,7 @@ impl Lexer for GoLexer {
                 b'\'' => {
                     pos += 1;
                     let mut escaped = false;
+                    let mut closed = false;
                     while pos < text.len() {
                         if escaped {
                             escaped = false;
@@ -89,10 +214,14 @@ impl Lexer for GoLexer {
                             escaped = true;
                         } else if text[pos] == b'\'' {
                             pos += 1;
+                            closed = true;
                             break;
                         }
                         pos += 1;
                     }
+                    if !closed {
+                        log.report(LexMessage::UnclosedStringLiteral, start..pos);
+                    }
                     tokens.push(Token::new(TokenKind::Char, start..pos));
                 }
 
@@ -155,36 +284,7 @@ impl Lexer for GoLexer {
                         pos += 1;
                     }
                     let word = &text[start..pos];
-                    let kind = match word {
-                        // Go keywords
-                        b"break" | b"case" | b"chan" | b"const" | b"continue" |
-                        b"default" | b"defer" | b"else" | b"fallthrough" | b"for" |
-                        b"func" | b"go" | b"goto" | b"if" | b"import" | b"interface" |
-                        b"map" | b"package" | b"range" | b"return" | b"select" |
-                        b"struct" | b"switch" | b"type" | b"var" => TokenKind::Keyword,
-                        
-                        // Boolean literals
-                        b"true" | b"false" => TokenKind::Boolean,
-                        
-                        // Nil
-                        b"nil" => TokenKind::Boolean,
-                        
-                        // Built-in types
-                        b"bool" | b"byte" | b"complex64" | b"complex128" | b"error" |
-                        b"float32" | b"float64" | b"int" | b"int8" | b"int16" |
-                        b"int32" | b"int64" | b"rune" | b"string" | b"uint" |
-                        b"uint8" | b"uint16" | b"uint32" | b"uint64" | b"uintptr" => TokenKind::TypeName,
-                        
-                        // Built-in functions
-                        b"append" | b"cap" | b"close" | b"complex" | b"copy" |
-                        b"delete" | b"imag" | b"len" | b"make" | b"new" |
-                        b"panic" | b"print" | b"println" | b"real" | b"recover" => TokenKind::FunctionName,
-                        
-                        // Special identifiers
-                        b"iota" => TokenKind::Keyword,
-                        
-                        _ => TokenKind::Identifier,
-                    };
+                    let kind = lookup_keyword(word).unwrap_or(TokenKind::Identifier);
                     tokens.push(Token::new(kind, start..pos));
                 }
 
@@ -223,11 +323,12 @@ impl Lexer for GoLexer {
                 // Unknown character
                 _ => {
                     pos += 1;
+                    log.report(LexMessage::UnexpectedCharacter(b), start..pos);
                     tokens.push(Token::new(TokenKind::Error, start..pos));
                 }
             }
         }
 
-        tokens
+        (tokens, exit)
     }
 }
@@ -3,13 +3,96 @@
 
 //! High-performance HTML lexer with full language support.
 
-use crate::syntax::lexer::{Lexer, is_whitespace};
+use std::collections::HashMap;
+
+use crate::syntax::lexer::diagnostic::{LexMessage, Logger};
+use crate::syntax::lexer::{Diagnostic, Lexer, LexerState, is_whitespace};
 use crate::syntax::{Token, TokenKind};
 
-pub struct HtmlLexer;
+/// An HTML lexer that can delegate the body of raw-text elements (`<script>`,
+/// `<style>`) to a sub-language lexer.
+///
+/// Sublexers are registered by lowercase tag name via [`with_sublexer`]. When
+/// none is registered for an element the body is emitted as plain text, as
+/// before.
+///
+/// [`with_sublexer`]: HtmlLexer::with_sublexer
+#[derive(Default)]
+pub struct HtmlLexer {
+    sublexers: HashMap<String, Box<dyn Lexer>>,
+}
+
+impl HtmlLexer {
+    /// Create an HTML lexer with no sub-language delegation.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `lexer` to highlight the body of the raw-text element named
+    /// `tag` (case-insensitive), e.g. `"script"` or `"style"`.
+    pub fn with_sublexer(mut self, tag: &str, lexer: Box<dyn Lexer>) -> Self {
+        self.sublexers.insert(tag.to_ascii_lowercase(), lexer);
+        self
+    }
+}
 
 impl Lexer for HtmlLexer {
     fn tokenize(&self, text: &[u8]) -> Vec<Token> {
+        let mut log = Logger::new();
+        self.run(text, &mut log)
+    }
+
+    fn tokenize_with_diagnostics(&self, text: &[u8]) -> (Vec<Token>, Vec<Diagnostic>) {
+        let mut log = Logger::new();
+        let tokens = self.run(text, &mut log);
+        (tokens, log.into_diagnostics())
+    }
+
+    fn tokenize_line(&self, line: &[u8], entry: LexerState) -> (Vec<Token>, LexerState) {
+        let mut tokens = Vec::new();
+        let mut offset = 0;
+
+        // Resume an `<!-- -->` comment carried in from the previous line.
+        if entry == LexerState::InHtmlComment {
+            match find_comment_end(line, 0) {
+                Some(end) => {
+                    tokens.push(Token::new(TokenKind::Comment, 0..end));
+                    offset = end;
+                }
+                None => {
+                    return (vec![Token::new(TokenKind::Comment, 0..line.len())], LexerState::InHtmlComment);
+                }
+            }
+        }
+
+        // Lex the remainder with the normal scanner. Its unclosed-block-comment
+        // diagnostic tells us whether the line ended mid-comment.
+        let rest = &line[offset..];
+        let mut log = Logger::new();
+        let mut rest_tokens = self.run(rest, &mut log);
+        for t in &mut rest_tokens {
+            t.span.start += offset;
+            t.span.end += offset;
+        }
+        tokens.extend(rest_tokens);
+
+        let exit = if log
+            .into_diagnostics()
+            .iter()
+            .any(|d| d.message == LexMessage::UnclosedBlockComment && d.span.end == rest.len())
+        {
+            LexerState::InHtmlComment
+        } else {
+            LexerState::Normal
+        };
+        (tokens, exit)
+    }
+}
+
+impl HtmlLexer {
+    /// Scan `text` into tokens, recording diagnostics for unterminated
+    /// comments, attribute strings, and tags into `log`.
+    fn run(&self, text: &[u8], log: &mut Logger) -> Vec<Token> {
         let mut tokens = Vec::with_capacity(text.len() / 8);
         let mut pos = 0;
 
@@ -29,13 +112,19 @@ impl Lexer for HtmlLexer {
                 // HTML Comment
                 b'<' if pos + 3 < text.len() && &text[pos..pos+4] == b"<!--" => {
                     pos += 4;
+                    let mut closed = false;
                     while pos + 2 < text.len() {
                         if &text[pos..pos+3] == b"-->" {
                             pos += 3;
+                            closed = true;
                             break;
                         }
                         pos += 1;
                     }
+                    if !closed {
+                        pos = text.len();
+                        log.report(LexMessage::UnclosedBlockComment, start..pos);
+                    }
                     tokens.push(Token::new(TokenKind::Comment, start..pos));
                 }
 
@@ -92,6 +181,7 @@ impl Lexer for HtmlLexer {
                     while pos < text.len() && !is_whitespace(text[pos]) && text[pos] != b'>' && text[pos] != b'/' {
                         pos += 1;
                     }
+                    let tag_name = text[tag_start..pos].to_ascii_lowercase();
                     if pos > tag_start {
                         tokens.push(Token::new(TokenKind::Keyword, tag_start..pos));
                     }
@@ -157,6 +247,8 @@ impl Lexer for HtmlLexer {
                                     }
                                     if pos < text.len() {
                                         pos += 1;
+                                    } else {
+                                        log.report(LexMessage::UnclosedStringLiteral, value_start..pos);
                                     }
                                     tokens.push(Token::new(TokenKind::String, value_start..pos));
                                 }
@@ -173,6 +265,17 @@ impl Lexer for HtmlLexer {
                     else if pos < text.len() && text[pos] == b'>' {
                         tokens.push(Token::new(TokenKind::Operator, pos..pos+1));
                         pos += 1;
+
+                        // Raw-text elements (`<script>`, `<style>`): their body
+                        // is not HTML, so scan to the matching close tag and,
+                        // if a sublexer is registered, delegate it.
+                        if is_raw_text(&tag_name) {
+                            pos = self.lex_raw_text(text, pos, &tag_name, &mut tokens);
+                        }
+                    }
+                    // Ran off the end of the buffer without closing the tag.
+                    else if pos >= text.len() {
+                        log.report(LexMessage::UnterminatedTag, start..pos);
                     }
                 }
 
@@ -188,4 +291,67 @@ impl Lexer for HtmlLexer {
 
         tokens
     }
+
+    /// Lex the body of a raw-text element starting at `pos`, stopping at the
+    /// matching case-insensitive `</tag>`. If a sublexer is registered for
+    /// `tag` the body is delegated to it (with its spans re-based into parent
+    /// coordinates); otherwise the body is emitted as a single text token.
+    /// Returns the position just after the body (at the `<` of the close tag,
+    /// or end-of-input).
+    fn lex_raw_text(&self, text: &[u8], pos: usize, tag: &[u8], tokens: &mut Vec<Token>) -> usize {
+        let body_start = pos;
+        let body_end = find_close_tag(text, pos, tag);
+
+        if body_end > body_start {
+            let body = &text[body_start..body_end];
+            match self.sublexers.get(&String::from_utf8_lossy(tag).into_owned()) {
+                Some(sub) => {
+                    for mut tok in sub.tokenize(body) {
+                        tok.span.start += body_start;
+                        tok.span.end += body_start;
+                        tokens.push(tok);
+                    }
+                }
+                None => tokens.push(Token::new(TokenKind::Identifier, body_start..body_end)),
+            }
+        }
+
+        body_end
+    }
+}
+
+/// Find the offset just past the next `-->` at or after `from`, or `None`.
+fn find_comment_end(text: &[u8], from: usize) -> Option<usize> {
+    let mut i = from;
+    while i + 3 <= text.len() {
+        if &text[i..i + 3] == b"-->" {
+            return Some(i + 3);
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Raw-text elements whose body is not parsed as HTML.
+fn is_raw_text(tag: &[u8]) -> bool {
+    matches!(tag, b"script" | b"style")
+}
+
+/// Scan from `pos` for the next `<` immediately followed by `/` and the tag
+/// name (case-insensitive), returning its `<` offset, or `text.len()` if none
+/// is found. The byte after the tag name is not checked, and a `</script`
+/// inside a string of the body ends the body too.
+fn find_close_tag(text: &[u8], mut pos: usize, tag: &[u8]) -> usize {
+    while pos < text.len() {
+        if text[pos] == b'<'
+            && pos + 1 < text.len()
+            && text[pos + 1] == b'/'
+            && text[pos + 2..].len() >= tag.len()
+            && text[pos + 2..pos + 2 + tag.len()].eq_ignore_ascii_case(tag)
+        {
+            return pos;
+        }
+        pos += 1;
+    }
+    text.len()
 }
@@ -0,0 +1,298 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Stateful scanning of interpolated string literals.
+//!
+//! A plain lexer treats a string as one opaque [`TokenKind::String`] span, but
+//! several languages here embed live expressions inside strings — shell
+//! `"$var ${x}"`, C# `$"{count} items"`. Those holes should be tokenized as
+//! code, not string text. [`tokenize_interpolated`] walks a string with a small
+//! control block ([`ControlBlock`]) tracking whether the cursor is in string
+//! text or inside a hole, and a nesting stack so braces and strings nested
+//! inside a hole balance correctly. Literal runs stay [`TokenKind::String`], the
+//! hole markers become [`TokenKind::StringInterpolationDelim`], and the embedded
+//! expression is re-lexed by the host [`Lexer`] so it nests up to
+//! [`MAX_INTERP_DEPTH`] levels, past which a hole is kept as flat text.
+
+use crate::syntax::lexer::{Lexer, is_ident_continue, is_ident_start};
+use crate::syntax::{Token, TokenKind};
+
+/// The deepest an interpolation hole may nest before
+/// [`tokenize_interpolated`] stops re-lexing and flattens the remainder of
+/// the hole to a single token instead. A legitimate file is very unlikely to
+/// nest this deep; an unbounded recursion here (one stack frame of
+/// [`Lexer::tokenize`]/[`Lexer::tokenize_capped`] per hole) would overflow the
+/// call stack on one that does.
+pub(crate) const MAX_INTERP_DEPTH: usize = 64;
+
+/// How a language spells its interpolated-string syntax.
+pub(crate) struct InterpConfig {
+    /// The quote byte that both opens and, unescaped, closes the string.
+    pub quote: u8,
+    /// Whether a backslash escapes the following byte in string text.
+    pub escape: bool,
+    /// Shell-style holes introduced by `$`/`${` (`true`) versus C#-style bare
+    /// `{ ... }` holes (`false`).
+    pub dollar: bool,
+    /// Whether a top-level `:` inside a braced hole begins a format/alignment
+    /// specifier (as in C# `{value:N2}`). The specifier and everything up to the
+    /// closing brace stay literal text rather than being re-lexed as code.
+    pub format_specifier: bool,
+}
+
+/// The mutable control block threaded through a single interpolated string: are
+/// we in string text right now, and how deeply are we nested in holes?
+struct ControlBlock {
+    /// `true` while scanning literal string text, `false` inside a hole.
+    is_within_text: bool,
+    /// One entry per open hole, always `0`: braces within a hole are balanced
+    /// by [`find_hole_end`]. Empty at the top level of the string.
+    stack: Vec<u32>,
+}
+
+/// Tokenize the interpolated string starting at `pos` (which must index the
+/// opening quote), pushing tokens into `out` and returning the index just past
+/// the closing quote (or `text.len()` if the string is unterminated).
+///
+/// The embedded expression inside each hole is handed back to `lexer` so nested
+/// strings — including nested interpolated ones — are lexed the same way.
+/// `depth` counts how many enclosing holes this call is already nested inside
+/// (`0` for a top-level string); once it reaches [`MAX_INTERP_DEPTH`] the
+/// interior of further holes is kept as a flat [`TokenKind::String`] span
+/// instead of being re-lexed, bounding the recursion through
+/// [`Lexer::tokenize_capped`].
+pub(crate) fn tokenize_interpolated(
+    lexer: &dyn Lexer,
+    text: &[u8],
+    pos: usize,
+    cfg: &InterpConfig,
+    out: &mut Vec<Token>,
+    depth: usize,
+) -> usize {
+    let mut cb = ControlBlock { is_within_text: true, stack: Vec::new() };
+    let mut pos = pos;
+    // Start of the current literal run, including the opening quote.
+    let mut lit_start = pos;
+    pos += 1;
+
+    while pos < text.len() {
+        let b = text[pos];
+
+        // Escapes (`\"`, `\\`, …) never open a hole or close the string.
+        if cfg.escape && b == b'\\' {
+            pos = (pos + 2).min(text.len());
+            continue;
+        }
+
+        // Closing quote: flush the trailing literal run and finish.
+        if b == cfg.quote {
+            pos += 1;
+            out.push(Token::new(TokenKind::String, lit_start..pos));
+            return pos;
+        }
+
+        // A hole opener, spelled per language.
+        if let Some(delim_end) = hole_opener(text, pos, cfg) {
+            // Flush the literal text accumulated before the hole.
+            if pos > lit_start {
+                out.push(Token::new(TokenKind::String, lit_start..pos));
+            }
+            cb.is_within_text = false;
+            cb.stack.push(0);
+
+            // Shell `$var` has no closing brace: emit the bare variable and
+            // resume string text immediately.
+            if cfg.dollar && text[pos + 1] != b'{' {
+                let var_end = scan_bare_variable(text, pos);
+                out.push(Token::new(TokenKind::VariableName, pos..var_end));
+                cb.stack.pop();
+                cb.is_within_text = true;
+                pos = var_end;
+                lit_start = pos;
+                continue;
+            }
+
+            // Braced hole: `${` (shell) or `{` (C#). Emit the opener marker,
+            // re-lex the balanced interior as code, then the closer marker.
+            out.push(Token::new(TokenKind::StringInterpolationDelim, pos..delim_end));
+            let expr_start = delim_end;
+            let expr_end = find_hole_end(text, expr_start);
+            // A `:` format specifier (`:N2`) is not code: re-lex only the
+            // expression before it and keep the specifier as text. An
+            // alignment (`,-10`) before the `:` is re-lexed with the expression.
+            let code_end = if cfg.format_specifier {
+                find_format_colon(&text[expr_start..expr_end]).map_or(expr_end, |c| expr_start + c)
+            } else {
+                expr_end
+            };
+            let mut inner = if depth < MAX_INTERP_DEPTH {
+                lexer.tokenize_capped(&text[expr_start..code_end], depth + 1)
+            } else {
+                vec![Token::new(TokenKind::String, 0..code_end - expr_start)]
+            };
+            for token in &mut inner {
+                token.span.start += expr_start;
+                token.span.end += expr_start;
+            }
+            out.append(&mut inner);
+            if code_end < expr_end {
+                out.push(Token::new(TokenKind::String, code_end..expr_end));
+            }
+            if expr_end < text.len() && text[expr_end] == b'}' {
+                out.push(Token::new(TokenKind::StringInterpolationDelim, expr_end..expr_end + 1));
+                pos = expr_end + 1;
+            } else {
+                pos = expr_end;
+            }
+            cb.stack.pop();
+            cb.is_within_text = true;
+            lit_start = pos;
+            continue;
+        }
+
+        pos += 1;
+    }
+
+    // Ran to end-of-input without a closing quote.
+    debug_assert!(cb.stack.is_empty() && cb.is_within_text);
+    out.push(Token::new(TokenKind::String, lit_start..text.len()));
+    text.len()
+}
+
+/// If a hole opens at `pos`, return the index just past its opener marker
+/// (`${`/`{`, or the `$` of a bare `$var`); otherwise `None`.
+fn hole_opener(text: &[u8], pos: usize, cfg: &InterpConfig) -> Option<usize> {
+    let b = text[pos];
+    if cfg.dollar {
+        if b != b'$' || pos + 1 >= text.len() {
+            return None;
+        }
+        match text[pos + 1] {
+            b'{' => Some(pos + 2),
+            c if is_ident_start(c) => Some(pos + 1),
+            _ => None,
+        }
+    } else {
+        if b != b'{' {
+            return None;
+        }
+        // `{{` is an escaped brace, not a hole.
+        if text.get(pos + 1) == Some(&b'{') {
+            return None;
+        }
+        Some(pos + 1)
+    }
+}
+
+/// Find the byte offset of the top-level `:` that begins a format specifier
+/// within a hole interior, or `None` if there is none. Colons nested inside
+/// parentheses or brackets (e.g. a parenthesized ternary `(c ? a : b)`) and the
+/// `::` namespace-alias operator are skipped so only the specifier colon counts.
+fn find_format_colon(expr: &[u8]) -> Option<usize> {
+    let mut depth = 0i32;
+    let mut i = 0;
+    while i < expr.len() {
+        match expr[i] {
+            b'(' | b'[' => depth += 1,
+            b')' | b']' => depth -= 1,
+            b':' if depth == 0 => {
+                if expr.get(i + 1) == Some(&b':') {
+                    i += 2; // `::` is not a specifier separator.
+                    continue;
+                }
+                return Some(i);
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Scan a bare shell variable (`$name`) starting at its `$`, returning the index
+/// just past the name.
+fn scan_bare_variable(text: &[u8], pos: usize) -> usize {
+    let mut end = pos + 1;
+    while end < text.len() && (is_ident_continue(text[end]) || text[end] == b'_') {
+        end += 1;
+    }
+    end
+}
+
+/// Given `text` positioned just inside an opening brace, return the index of the
+/// matching `}` (or `text.len()` if the hole is unterminated). Nested braces are
+/// balanced and string literals are skipped so a `}` inside a string does not
+/// close the hole.
+fn find_hole_end(text: &[u8], mut pos: usize) -> usize {
+    let mut depth = 1u32;
+    while pos < text.len() {
+        match text[pos] {
+            b'{' => depth += 1,
+            b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return pos;
+                }
+            }
+            q @ (b'"' | b'\'') => {
+                pos += 1;
+                while pos < text.len() && text[pos] != q {
+                    if text[pos] == b'\\' {
+                        pos += 1;
+                    }
+                    pos += 1;
+                }
+            }
+            _ => {}
+        }
+        pos += 1;
+    }
+    pos
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syntax::lexer::shell::ShellLexer;
+
+    #[test]
+    fn test_shell_braced_hole_is_code() {
+        let lexer = ShellLexer;
+        let cfg = InterpConfig { quote: b'"', escape: true, dollar: true, format_specifier: false };
+        let text = b"\"a ${x + 1} b\"";
+        let mut out = Vec::new();
+        let end = tokenize_interpolated(&lexer, text, 0, &cfg, &mut out, 0);
+
+        assert_eq!(end, text.len());
+        // The `${` and `}` markers are distinct delimiters, and the interior is
+        // re-lexed as code (so an operator token appears between them).
+        let delims = out.iter().filter(|t| t.kind == TokenKind::StringInterpolationDelim).count();
+        assert_eq!(delims, 2);
+        assert!(out.iter().any(|t| t.kind == TokenKind::Operator));
+    }
+
+    #[test]
+    fn test_shell_bare_variable_resumes_text() {
+        let lexer = ShellLexer;
+        let cfg = InterpConfig { quote: b'"', escape: true, dollar: true, format_specifier: false };
+        let text = b"\"hi $name!\"";
+        let mut out = Vec::new();
+        tokenize_interpolated(&lexer, text, 0, &cfg, &mut out, 0);
+
+        assert!(out.iter().any(|t| t.kind == TokenKind::VariableName));
+        // Text before and after the variable both stay string spans.
+        assert!(out.iter().filter(|t| t.kind == TokenKind::String).count() >= 2);
+    }
+
+    #[test]
+    fn test_unterminated_string_spans_to_eof() {
+        let lexer = ShellLexer;
+        let cfg = InterpConfig { quote: b'"', escape: true, dollar: true, format_specifier: false };
+        let text = b"\"no close";
+        let mut out = Vec::new();
+        let end = tokenize_interpolated(&lexer, text, 0, &cfg, &mut out, 0);
+
+        assert_eq!(end, text.len());
+        assert_eq!(out.last().unwrap().span.end, text.len());
+    }
+}
@@ -3,195 +3,324 @@
 
 //! JavaScript/TypeScript lexer with modern syntax support.
 
-use crate::syntax::lexer::{Lexer, is_ident_start, is_ident_continue, is_ascii_digit};
+use crate::syntax::lexer::dispatch::{DispatchTable, LexState};
+use crate::syntax::lexer::{Diagnostic, LexMessage, Logger};
+use crate::syntax::lexer::{Lexer, is_ident_continue, is_ascii_digit, c_line_comment_kind, c_block_comment_kind};
 use crate::syntax::{Token, TokenKind};
 
 pub struct JavaScriptLexer;
 
 impl Lexer for JavaScriptLexer {
     fn tokenize(&self, text: &[u8]) -> Vec<Token> {
-        let mut tokens = Vec::with_capacity(text.len() / 8);
-        let mut pos = 0;
-
-        while pos < text.len() {
-            let start = pos;
-            let b = text[pos];
-
-            match b {
-                // Whitespace
-                b' ' | b'\t' | b'\n' | b'\r' => {
-                    while pos < text.len() && matches!(text[pos], b' ' | b'\t' | b'\n' | b'\r') {
-                        pos += 1;
-                    }
-                    tokens.push(Token::new(TokenKind::Whitespace, start..pos));
-                }
+        run(text, &mut Logger::new())
+    }
 
-                // Line comment
-                b'/' if pos + 1 < text.len() && text[pos + 1] == b'/' => {
-                    pos += 2;
-                    while pos < text.len() && text[pos] != b'\n' {
-                        pos += 1;
-                    }
-                    tokens.push(Token::new(TokenKind::Comment, start..pos));
-                }
+    fn tokenize_with_diagnostics(&self, text: &[u8]) -> (Vec<Token>, Vec<Diagnostic>) {
+        let mut log = Logger::new();
+        let tokens = run(text, &mut log);
+        (tokens, log.into_diagnostics())
+    }
+}
 
-                // Block comment
-                b'/' if pos + 1 < text.len() && text[pos + 1] == b'*' => {
-                    pos += 2;
-                    while pos + 1 < text.len() {
-                        if text[pos] == b'*' && text[pos + 1] == b'/' {
-                            pos += 2;
-                            break;
-                        }
-                        pos += 1;
-                    }
-                    tokens.push(Token::new(TokenKind::Comment, start..pos));
-                }
+/// The byte-handler table, built at compile time. The first-byte
+/// decision for every token is a single load from this array plus an indirect
+/// call, rather than the cascading guards of a large `match`.
+static TABLE: DispatchTable = build_table();
 
-                // Template literals
-                b'`' => {
-                    pos += 1;
-                    while pos < text.len() {
-                        if text[pos] == b'\\' {
-                            pos += 2;
-                        } else if text[pos] == b'`' {
-                            pos += 1;
-                            break;
-                        } else {
-                            pos += 1;
-                        }
-                    }
-                    tokens.push(Token::new(TokenKind::String, start..pos));
-                }
+const fn build_table() -> DispatchTable {
+    DispatchTable::new(handle_unknown)
+        .set(b' ', handle_whitespace)
+        .set(b'\t', handle_whitespace)
+        .set(b'\n', handle_whitespace)
+        .set(b'\r', handle_whitespace)
+        .set(b'/', handle_slash)
+        .set(b'`', handle_template)
+        .set(b'"', handle_string)
+        .set(b'\'', handle_string)
+        .set_range(b'0', b'9', handle_number)
+        .set_range(b'a', b'z', handle_ident)
+        .set_range(b'A', b'Z', handle_ident)
+        .set(b'_', handle_ident)
+        .set(b'$', handle_ident)
+        .set(b'+', handle_operator)
+        .set(b'-', handle_operator)
+        .set(b'*', handle_operator)
+        .set(b'%', handle_operator)
+        .set(b'&', handle_operator)
+        .set(b'|', handle_operator)
+        .set(b'^', handle_operator)
+        .set(b'!', handle_operator)
+        .set(b'=', handle_operator)
+        .set(b'<', handle_operator)
+        .set(b'>', handle_operator)
+        .set(b'?', handle_operator)
+        .set(b':', handle_operator)
+        .set(b'~', handle_operator)
+        .set(b'{', handle_delimiter)
+        .set(b'}', handle_delimiter)
+        .set(b'[', handle_delimiter)
+        .set(b']', handle_delimiter)
+        .set(b'(', handle_delimiter)
+        .set(b')', handle_delimiter)
+        .set(b',', handle_punctuation)
+        .set(b';', handle_punctuation)
+        .set(b'.', handle_punctuation)
+}
 
-                // String literals
-                b'"' | b'\'' => {
-                    let quote = b;
-                    pos += 1;
-                    let mut escaped = false;
-                    while pos < text.len() {
-                        if escaped {
-                            escaped = false;
-                        } else if text[pos] == b'\\' {
-                            escaped = true;
-                        } else if text[pos] == quote {
-                            pos += 1;
-                            break;
-                        }
-                        pos += 1;
-                    }
-                    tokens.push(Token::new(TokenKind::String, start..pos));
-                }
+/// Drive [`TABLE`] over `text`, reporting unterminated strings, block comments,
+/// and template literals into `log`; the `tokenize` fast path passes a
+/// throwaway logger so nothing is collected unless a caller asks.
+fn run(text: &[u8], log: &mut Logger) -> Vec<Token> {
+    let mut st = LexState { text, pos: 0, tokens: Vec::with_capacity(text.len() / 8), log };
+    while st.pos < text.len() {
+        (TABLE.dispatch(text[st.pos]))(&mut st);
+    }
+    st.tokens
+}
 
-                // Numbers
-                b'0'..=b'9' => {
-                    pos += 1;
-                    
-                    // Hex
-                    if start + 1 < text.len() && text[start] == b'0' && matches!(text[start + 1], b'x' | b'X') {
-                        pos += 1;
-                        while pos < text.len() && (is_ascii_digit(text[pos]) || matches!(text[pos], b'a'..=b'f' | b'A'..=b'F')) {
-                            pos += 1;
-                        }
-                    }
-                    // Binary
-                    else if start + 1 < text.len() && text[start] == b'0' && matches!(text[start + 1], b'b' | b'B') {
-                        pos += 1;
-                        while pos < text.len() && matches!(text[pos], b'0' | b'1') {
-                            pos += 1;
-                        }
-                    }
-                    // Octal
-                    else if start + 1 < text.len() && text[start] == b'0' && matches!(text[start + 1], b'o' | b'O') {
-                        pos += 1;
-                        while pos < text.len() && matches!(text[pos], b'0'..=b'7') {
-                            pos += 1;
-                        }
-                    }
-                    // Decimal/Float
-                    else {
-                        while pos < text.len() && is_ascii_digit(text[pos]) {
-                            pos += 1;
-                        }
-                        
-                        // Float
-                        if pos < text.len() && text[pos] == b'.' && pos + 1 < text.len() && is_ascii_digit(text[pos + 1]) {
-                            pos += 1;
-                            while pos < text.len() && is_ascii_digit(text[pos]) {
-                                pos += 1;
-                            }
-                        }
-                        
-                        // Exponent
-                        if pos < text.len() && matches!(text[pos], b'e' | b'E') {
-                            pos += 1;
-                            if pos < text.len() && matches!(text[pos], b'+' | b'-') {
-                                pos += 1;
-                            }
-                            while pos < text.len() && is_ascii_digit(text[pos]) {
-                                pos += 1;
-                            }
-                        }
-                    }
-                    
-                    tokens.push(Token::new(TokenKind::Number, start..pos));
-                }
+fn handle_whitespace(st: &mut LexState<'_>) {
+    let start = st.pos;
+    while st.pos < st.text.len() && matches!(st.text[st.pos], b' ' | b'\t' | b'\n' | b'\r') {
+        st.pos += 1;
+    }
+    st.tokens.push(Token::new(TokenKind::Whitespace, start..st.pos));
+}
 
-                // Identifiers and keywords
-                _ if is_ident_start(b) || b == b'$' => {
-                    while pos < text.len() && (is_ident_continue(text[pos]) || text[pos] == b'$') {
-                        pos += 1;
-                    }
-                    
-                    let word = &text[start..pos];
-                    let kind = match word {
-                        b"in" | b"of" | b"instanceof" | b"typeof" | b"delete" | b"void" => TokenKind::KeywordOperator,
-                        b"if" | b"else" | b"switch" | b"case" | b"default" | b"for" | b"while" | b"do" | b"break" | b"continue" | b"return" | b"throw" | b"try" | b"catch" | b"finally" => TokenKind::KeywordControl,
-                        b"function" | b"async" | b"await" | b"yield" => TokenKind::KeywordFunction,
-                        b"import" | b"export" | b"from" | b"as" => TokenKind::KeywordImport,
-                        b"let" | b"const" | b"var" => TokenKind::KeywordStorage,
-                        b"class" | b"interface" | b"extends" | b"implements" | b"enum" | b"type" => TokenKind::KeywordType,
-                        b"new" | b"this" | b"super" | b"static" | b"public" | b"private" | b"protected" | b"readonly" => TokenKind::Keyword,
-                        b"true" | b"false" => TokenKind::Boolean,
-                        b"null" | b"undefined" => TokenKind::Null,
-                        _ => TokenKind::Identifier,
-                    };
-                    
-                    tokens.push(Token::new(kind, start..pos));
-                }
+/// `/` begins a line comment, a block comment, or an operator.
+fn handle_slash(st: &mut LexState<'_>) {
+    let (text, start) = (st.text, st.pos);
+    if start + 1 < text.len() && text[start + 1] == b'/' {
+        st.pos += 2;
+        while st.pos < text.len() && text[st.pos] != b'\n' {
+            st.pos += 1;
+        }
+        st.tokens.push(Token::new(c_line_comment_kind(&text[start..st.pos]), start..st.pos));
+    } else if start + 1 < text.len() && text[start + 1] == b'*' {
+        st.pos += 2;
+        let mut closed = false;
+        while st.pos + 1 < text.len() {
+            if text[st.pos] == b'*' && text[st.pos + 1] == b'/' {
+                st.pos += 2;
+                closed = true;
+                break;
+            }
+            st.pos += 1;
+        }
+        if !closed {
+            st.pos = text.len();
+            st.log.report(LexMessage::UnclosedBlockComment, start..st.pos);
+        }
+        st.tokens.push(Token::new(c_block_comment_kind(&text[start..st.pos]), start..st.pos));
+    } else {
+        handle_operator(st);
+    }
+}
 
-                // Operators
-                b'+' | b'-' | b'*' | b'/' | b'%' | b'&' | b'|' | b'^' | b'!' | b'=' | b'<' | b'>' | b'?' | b':' | b'~' => {
-                    pos += 1;
-                    // Handle multi-character operators (==, ===, <=, >=, etc.)
-                    while pos < text.len() && matches!(text[pos], b'=' | b'&' | b'|' | b'<' | b'>') {
-                        pos += 1;
-                    }
-                    tokens.push(Token::new(TokenKind::Operator, start..pos));
+fn handle_template(st: &mut LexState<'_>) {
+    st.pos = lex_template(st.text, st.pos, &mut st.tokens, st.log);
+}
+
+fn handle_string(st: &mut LexState<'_>) {
+    let (text, start) = (st.text, st.pos);
+    let quote = text[start];
+    st.pos += 1;
+    let mut escaped = false;
+    let mut closed = false;
+    while st.pos < text.len() {
+        if escaped {
+            escaped = false;
+        } else if text[st.pos] == b'\\' {
+            escaped = true;
+        } else if text[st.pos] == quote {
+            st.pos += 1;
+            closed = true;
+            break;
+        }
+        st.pos += 1;
+    }
+    if !closed {
+        st.log.report(LexMessage::UnclosedStringLiteral, start..st.pos);
+    }
+    st.tokens.push(Token::new(TokenKind::String, start..st.pos));
+}
+
+fn handle_number(st: &mut LexState<'_>) {
+    let (text, start) = (st.text, st.pos);
+    st.pos += 1;
+
+    // Hex
+    if start + 1 < text.len() && text[start] == b'0' && matches!(text[start + 1], b'x' | b'X') {
+        st.pos += 1;
+        while st.pos < text.len() && (is_ascii_digit(text[st.pos]) || matches!(text[st.pos], b'a'..=b'f' | b'A'..=b'F')) {
+            st.pos += 1;
+        }
+    }
+    // Binary
+    else if start + 1 < text.len() && text[start] == b'0' && matches!(text[start + 1], b'b' | b'B') {
+        st.pos += 1;
+        while st.pos < text.len() && matches!(text[st.pos], b'0' | b'1') {
+            st.pos += 1;
+        }
+    }
+    // Octal
+    else if start + 1 < text.len() && text[start] == b'0' && matches!(text[start + 1], b'o' | b'O') {
+        st.pos += 1;
+        while st.pos < text.len() && matches!(text[st.pos], b'0'..=b'7') {
+            st.pos += 1;
+        }
+    }
+    // Decimal/Float
+    else {
+        while st.pos < text.len() && is_ascii_digit(text[st.pos]) {
+            st.pos += 1;
+        }
+
+        // Float
+        if st.pos < text.len() && text[st.pos] == b'.' && st.pos + 1 < text.len() && is_ascii_digit(text[st.pos + 1]) {
+            st.pos += 1;
+            while st.pos < text.len() && is_ascii_digit(text[st.pos]) {
+                st.pos += 1;
+            }
+        }
+
+        // Exponent
+        if st.pos < text.len() && matches!(text[st.pos], b'e' | b'E') {
+            st.pos += 1;
+            if st.pos < text.len() && matches!(text[st.pos], b'+' | b'-') {
+                st.pos += 1;
+            }
+            while st.pos < text.len() && is_ascii_digit(text[st.pos]) {
+                st.pos += 1;
+            }
+        }
+    }
+
+    st.tokens.push(Token::new(TokenKind::Number, start..st.pos));
+}
+
+fn handle_ident(st: &mut LexState<'_>) {
+    let (text, start) = (st.text, st.pos);
+    while st.pos < text.len() && (is_ident_continue(text[st.pos]) || text[st.pos] == b'$') {
+        st.pos += 1;
+    }
+
+    let word = &text[start..st.pos];
+    let kind = match word {
+        b"in" | b"of" | b"instanceof" | b"typeof" | b"delete" | b"void" => TokenKind::KeywordOperator,
+        b"if" | b"else" | b"switch" | b"case" | b"default" | b"for" | b"while" | b"do" | b"break" | b"continue" | b"return" | b"throw" | b"try" | b"catch" | b"finally" => TokenKind::KeywordControl,
+        b"function" | b"async" | b"await" | b"yield" => TokenKind::KeywordFunction,
+        b"import" | b"export" | b"from" | b"as" => TokenKind::KeywordImport,
+        b"let" | b"const" | b"var" => TokenKind::KeywordStorage,
+        b"class" | b"interface" | b"extends" | b"implements" | b"enum" | b"type" => TokenKind::KeywordType,
+        b"new" | b"this" | b"super" | b"static" | b"public" | b"private" | b"protected" | b"readonly" => TokenKind::Keyword,
+        b"true" | b"false" => TokenKind::Boolean,
+        b"null" | b"undefined" => TokenKind::Null,
+        _ => TokenKind::Identifier,
+    };
+
+    st.tokens.push(Token::new(kind, start..st.pos));
+}
+
+fn handle_operator(st: &mut LexState<'_>) {
+    let (text, start) = (st.text, st.pos);
+    st.pos += 1;
+    // Handle multi-character operators (==, ===, <=, >=, etc.)
+    while st.pos < text.len() && matches!(text[st.pos], b'=' | b'&' | b'|' | b'<' | b'>') {
+        st.pos += 1;
+    }
+    st.tokens.push(Token::new(TokenKind::Operator, start..st.pos));
+}
+
+fn handle_delimiter(st: &mut LexState<'_>) {
+    let start = st.pos;
+    st.pos += 1;
+    st.tokens.push(Token::new(TokenKind::Delimiter, start..st.pos));
+}
+
+fn handle_punctuation(st: &mut LexState<'_>) {
+    let start = st.pos;
+    st.pos += 1;
+    st.tokens.push(Token::new(TokenKind::Punctuation, start..st.pos));
+}
+
+fn handle_unknown(st: &mut LexState<'_>) {
+    let start = st.pos;
+    st.pos += 1;
+    st.tokens.push(Token::new(TokenKind::Error, start..st.pos));
+}
+
+/// Tokenize a template literal that starts at `start` (the opening backtick),
+/// splitting it into `String` spans around each `${ ... }` interpolation and
+/// re-entering the normal token loop for the expression holes. Returns the
+/// position just past the closing backtick (or end of input if unterminated).
+fn lex_template(text: &[u8], start: usize, tokens: &mut Vec<Token>, log: &mut Logger) -> usize {
+    let mut pos = start + 1;
+    let mut seg_start = start;
+
+    while pos < text.len() {
+        match text[pos] {
+            b'\\' => {
+                pos += 2;
+            }
+            b'`' => {
+                pos += 1;
+                tokens.push(Token::new(TokenKind::String, seg_start..pos));
+                return pos;
+            }
+            b'$' if pos + 1 < text.len() && text[pos + 1] == b'{' => {
+                // Close the literal run that precedes the interpolation, then
+                // emit the `${` opener and re-lex the expression in between.
+                if pos > seg_start {
+                    tokens.push(Token::new(TokenKind::String, seg_start..pos));
                 }
+                tokens.push(Token::new(TokenKind::Operator, pos..pos + 2));
 
-                // Delimiters
-                b'{' | b'}' | b'[' | b']' | b'(' | b')' => {
-                    pos += 1;
-                    tokens.push(Token::new(TokenKind::Delimiter, start..pos));
+                let expr_start = pos + 2;
+                let mut i = expr_start;
+                let mut depth = 1usize;
+                while i < text.len() {
+                    match text[i] {
+                        b'{' => depth += 1,
+                        b'}' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        _ => {}
+                    }
+                    i += 1;
                 }
 
-                // Punctuation
-                b',' | b';' | b'.' => {
-                    pos += 1;
-                    tokens.push(Token::new(TokenKind::Punctuation, start..pos));
+                // Diagnostics from the hole would carry slice-relative spans,
+                // so collect the tokens only and discard the sub-logger.
+                for mut tok in run(&text[expr_start..i], &mut Logger::new()) {
+                    tok.span.start += expr_start;
+                    tok.span.end += expr_start;
+                    tokens.push(tok);
                 }
 
-                // Unknown
-                _ => {
-                    pos += 1;
-                    tokens.push(Token::new(TokenKind::Error, start..pos));
+                if i < text.len() {
+                    tokens.push(Token::new(TokenKind::Operator, i..i + 1));
+                    pos = i + 1;
+                } else {
+                    pos = i;
                 }
+                seg_start = pos;
+            }
+            _ => {
+                pos += 1;
             }
         }
+    }
 
-        tokens
+    // Unterminated template literal: color the trailing run as a string and
+    // flag the whole literal from its opening backtick.
+    if seg_start < text.len() {
+        tokens.push(Token::new(TokenKind::String, seg_start..text.len()));
     }
+    log.report(LexMessage::UnclosedStringLiteral, start..text.len());
+    text.len()
 }
 
 #[cfg(test)]
@@ -220,4 +349,52 @@ mod tests {
         let has_string = tokens.iter().any(|t| t.kind == TokenKind::String);
         assert!(has_string);
     }
+
+    #[test]
+    fn test_js_template_interpolation() {
+        let lexer = JavaScriptLexer;
+        let text = b"`a ${ obj[`x`] + 1 } b`";
+        let tokens = lexer.tokenize(text);
+
+        // The identifier and number inside `${ ... }` must surface as their own
+        // tokens rather than being swallowed into one string.
+        assert!(tokens.iter().any(|t| t.kind == TokenKind::Identifier));
+        assert!(tokens.iter().any(|t| t.kind == TokenKind::Number));
+        // The spans must tile the whole input without gaps or overlaps.
+        let mut expected = 0;
+        for t in &tokens {
+            assert_eq!(t.span.start, expected);
+            expected = t.span.end;
+        }
+        assert_eq!(expected, text.len());
+    }
+
+    #[test]
+    fn test_js_unterminated_diagnostics() {
+        let lexer = JavaScriptLexer;
+        let (_, diags) = lexer.tokenize_with_diagnostics(b"x = \"oops");
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].message, LexMessage::UnclosedStringLiteral);
+
+        let (_, diags) = lexer.tokenize_with_diagnostics(b"/* open");
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].message, LexMessage::UnclosedBlockComment);
+
+        let (_, diags) = lexer.tokenize_with_diagnostics(b"`no close ${a}");
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].message, LexMessage::UnclosedStringLiteral);
+    }
+
+    #[test]
+    fn test_js_dispatch_covers_all_classes() {
+        let lexer = JavaScriptLexer;
+        let text = b"x = 0xFF / 2; // done";
+        let tokens = lexer.tokenize(text);
+
+        assert!(tokens.iter().any(|t| t.kind == TokenKind::Identifier));
+        assert!(tokens.iter().any(|t| t.kind == TokenKind::Operator));
+        assert!(tokens.iter().any(|t| t.kind == TokenKind::Number));
+        assert!(tokens.iter().any(|t| t.kind == TokenKind::Punctuation));
+        assert!(tokens.iter().any(|t| t.kind == TokenKind::LineComment));
+    }
 }
@@ -3,11 +3,21 @@
 
 //! High-performance JSON lexer with JSONC (JSON with comments) support.
 
-use crate::syntax::lexer::{Lexer, is_ascii_digit};
+use crate::syntax::lexer::keyword::KeywordTable;
+use crate::syntax::lexer::{Diagnostic, LexMessage, Lexer, Logger, is_ascii_digit};
 use crate::syntax::{Token, TokenKind};
+use std::collections::HashSet;
 
 pub struct JsonLexer;
 
+/// JSON's three literal keywords, classified through the shared hashed
+/// [`KeywordTable`] rather than open-coded byte-slice comparisons.
+static KEYWORDS: KeywordTable = KeywordTable::new(&[
+    (b"true", TokenKind::Boolean),
+    (b"false", TokenKind::Boolean),
+    (b"null", TokenKind::Null),
+]);
+
 #[inline]
 fn is_whitespace(b: u8) -> bool {
     matches!(b, b' ' | b'\t' | b'\n' | b'\r')
@@ -46,7 +56,14 @@ impl Lexer for JsonLexer {
                         }
                         pos += 1;
                     }
-                    tokens.push(Token::new(TokenKind::String, start..pos));
+                    // Scopes let a theme target JSON strings specifically
+                    // (e.g. `string.quoted.source.json`) without this lexer
+                    // needing a dedicated `TokenKind` for them.
+                    tokens.push(Token::with_scopes(
+                        TokenKind::String,
+                        start..pos,
+                        &["string", "string.quoted", "source.json"],
+                    ));
                 }
 
                 // Numbers
@@ -94,19 +111,26 @@ impl Lexer for JsonLexer {
                             while pos < text.len() && text[pos] != b'\n' {
                                 pos += 1;
                             }
-                            tokens.push(Token::new(TokenKind::Comment, start..pos));
+                            tokens.push(Token::new(TokenKind::LineComment, start..pos));
                         }
                         // Block comment
                         b'*' => {
                             pos += 2;
+                            let mut closed = false;
                             while pos + 1 < text.len() {
                                 if text[pos] == b'*' && text[pos + 1] == b'/' {
                                     pos += 2;
+                                    closed = true;
                                     break;
                                 }
                                 pos += 1;
                             }
-                            tokens.push(Token::new(TokenKind::Comment, start..pos));
+                            // An unterminated block comment spans to end-of-input
+                            // rather than stopping one byte short of it.
+                            if !closed {
+                                pos = text.len();
+                            }
+                            tokens.push(Token::new(TokenKind::BlockComment, start..pos));
                         }
                         _ => {
                             // Not a comment, treat as error
@@ -116,18 +140,15 @@ impl Lexer for JsonLexer {
                     }
                 }
 
-                // Keywords: true, false, null
-                b't' if pos + 4 <= text.len() && &text[pos..pos + 4] == b"true" => {
-                    pos += 4;
-                    tokens.push(Token::new(TokenKind::Boolean, start..pos));
-                }
-                b'f' if pos + 5 <= text.len() && &text[pos..pos + 5] == b"false" => {
-                    pos += 5;
-                    tokens.push(Token::new(TokenKind::Boolean, start..pos));
-                }
-                b'n' if pos + 4 <= text.len() && &text[pos..pos + 4] == b"null" => {
-                    pos += 4;
-                    tokens.push(Token::new(TokenKind::Null, start..pos));
+                // Literal keywords (`true`/`false`/`null`). Scan the whole
+                // letter run and classify it through the shared keyword table;
+                // anything that is not a keyword is flagged as an error span.
+                b'a'..=b'z' | b'A'..=b'Z' => {
+                    while pos < text.len() && text[pos].is_ascii_alphabetic() {
+                        pos += 1;
+                    }
+                    let kind = KEYWORDS.lookup(&text[start..pos]).unwrap_or(TokenKind::Error);
+                    tokens.push(Token::new(kind, start..pos));
                 }
 
                 // Delimiters and operators
@@ -166,6 +187,92 @@ impl Lexer for JsonLexer {
 
         tokens
     }
+
+    fn diagnose(&self, text: &[u8]) -> Vec<Diagnostic> {
+        let tokens = self.tokenize(text);
+        let mut log = Logger::new();
+
+        // Lexical problems, token by token.
+        for token in &tokens {
+            let slice = &text[token.span.clone()];
+            match token.kind {
+                TokenKind::String if !string_is_terminated(slice) => {
+                    log.report(LexMessage::UnclosedStringLiteral, token.span.clone());
+                }
+                k if k.is_comment() => {
+                    if slice.starts_with(b"/*") && !slice.ends_with(b"*/") {
+                        log.report(LexMessage::UnclosedBlockComment, token.span.clone());
+                    }
+                    // Any comment is a JSONC extension, illegal in strict JSON.
+                    log.report(LexMessage::CommentInStrictJson, token.span.clone());
+                }
+                TokenKind::Error => {
+                    log.report(LexMessage::UnexpectedCharacter(slice[0]), token.span.clone());
+                }
+                _ => {}
+            }
+        }
+
+        // Structural lints over the significant tokens (whitespace/comments
+        // elided), tracking the object/array nesting so keys are scoped.
+        let sig: Vec<&Token> = tokens
+            .iter()
+            .filter(|t| !t.kind.is_trivia())
+            .collect();
+        // `Some(set)` is an object scope (tracking its keys); `None` an array.
+        let mut scopes: Vec<Option<HashSet<&[u8]>>> = Vec::new();
+        for (i, token) in sig.iter().enumerate() {
+            let first = text[token.span.start];
+            match token.kind {
+                TokenKind::JsonBrace if first == b'{' => scopes.push(Some(HashSet::new())),
+                TokenKind::JsonBracket if first == b'[' => scopes.push(None),
+                TokenKind::JsonBrace | TokenKind::JsonBracket => {
+                    scopes.pop();
+                }
+                TokenKind::JsonComma => {
+                    if let Some(next) = sig.get(i + 1) {
+                        let nf = text[next.span.start];
+                        let closes = (next.kind == TokenKind::JsonBrace && nf == b'}')
+                            || (next.kind == TokenKind::JsonBracket && nf == b']');
+                        if closes {
+                            log.report(LexMessage::TrailingComma, token.span.clone());
+                        }
+                    }
+                }
+                TokenKind::String => {
+                    let is_key = matches!(scopes.last(), Some(Some(_)))
+                        && sig.get(i + 1).map(|t| t.kind) == Some(TokenKind::JsonColon);
+                    if is_key {
+                        let key = &text[token.span.clone()];
+                        if let Some(Some(set)) = scopes.last_mut() {
+                            if !set.insert(key) {
+                                log.report(LexMessage::DuplicateKey, token.span.clone());
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        log.into_diagnostics()
+    }
+}
+
+/// Whether a `String` token slice closed with an unescaped `"` rather than
+/// running to end-of-input.
+fn string_is_terminated(s: &[u8]) -> bool {
+    if s.len() < 2 || s[0] != b'"' || s[s.len() - 1] != b'"' {
+        return false;
+    }
+    // An odd number of backslashes before the final quote escapes it.
+    let mut backslashes = 0;
+    let mut i = s.len() - 1;
+    while i > 1 && s[i - 1] == b'\\' {
+        backslashes += 1;
+        i -= 1;
+    }
+    backslashes % 2 == 0
 }
 
 #[cfg(test)]
@@ -183,6 +290,30 @@ mod tests {
         assert_eq!(tokens[2].kind, TokenKind::JsonColon); // :
     }
 
+    #[test]
+    fn test_json_keyword_table() {
+        let lexer = JsonLexer;
+        let tokens = lexer.tokenize(b"[true, nul]");
+        // `true` classifies as a boolean; the misspelled `nul` is an error span.
+        assert!(tokens.iter().any(|t| t.kind == TokenKind::Boolean));
+        assert!(tokens.iter().any(|t| t.kind == TokenKind::Error));
+    }
+
+    #[test]
+    fn test_json_diagnostics() {
+        let lexer = JsonLexer;
+        let diags = lexer.diagnose(br#"{"a": 1, "a": 2,}"#);
+        let kinds: Vec<_> = diags.iter().map(|d| d.message).collect();
+        assert!(kinds.contains(&LexMessage::DuplicateKey));
+        assert!(kinds.contains(&LexMessage::TrailingComma));
+
+        let diags = lexer.diagnose(b"{\"x\": \"unterminated");
+        assert!(diags.iter().any(|d| d.message == LexMessage::UnclosedStringLiteral));
+
+        let diags = lexer.diagnose(b"// hi\n{}");
+        assert!(diags.iter().any(|d| d.message == LexMessage::CommentInStrictJson));
+    }
+
     #[test]
     fn test_json_numbers() {
         let lexer = JsonLexer;
@@ -216,9 +347,22 @@ mod tests {
         let tokens = lexer.tokenize(text);
         
         let comments: Vec<_> = tokens.iter()
-            .filter(|t| t.kind == TokenKind::Comment)
+            .filter(|t| t.kind.is_comment())
             .collect();
-        
+
         assert_eq!(comments.len(), 2);
+        // The two flavors are distinguished: one line, one block.
+        assert_eq!(comments[0].kind, TokenKind::LineComment);
+        assert_eq!(comments[1].kind, TokenKind::BlockComment);
+    }
+
+    #[test]
+    fn test_jsonc_unterminated_block_comment_spans_to_eof() {
+        let lexer = JsonLexer;
+        let text = b"{} /* never closed";
+        let tokens = lexer.tokenize(text);
+
+        let comment = tokens.iter().find(|t| t.kind == TokenKind::BlockComment).unwrap();
+        assert_eq!(comment.span.end, text.len());
     }
 }
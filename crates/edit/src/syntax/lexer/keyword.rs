@@ -0,0 +1,303 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Shared keyword classification through a small open-addressing hash table
+//! (not a perfect hash).
+//!
+//! Each language lexer used to classify an already-scanned identifier with a
+//! long `match word { ... }` over byte-string literals. That compiles to a
+//! cascade of length/`memcmp` comparisons run once per identifier. Instead we
+//! keep the keyword set as a flat data table and resolve it through a tiny
+//! hash so recognition is O(word length) regardless of how large the set
+//! grows — adding a keyword is a one-line table edit, not new control flow.
+//!
+//! The table is laid out lazily the first time a lexer looks a word up and
+//! cached for the lifetime of the process via [`OnceLock`]. Slots use open
+//! addressing, so correctness never depends on the hash being collision-free;
+//! the hash only has to spread the keywords well enough to keep probe chains
+//! short.
+
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::sync::OnceLock;
+
+use crate::syntax::TokenKind;
+
+/// A keyword lookup table built once per language from a static entry list.
+pub(crate) struct KeywordTable {
+    /// The canonical `(keyword, kind)` pairs. Editing this is how a language
+    /// gains or loses a keyword. For a case-insensitive table (see
+    /// [`new_ignore_ascii_case`]) the keywords must be stored uppercased.
+    ///
+    /// [`new_ignore_ascii_case`]: KeywordTable::new_ignore_ascii_case
+    entries: &'static [(&'static [u8], TokenKind)],
+    /// Whether lookups fold ASCII case. SQL and similar case-insensitive
+    /// languages set this so a single uppercased table serves `select` and
+    /// `SELECT` alike without allocating an uppercase copy of each word.
+    ignore_case: bool,
+    /// Lazily-filled open-addressing slots holding indices into `entries`,
+    /// with `u16::MAX` marking an empty slot. Sized to the next power of two
+    /// at least twice as large as `entries` to keep the load factor low.
+    slots: OnceLock<Vec<u16>>,
+}
+
+impl KeywordTable {
+    /// Create a case-sensitive table over a static list of `(keyword, kind)`
+    /// pairs.
+    pub(crate) const fn new(entries: &'static [(&'static [u8], TokenKind)]) -> Self {
+        Self { entries, ignore_case: false, slots: OnceLock::new() }
+    }
+
+    /// Create a case-insensitive table. The stored keywords must already be
+    /// uppercased; lookups compare against `word` with `eq_ignore_ascii_case`.
+    pub(crate) const fn new_ignore_ascii_case(entries: &'static [(&'static [u8], TokenKind)]) -> Self {
+        Self { entries, ignore_case: true, slots: OnceLock::new() }
+    }
+
+    /// Classify `word`, returning its [`TokenKind`] if it is a keyword.
+    pub(crate) fn lookup(&self, word: &[u8]) -> Option<TokenKind> {
+        if word.is_empty() {
+            return None;
+        }
+        let slots = self.slots.get_or_init(|| self.build());
+        let mask = slots.len() - 1;
+        let mut idx = self.hash(word) & mask;
+        loop {
+            let slot = slots[idx];
+            if slot == u16::MAX {
+                return None;
+            }
+            let (kw, kind) = self.entries[slot as usize];
+            // Cheap length/first-byte guard before the full comparison so a
+            // probe that lands on the wrong keyword bails without a `memcmp`.
+            if kw.len() == word.len() && self.byte_eq(kw[0], word[0]) && self.word_eq(kw, word) {
+                return Some(kind);
+            }
+            idx = (idx + 1) & mask;
+        }
+    }
+
+    /// Populate the open-addressing slot array from `entries`.
+    fn build(&self) -> Vec<u16> {
+        let cap = (self.entries.len() * 2).next_power_of_two().max(2);
+        let mut slots = vec![u16::MAX; cap];
+        let mask = cap - 1;
+        for (i, (kw, _)) in self.entries.iter().enumerate() {
+            let mut idx = self.hash(kw) & mask;
+            while slots[idx] != u16::MAX {
+                idx = (idx + 1) & mask;
+            }
+            slots[idx] = i as u16;
+        }
+        slots
+    }
+
+    /// FNV-1a-style byte hash seeded with the word length. Case-insensitive
+    /// tables fold each byte to uppercase so `hash("select") == hash("SELECT")`.
+    #[inline]
+    fn hash(&self, word: &[u8]) -> usize {
+        let mut h = word.len().wrapping_mul(0x0100_0193);
+        for &b in word {
+            let b = if self.ignore_case { b.to_ascii_uppercase() } else { b };
+            h = (h ^ b as usize).wrapping_mul(0x0100_0193);
+        }
+        h
+    }
+
+    #[inline]
+    fn byte_eq(&self, stored: u8, probe: u8) -> bool {
+        if self.ignore_case { stored == probe.to_ascii_uppercase() } else { stored == probe }
+    }
+
+    #[inline]
+    fn word_eq(&self, stored: &[u8], probe: &[u8]) -> bool {
+        if self.ignore_case { stored.eq_ignore_ascii_case(probe) } else { stored == probe }
+    }
+}
+
+/// A node in the keyword [`KeywordAutomaton`]'s trie.
+struct AcNode {
+    /// Child transitions keyed by byte (the `goto` function); `u32::MAX` means
+    /// no edge.
+    goto: [u32; 256],
+    /// Failure link: the deepest proper-suffix node reachable from the root.
+    fail: u32,
+    /// The classification and keyword length if this node terminates a keyword,
+    /// else `None`. The length lets a buffer scan recover the match's start.
+    output: Option<(TokenKind, u32)>,
+}
+
+impl AcNode {
+    fn new() -> Self {
+        Self { goto: [u32::MAX; 256], fail: 0, output: None }
+    }
+}
+
+/// An Aho-Corasick automaton over a language's keyword set.
+///
+/// Where [`KeywordTable`] answers "is this whole identifier a keyword" with a
+/// hash lookup, the automaton answers the same question by walking a trie —
+/// but because it also carries failure links it can later scan a whole buffer
+/// for every keyword occurrence in a single pass (see [`find_all`]), which a
+/// hash cannot. Lexers use [`classify`] on an already-scanned identifier; the
+/// buffer scan backs "highlight all occurrences" of a keyword.
+///
+/// [`find_all`]: KeywordAutomaton::find_all
+/// [`classify`]: KeywordAutomaton::classify
+pub(crate) struct KeywordAutomaton {
+    /// The `(keyword, kind)` pairs, editable as a flat table like
+    /// [`KeywordTable::entries`].
+    entries: &'static [(&'static [u8], TokenKind)],
+    /// The trie, built lazily on first use and cached for the process lifetime.
+    nodes: OnceLock<Vec<AcNode>>,
+}
+
+impl KeywordAutomaton {
+    /// Create an automaton over a static list of `(keyword, kind)` pairs.
+    pub(crate) const fn new(entries: &'static [(&'static [u8], TokenKind)]) -> Self {
+        Self { entries, nodes: OnceLock::new() }
+    }
+
+    /// Classify a whole identifier slice, returning [`TokenKind::Identifier`]
+    /// when it is not a keyword.
+    ///
+    /// This is an exact-match query: follow `goto` edges for each byte (never
+    /// the failure links, which only matter mid-buffer) and require that the
+    /// walk both survives every byte and lands on a terminal node.
+    pub(crate) fn classify(&self, word: &[u8]) -> TokenKind {
+        let nodes = self.nodes.get_or_init(|| self.build());
+        let mut node = 0u32;
+        for &b in word {
+            let next = nodes[node as usize].goto[b as usize];
+            if next == u32::MAX {
+                return TokenKind::Identifier;
+            }
+            node = next;
+        }
+        // An output inherited along a failure link belongs to a shorter suffix
+        // keyword, so only an output as long as `word` is an exact match.
+        nodes[node as usize]
+            .output
+            .filter(|&(_, len)| len as usize == word.len())
+            .map_or(TokenKind::Identifier, |(kind, _)| kind)
+    }
+
+    /// Scan a buffer and report every keyword occurrence as a byte range and
+    /// its kind. Reserved for "highlight all occurrences"; kept here so the one
+    /// automaton serves both the per-identifier and whole-buffer cases.
+    #[allow(dead_code)]
+    pub(crate) fn find_all(&self, text: &[u8]) -> Vec<(Range<usize>, TokenKind)> {
+        let nodes = self.nodes.get_or_init(|| self.build());
+        let mut matches = Vec::new();
+        let mut node = 0u32;
+        for (i, &b) in text.iter().enumerate() {
+            // Follow failure links until a `goto` edge exists or we reach root.
+            while node != 0 && nodes[node as usize].goto[b as usize] == u32::MAX {
+                node = nodes[node as usize].fail;
+            }
+            let next = nodes[node as usize].goto[b as usize];
+            node = if next == u32::MAX { 0 } else { next };
+            // Walk the failure chain so a keyword that is a suffix of a longer
+            // one ending here is reported too. An inherited output repeats the
+            // next node's, so equal lengths are skipped.
+            let mut n = node;
+            let mut last_len = 0;
+            while n != 0 {
+                if let Some((kind, len)) = nodes[n as usize].output {
+                    if len != last_len {
+                        let end = i + 1;
+                        matches.push((end - len as usize..end, kind));
+                        last_len = len;
+                    }
+                }
+                n = nodes[n as usize].fail;
+            }
+        }
+        matches
+    }
+
+    /// Build the trie and its failure links. Insert every keyword byte-by-byte,
+    /// then BFS from the root so each node's failure pointer is the deepest
+    /// proper-suffix node, found by walking its parent's failure chain.
+    fn build(&self) -> Vec<AcNode> {
+        let mut nodes = vec![AcNode::new()];
+        for (kw, kind) in self.entries {
+            let mut node = 0usize;
+            for &b in *kw {
+                let next = nodes[node].goto[b as usize];
+                node = if next == u32::MAX {
+                    nodes.push(AcNode::new());
+                    let idx = (nodes.len() - 1) as u32;
+                    nodes[node].goto[b as usize] = idx;
+                    idx as usize
+                } else {
+                    next as usize
+                };
+            }
+            nodes[node].output = Some((*kind, kw.len() as u32));
+        }
+
+        // BFS to compute failure links. Depth-1 nodes fail to the root.
+        let mut queue = VecDeque::new();
+        for b in 0..256 {
+            let child = nodes[0].goto[b];
+            if child != u32::MAX {
+                nodes[child as usize].fail = 0;
+                queue.push_back(child);
+            }
+        }
+        while let Some(node) = queue.pop_front() {
+            for b in 0..256 {
+                let child = nodes[node as usize].goto[b];
+                if child == u32::MAX {
+                    continue;
+                }
+                // Walk the parent's failure chain to find the next node with a
+                // `b` edge; that is this child's failure target.
+                let mut f = nodes[node as usize].fail;
+                while f != 0 && nodes[f as usize].goto[b] == u32::MAX {
+                    f = nodes[f as usize].fail;
+                }
+                let target = nodes[f as usize].goto[b];
+                let fail = if target != u32::MAX && target != child { target } else { 0 };
+                nodes[child as usize].fail = fail;
+                // Inherit output along the failure link so suffix keywords still
+                // report when a longer word subsumes them.
+                if nodes[child as usize].output.is_none() {
+                    nodes[child as usize].output = nodes[fail as usize].output;
+                }
+                queue.push_back(child);
+            }
+        }
+
+        nodes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static KW: KeywordAutomaton = KeywordAutomaton::new(&[
+        (b"if", TokenKind::Keyword),
+        (b"int", TokenKind::TypeName),
+        (b"true", TokenKind::Boolean),
+    ]);
+
+    #[test]
+    fn test_automaton_classify() {
+        assert_eq!(KW.classify(b"if"), TokenKind::Keyword);
+        assert_eq!(KW.classify(b"int"), TokenKind::TypeName);
+        assert_eq!(KW.classify(b"true"), TokenKind::Boolean);
+        // Prefixes and unknown words are plain identifiers.
+        assert_eq!(KW.classify(b"in"), TokenKind::Identifier);
+        assert_eq!(KW.classify(b"integer"), TokenKind::Identifier);
+        assert_eq!(KW.classify(b"foo"), TokenKind::Identifier);
+    }
+
+    #[test]
+    fn test_automaton_find_all() {
+        let found = KW.find_all(b"if x = int;");
+        assert_eq!(found, vec![(0..2, TokenKind::Keyword), (6..9, TokenKind::TypeName)]);
+    }
+}
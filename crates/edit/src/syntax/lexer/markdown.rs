@@ -3,13 +3,39 @@
 
 //! Markdown lexer with support for common formatting.
 
+use crate::syntax::lexer::{Diagnostic, Injection, LexMessage, Logger};
 use crate::syntax::lexer::Lexer;
-use crate::syntax::{Token, TokenKind};
+use crate::syntax::{Language, Token, TokenKind};
 
 pub struct MarkdownLexer;
 
 impl Lexer for MarkdownLexer {
     fn tokenize(&self, text: &[u8]) -> Vec<Token> {
+        self.run(text, &mut Logger::new(), &mut Vec::new())
+    }
+
+    fn tokenize_with_diagnostics(&self, text: &[u8]) -> (Vec<Token>, Vec<Diagnostic>) {
+        let mut log = Logger::new();
+        let tokens = self.run(text, &mut log, &mut Vec::new());
+        (tokens, log.into_diagnostics())
+    }
+
+    fn injections(&self, text: &[u8]) -> Vec<Injection> {
+        let mut injections = Vec::new();
+        self.run(text, &mut Logger::new(), &mut injections);
+        injections
+    }
+}
+
+impl MarkdownLexer {
+    /// Scan `text`, reporting fenced and inline code spans that run to
+    /// end-of-input without a closing fence into `log`. Fenced code blocks
+    /// whose info string (the word right after the opening ` ``` `) names a
+    /// recognized language push an [`Injection`] for their body onto
+    /// `injections`, so [`SyntaxHighlighter::update`](crate::syntax::SyntaxHighlighter::update)
+    /// can delegate it to that language's own lexer. The `tokenize` fast path
+    /// passes throwaway sinks for both, so nothing is collected otherwise.
+    fn run(&self, text: &[u8], log: &mut Logger, injections: &mut Vec<Injection>) -> Vec<Token> {
         let mut tokens = Vec::with_capacity(text.len() / 16);
         let mut pos = 0;
 
@@ -21,31 +47,128 @@ impl Lexer for MarkdownLexer {
             let at_line_start = pos == 0 || (pos > 0 && text[pos - 1] == b'\n');
 
             match b {
-                // Headings (must be at line start)
+                // ATX headings (must be at line start; the `#` run must be
+                // followed by a space or the end of the line).
                 b'#' if at_line_start => {
                     let mut level = 0;
                     while pos < text.len() && text[pos] == b'#' && level < 6 {
                         pos += 1;
                         level += 1;
                     }
-                    // Consume the rest of the line
+                    if pos >= text.len() || matches!(text[pos], b' ' | b'\n') {
+                        while pos < text.len() && text[pos] != b'\n' {
+                            pos += 1;
+                        }
+                        tokens.push(Token::new(TokenKind::markdown_heading(level), start..pos));
+                    } else {
+                        // Not a heading after all — treat the line as text.
+                        while pos < text.len() && !is_inline_special(text[pos]) {
+                            pos += 1;
+                        }
+                        tokens.push(Token::new(TokenKind::Identifier, start..pos));
+                    }
+                }
+
+                // Blockquote prefix, possibly nested (`>`, `> >`).
+                b'>' if at_line_start => {
+                    while pos < text.len() && matches!(text[pos], b'>' | b' ') {
+                        pos += 1;
+                    }
+                    tokens.push(Token::new(TokenKind::MarkdownBlockQuote, start..pos));
+                }
+
+                // Setext underline / thematic break: a line of only `=` or `-`.
+                b'=' | b'-' if at_line_start && is_setext_underline(text, pos) => {
+                    let level = if b == b'=' { 1 } else { 2 };
                     while pos < text.len() && text[pos] != b'\n' {
                         pos += 1;
                     }
-                    tokens.push(Token::new(TokenKind::MarkdownHeading, start..pos));
+                    tokens.push(Token::new(TokenKind::markdown_heading(level), start..pos));
+                }
+
+                // Unordered list marker (`-`, `*`, `+` then a space), optionally
+                // followed by a task-list checkbox.
+                b'-' | b'*' | b'+'
+                    if at_line_start && pos + 1 < text.len() && text[pos + 1] == b' ' =>
+                {
+                    pos += 1;
+                    while pos < text.len() && text[pos] == b' ' {
+                        pos += 1;
+                    }
+                    tokens.push(Token::new(TokenKind::MarkdownListMarker, start..pos));
+
+                    // Task box: `[ ]`, `[x]`, or `[X]`.
+                    if pos + 2 < text.len()
+                        && text[pos] == b'['
+                        && matches!(text[pos + 1], b' ' | b'x' | b'X')
+                        && text[pos + 2] == b']'
+                    {
+                        let box_start = pos;
+                        pos += 3;
+                        tokens.push(Token::new(TokenKind::MarkdownTaskBox, box_start..pos));
+                    }
+                }
+
+                // Ordered list marker (`1.`, `2)` then a space).
+                b'0'..=b'9' if at_line_start && is_ordered_marker(text, pos) => {
+                    while pos < text.len() && text[pos].is_ascii_digit() {
+                        pos += 1;
+                    }
+                    pos += 1; // the `.` or `)`
+                    while pos < text.len() && text[pos] == b' ' {
+                        pos += 1;
+                    }
+                    tokens.push(Token::new(TokenKind::MarkdownListMarker, start..pos));
+                }
+
+                // Table alignment row (`|:---|:--:|---:|`) is highlighted whole;
+                // any other pipe is a cell separator.
+                b'|' if at_line_start && is_alignment_row(text, pos) => {
+                    while pos < text.len() && text[pos] != b'\n' {
+                        pos += 1;
+                    }
+                    tokens.push(Token::new(TokenKind::MarkdownTableDelimiter, start..pos));
+                }
+                b'|' => {
+                    pos += 1;
+                    tokens.push(Token::new(TokenKind::MarkdownTableDelimiter, start..pos));
                 }
 
                 // Code blocks with backticks
                 b'`' if pos + 2 < text.len() && text[pos + 1] == b'`' && text[pos + 2] == b'`' => {
                     pos += 3;
+                    // The info string names the embedded language, e.g. ```rust.
+                    let info_start = pos;
+                    while pos < text.len() && text[pos] != b'\n' {
+                        pos += 1;
+                    }
+                    let info = &text[info_start..pos];
+                    let body_start = if pos < text.len() { pos + 1 } else { pos };
+
                     // Find the closing ```
+                    let mut closed = false;
+                    let mut body_end = body_start;
+                    pos = body_start;
                     while pos + 2 < text.len() {
                         if text[pos] == b'`' && text[pos + 1] == b'`' && text[pos + 2] == b'`' {
+                            body_end = pos;
                             pos += 3;
+                            closed = true;
                             break;
                         }
                         pos += 1;
                     }
+                    if !closed {
+                        pos = text.len();
+                        log.report(LexMessage::UnclosedStringLiteral, start..pos);
+                    } else if let Some(language) = std::str::from_utf8(info)
+                        .ok()
+                        .map(str::trim)
+                        .filter(|tag| !tag.is_empty())
+                        .and_then(Language::from_tag)
+                    {
+                        injections.push(Injection { range: body_start..body_end, language });
+                    }
                     tokens.push(Token::new(TokenKind::MarkdownCode, start..pos));
                 }
 
@@ -57,6 +180,8 @@ impl Lexer for MarkdownLexer {
                     }
                     if pos < text.len() {
                         pos += 1;
+                    } else {
+                        log.report(LexMessage::UnclosedStringLiteral, start..pos);
                     }
                     tokens.push(Token::new(TokenKind::MarkdownCode, start..pos));
                 }
@@ -135,18 +260,18 @@ impl Lexer for MarkdownLexer {
 
                 // Regular text - consume until next special character
                 _ => {
-                    while pos < text.len() {
-                        let ch = text[pos];
-                        if matches!(ch, b'#' | b'`' | b'*' | b'_' | b'[' | b'\n') {
-                            break;
-                        }
+                    while pos < text.len() && !is_inline_special(text[pos]) {
                         pos += 1;
                     }
-                    // Don't create empty tokens
+                    // Don't create empty tokens. If the very first byte is
+                    // itself inline-special (e.g. a bare newline not caught
+                    // by an earlier arm), consume just it as text rather
+                    // than dropping it with no token at all.
                     if pos > start {
                         tokens.push(Token::new(TokenKind::Identifier, start..pos));
                     } else {
                         pos += 1;
+                        tokens.push(Token::new(TokenKind::Identifier, start..pos));
                     }
                 }
             }
@@ -156,6 +281,57 @@ impl Lexer for MarkdownLexer {
     }
 }
 
+/// Bytes that end a run of plain text because they can begin inline markup.
+fn is_inline_special(b: u8) -> bool {
+    matches!(b, b'#' | b'`' | b'*' | b'_' | b'[' | b'|' | b'\n')
+}
+
+/// Whether the line at `pos` consists solely of `=`/`-` (and spaces), i.e. a
+/// setext heading underline or a thematic break.
+fn is_setext_underline(text: &[u8], pos: usize) -> bool {
+    let underline = text[pos];
+    let mut p = pos;
+    let mut count = 0;
+    while p < text.len() && text[p] != b'\n' {
+        match text[p] {
+            b' ' => {}
+            c if c == underline => count += 1,
+            _ => return false,
+        }
+        p += 1;
+    }
+    count > 0
+}
+
+/// Whether `pos` begins an ordered-list marker: one or more digits followed by
+/// `.` or `)` and then a space.
+fn is_ordered_marker(text: &[u8], pos: usize) -> bool {
+    let mut p = pos;
+    while p < text.len() && text[p].is_ascii_digit() {
+        p += 1;
+    }
+    p < text.len()
+        && matches!(text[p], b'.' | b')')
+        && p + 1 < text.len()
+        && text[p + 1] == b' '
+}
+
+/// Whether the line at `pos` is a table alignment row — only `|`, `:`, `-`, and
+/// spaces, with at least one `-`.
+fn is_alignment_row(text: &[u8], pos: usize) -> bool {
+    let mut p = pos;
+    let mut dashes = 0;
+    while p < text.len() && text[p] != b'\n' {
+        match text[p] {
+            b'|' | b':' | b' ' => {}
+            b'-' => dashes += 1,
+            _ => return false,
+        }
+        p += 1;
+    }
+    dashes > 0
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -165,12 +341,31 @@ mod tests {
         let lexer = MarkdownLexer;
         let text = b"# Heading 1\n## Heading 2";
         let tokens = lexer.tokenize(text);
-        
-        let headings: Vec<_> = tokens.iter()
-            .filter(|t| t.kind == TokenKind::MarkdownHeading)
-            .collect();
-        
-        assert_eq!(headings.len(), 2);
+
+        // Each heading carries its level as a distinct token kind.
+        assert!(tokens.iter().any(|t| t.kind == TokenKind::MarkdownHeading1));
+        assert!(tokens.iter().any(|t| t.kind == TokenKind::MarkdownHeading2));
+    }
+
+    #[test]
+    fn test_markdown_lists_and_tasks() {
+        let lexer = MarkdownLexer;
+        let text = b"- [x] done\n- todo\n1. first";
+        let tokens = lexer.tokenize(text);
+
+        let markers = tokens.iter().filter(|t| t.kind == TokenKind::MarkdownListMarker).count();
+        assert_eq!(markers, 3);
+        assert!(tokens.iter().any(|t| t.kind == TokenKind::MarkdownTaskBox));
+    }
+
+    #[test]
+    fn test_markdown_table_and_blockquote() {
+        let lexer = MarkdownLexer;
+        let text = b"> quote\n| a | b |\n|:---|---:|";
+        let tokens = lexer.tokenize(text);
+
+        assert!(tokens.iter().any(|t| t.kind == TokenKind::MarkdownBlockQuote));
+        assert!(tokens.iter().any(|t| t.kind == TokenKind::MarkdownTableDelimiter));
     }
 
     #[test]
@@ -186,6 +381,26 @@ mod tests {
         assert_eq!(code.len(), 2);
     }
 
+    #[test]
+    fn test_markdown_fence_injection() {
+        let lexer = MarkdownLexer;
+        let text = b"before\n```rust\nfn main() {}\n```\nafter";
+        let injections = lexer.injections(text);
+
+        assert_eq!(injections.len(), 1);
+        assert_eq!(injections[0].language, Language::Rust);
+        let body_start = text.windows(4).position(|w| w == b"rust").unwrap() + 5;
+        assert_eq!(&text[injections[0].range.clone()], b"fn main() {}\n");
+        assert_eq!(injections[0].range.start, body_start);
+    }
+
+    #[test]
+    fn test_markdown_fence_unknown_tag_has_no_injection() {
+        let lexer = MarkdownLexer;
+        let text = b"```totally-not-a-language\nsome text\n```";
+        assert!(lexer.injections(text).is_empty());
+    }
+
     #[test]
     fn test_markdown_formatting() {
         let lexer = MarkdownLexer;
@@ -198,4 +413,16 @@ mod tests {
         assert!(has_bold);
         assert!(has_italic);
     }
+
+    #[test]
+    fn test_markdown_unterminated_code() {
+        let lexer = MarkdownLexer;
+        let (_, diags) = lexer.tokenize_with_diagnostics(b"```\nno close\n");
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].message, LexMessage::UnclosedStringLiteral);
+
+        let (_, diags) = lexer.tokenize_with_diagnostics(b"text `inline");
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].message, LexMessage::UnclosedStringLiteral);
+    }
 }
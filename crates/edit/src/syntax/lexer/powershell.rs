@@ -1,17 +1,106 @@
-use super::Lexer;
-use crate::syntax::token::{Token, TokenKind};
+use super::cursor::{self, Cursor};
+use super::interp::MAX_INTERP_DEPTH;
+use super::{Diagnostic, Lexer, LexMessage, Logger, LexerState, hash_line_comment_kind, ps_block_comment_kind};
+use crate::syntax::token::{Token, TokenFlags, TokenKind};
 
 pub struct PowerShellLexer;
 
 impl Lexer for PowerShellLexer {
     fn tokenize(&self, text: &[u8]) -> Vec<Token> {
+        self.run(text, LexerState::Normal, &mut Logger::new(), 0).0
+    }
+
+    fn tokenize_with_diagnostics(&self, text: &[u8]) -> (Vec<Token>, Vec<Diagnostic>) {
+        let mut log = Logger::new();
+        let (tokens, _) = self.run(text, LexerState::Normal, &mut log, 0);
+        (tokens, log.into_diagnostics())
+    }
+
+    fn tokenize_line(&self, line: &[u8], entry: LexerState) -> (Vec<Token>, LexerState) {
+        self.run(line, entry, &mut Logger::new(), 0)
+    }
+
+    fn tokenize_capped(&self, text: &[u8], depth: usize) -> Vec<Token> {
+        self.run(text, LexerState::Normal, &mut Logger::new(), depth).0
+    }
+}
+
+impl PowerShellLexer {
+    /// Scan `text` starting in `entry` state, returning the tokens and the exit
+    /// [`LexerState`]. `InBlockComment` resumes scanning for `#>`;
+    /// `InHereString` resumes scanning for the `\n"@`/`\n'@` terminator of the
+    /// carried quote byte. The exit state reflects whether the buffer ended
+    /// mid-construct, so a host can re-highlight a single edited line by feeding
+    /// the previous line's exit state and stop once the state stabilizes.
+    ///
+    /// `depth` is how many enclosing `$( ... )` subexpressions (inside a
+    /// `"..."`/here-string) this call is already nested inside — see
+    /// [`emit_interp`](Self::emit_interp)'s own `depth` parameter for how it
+    /// bounds the recursion.
+    fn run(&self, text: &[u8], entry: LexerState, log: &mut Logger, depth: usize) -> (Vec<Token>, LexerState) {
         let mut tokens = Vec::new();
         let bytes = text;
+        // A `&str` view for char-correct dispatch and Unicode identifiers; on
+        // the rare invalid-UTF-8 buffer this is the valid prefix up to the
+        // first bad byte — shorter than `bytes`, never empty just because one
+        // byte somewhere is invalid — and the loop falls back to byte dispatch
+        // for the offending tail.
+        let src = match std::str::from_utf8(bytes) {
+            Ok(s) => s,
+            Err(e) => std::str::from_utf8(&bytes[..e.valid_up_to()]).unwrap(),
+        };
         let mut pos = 0;
+        let mut exit = LexerState::Normal;
+
+        // Resume a multi-line construct carried in from the previous chunk.
+        match entry {
+            LexerState::InBlockComment => {
+                let mut closed = false;
+                while pos + 1 < bytes.len() {
+                    if bytes[pos] == b'#' && bytes[pos + 1] == b'>' {
+                        pos += 2;
+                        closed = true;
+                        break;
+                    }
+                    pos += 1;
+                }
+                let mut flags = TokenFlags::NONE;
+                if !closed {
+                    pos = bytes.len();
+                    exit = LexerState::InBlockComment;
+                    flags = TokenFlags::UNTERMINATED;
+                    log.report(LexMessage::UnclosedBlockComment, 0..pos);
+                }
+                tokens.push(Token::with_flags(ps_block_comment_kind(&bytes[0..pos]), 0..pos, flags));
+            }
+            LexerState::InHereString { quote } => {
+                let (end, closed) = scan_here_string_body(bytes, 0, quote);
+                if !closed {
+                    exit = LexerState::InHereString { quote };
+                    log.report(LexMessage::UnterminatedHereString, 0..end);
+                }
+                if quote == b'"' {
+                    self.emit_interpolated(bytes, 0, end, &mut tokens, depth);
+                    if !closed {
+                        flag_last(&mut tokens, TokenFlags::UNTERMINATED);
+                    }
+                } else {
+                    let flags = if closed { TokenFlags::NONE } else { TokenFlags::UNTERMINATED };
+                    tokens.push(Token::with_flags(TokenKind::String, 0..end, flags));
+                }
+                pos = end;
+            }
+            _ => {}
+        }
 
         while pos < bytes.len() {
             let start = pos;
-            let ch = bytes[pos] as char;
+            // Decode a real `char` so multi-byte UTF-8 dispatches correctly.
+            let ch = if pos < src.len() {
+                src[pos..].chars().next().unwrap()
+            } else {
+                bytes[pos] as char
+            };
 
             match ch {
                 // Whitespace
@@ -19,10 +108,7 @@ impl Lexer for PowerShellLexer {
                     while pos < bytes.len() && matches!(bytes[pos] as char, ' ' | '\t' | '\r' | '\n') {
                         pos += 1;
                     }
-                    tokens.push(Token {
-                        kind: TokenKind::Whitespace,
-                        span: start..pos,
-                    });
+                    tokens.push(Token::new(TokenKind::Whitespace, start..pos));
                 }
 
                 // Comment
@@ -30,70 +116,66 @@ impl Lexer for PowerShellLexer {
                     while pos < bytes.len() && bytes[pos] != b'\n' {
                         pos += 1;
                     }
-                    tokens.push(Token {
-                        kind: TokenKind::Comment,
-                        span: start..pos,
-                    });
+                    tokens.push(Token::new(hash_line_comment_kind(&bytes[start..pos]), start..pos));
                 }
 
                 // Block comment
-                '<' if pos + 1 < bytes.len() && bytes[pos + 1] == b'#' => {
+                '<' if { let mut c = Cursor::new(src); c.seek(pos); c.peek2() == Some('#') } => {
                     pos += 2;
+                    let mut closed = false;
                     while pos + 1 < bytes.len() {
                         if bytes[pos] == b'#' && bytes[pos + 1] == b'>' {
                             pos += 2;
+                            closed = true;
                             break;
                         }
                         pos += 1;
                     }
-                    tokens.push(Token {
-                        kind: TokenKind::Comment,
-                        span: start..pos,
-                    });
+                    let mut flags = TokenFlags::NONE;
+                    if !closed {
+                        pos = bytes.len();
+                        exit = LexerState::InBlockComment;
+                        flags = TokenFlags::UNTERMINATED;
+                        log.report(LexMessage::UnclosedBlockComment, start..pos);
+                    }
+                    tokens.push(Token::with_flags(ps_block_comment_kind(&bytes[start..pos]), start..pos, flags));
                 }
 
-                // Double-quoted string
+                // Double-quoted string (interpolating)
                 '"' => {
-                    pos += 1;
-                    while pos < bytes.len() {
-                        match bytes[pos] as char {
-                            '`' => pos += 2, // Escape with backtick
-                            '"' => {
-                                pos += 1;
-                                break;
-                            }
-                            '$' if pos + 1 < bytes.len() && matches!(bytes[pos + 1] as char, '{' | '(' | 'a'..='z' | 'A'..='Z' | '_') => {
-                                // Variable inside string
-                                pos += 1;
-                            }
-                            _ => pos += 1,
-                        }
+                    let (end, closed) = scan_double_quoted_end(bytes, pos);
+                    if !closed {
+                        log.report(LexMessage::UnclosedStringLiteral, start..end);
+                    }
+                    self.emit_interpolated(bytes, start, end, &mut tokens, depth);
+                    if !closed {
+                        flag_last(&mut tokens, TokenFlags::UNTERMINATED);
                     }
-                    tokens.push(Token {
-                        kind: TokenKind::String,
-                        span: start..pos,
-                    });
+                    pos = end;
                 }
 
                 // Single-quoted string
                 '\'' => {
                     pos += 1;
+                    let mut closed = false;
                     while pos < bytes.len() {
                         if bytes[pos] == b'\'' {
                             if pos + 1 < bytes.len() && bytes[pos + 1] == b'\'' {
                                 pos += 2; // Escaped quote
                             } else {
                                 pos += 1;
+                                closed = true;
                                 break;
                             }
                         } else {
                             pos += 1;
                         }
                     }
-                    tokens.push(Token {
-                        kind: TokenKind::String,
-                        span: start..pos,
-                    });
+                    let flags = if closed { TokenFlags::NONE } else { TokenFlags::UNTERMINATED };
+                    if !closed {
+                        log.report(LexMessage::UnclosedStringLiteral, start..pos);
+                    }
+                    tokens.push(Token::with_flags(TokenKind::String, start..pos, flags));
                 }
 
                 // Here-string @" or @'
@@ -104,75 +186,102 @@ impl Lexer for PowerShellLexer {
                     if pos < bytes.len() && bytes[pos] == b'\n' {
                         pos += 1;
                     }
-                    // Read until closing quote on new line
-                    while pos + 1 < bytes.len() {
-                        if bytes[pos] == b'\n' && bytes[pos + 1] == quote && pos + 2 < bytes.len() && bytes[pos + 2] == b'@' {
-                            pos += 3;
-                            break;
+                    let (end, closed) = scan_here_string_body(bytes, pos, quote);
+                    if !closed {
+                        exit = LexerState::InHereString { quote };
+                        log.report(LexMessage::UnterminatedHereString, start..end);
+                    }
+                    // `@"..."@` interpolates; `@'...'@` is a literal.
+                    if quote == b'"' {
+                        self.emit_interpolated(bytes, start, end, &mut tokens, depth);
+                        if !closed {
+                            flag_last(&mut tokens, TokenFlags::UNTERMINATED);
                         }
-                        pos += 1;
+                    } else {
+                        let flags = if closed { TokenFlags::NONE } else { TokenFlags::UNTERMINATED };
+                        tokens.push(Token::with_flags(TokenKind::String, start..end, flags));
                     }
-                    tokens.push(Token {
-                        kind: TokenKind::String,
-                        span: start..pos,
-                    });
+                    pos = end;
                 }
 
                 // Variables
                 '$' => {
                     pos += 1;
-                    if pos < bytes.len() {
-                        match bytes[pos] as char {
-                            // Special variables
-                            '?' | '^' | '$' => {
+                    let next = if pos < src.len() {
+                        src[pos..].chars().next()
+                    } else if pos < bytes.len() {
+                        Some(bytes[pos] as char)
+                    } else {
+                        None
+                    };
+                    match next {
+                        // Special variables
+                        Some('?') | Some('^') | Some('$') => {
+                            pos += 1;
+                        }
+                        // Braced variable
+                        Some('{') => {
+                            pos += 1;
+                            while pos < bytes.len() && bytes[pos] != b'}' {
                                 pos += 1;
                             }
-                            // Braced variable
-                            '{' => {
+                            if pos < bytes.len() {
                                 pos += 1;
-                                while pos < bytes.len() && bytes[pos] != b'}' {
-                                    pos += 1;
-                                }
-                                if pos < bytes.len() {
-                                    pos += 1;
-                                }
+                            } else {
+                                log.report(LexMessage::UnterminatedBracedVariable, start..pos);
                             }
-                            // Subexpression
-                            '(' => {
-                                pos += 1;
-                                let mut depth = 1;
-                                while pos < bytes.len() && depth > 0 {
-                                    match bytes[pos] as char {
-                                        '(' => depth += 1,
-                                        ')' => depth -= 1,
-                                        _ => {}
-                                    }
-                                    pos += 1;
+                        }
+                        // Subexpression
+                        Some('(') => {
+                            pos += 1;
+                            let mut depth = 1;
+                            while pos < bytes.len() && depth > 0 {
+                                match bytes[pos] as char {
+                                    '(' => depth += 1,
+                                    ')' => depth -= 1,
+                                    _ => {}
                                 }
+                                pos += 1;
                             }
-                            // Regular variable
-                            'a'..='z' | 'A'..='Z' | '_' => {
-                                while pos < bytes.len() && matches!(bytes[pos] as char, 'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | ':') {
+                        }
+                        // Regular variable (Unicode identifier, `:` scope)
+                        Some(c) if cursor::is_ident_start(c) => {
+                            if pos < src.len() {
+                                let mut cur = Cursor::new(src);
+                                cur.seek(pos);
+                                pos = cur.eat_while(|c| cursor::is_ident_continue(c) || c == ':');
+                            } else {
+                                // Past the valid UTF-8 prefix: `Cursor` has no
+                                // `&str` to seek into here, so fall back to
+                                // the same lossy byte-cast dispatch the rest
+                                // of this function already uses for the
+                                // offending tail.
+                                while pos < bytes.len()
+                                    && (cursor::is_ident_continue(bytes[pos] as char) || bytes[pos] == b':')
+                                {
                                     pos += 1;
                                 }
                             }
-                            _ => {}
                         }
+                        _ => {}
                     }
-                    tokens.push(Token {
-                        kind: TokenKind::VariableName,
-                        span: start..pos,
-                    });
+                    tokens.push(Token::new(TokenKind::VariableName, start..pos));
                 }
 
                 // Numbers
                 '0'..='9' => {
+                    let mut flags = TokenFlags::NONE;
                     // Hex
                     if ch == '0' && pos + 1 < bytes.len() && matches!(bytes[pos + 1] as char, 'x' | 'X') {
                         pos += 2;
+                        let digits_start = pos;
                         while pos < bytes.len() && (bytes[pos] as char).is_ascii_hexdigit() {
                             pos += 1;
                         }
+                        // `0x` with no hex digits is a degenerate literal.
+                        if pos == digits_start {
+                            flags = TokenFlags::INVALID_SUFFIX;
+                        }
                     } else {
                         while pos < bytes.len() && (bytes[pos] as char).is_ascii_digit() {
                             pos += 1;
@@ -190,21 +299,28 @@ impl Lexer for PowerShellLexer {
                             pos += 2;
                         }
                     }
-                    tokens.push(Token {
-                        kind: TokenKind::Number,
-                        span: start..pos,
-                    });
+                    tokens.push(Token::with_flags(TokenKind::Number, start..pos, flags));
                 }
 
-                // Keywords, cmdlets, and identifiers
-                'a'..='z' | 'A'..='Z' | '_' => {
-                    while pos < bytes.len() {
-                        match bytes[pos] as char {
-                            'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-' => pos += 1,
-                            _ => break,
+                // Keywords, cmdlets, and identifiers (Unicode-aware)
+                _ if cursor::is_ident_start(ch) => {
+                    if pos < src.len() {
+                        let mut cur = Cursor::new(src);
+                        cur.seek(pos);
+                        cur.bump();
+                        cur.eat_while(|c| cursor::is_ident_continue(c) || c == '-');
+                        pos = cur.offset();
+                    } else {
+                        // Past the valid UTF-8 prefix: same fallback as the
+                        // variable-name branch above.
+                        pos += 1;
+                        while pos < bytes.len()
+                            && (cursor::is_ident_continue(bytes[pos] as char) || bytes[pos] == b'-')
+                        {
+                            pos += 1;
                         }
                     }
-                    
+
                     let word = std::str::from_utf8(&bytes[start..pos]).unwrap_or("");
                     let kind = match word.to_lowercase().as_str() {
                         // Keywords
@@ -230,7 +346,7 @@ impl Lexer for PowerShellLexer {
                         _ => TokenKind::Identifier,
                     };
                     
-                    tokens.push(Token { kind, span: start..pos });
+                    tokens.push(Token::new(kind, start..pos));
                 }
 
                 // Operators and punctuation
@@ -240,10 +356,7 @@ impl Lexer for PowerShellLexer {
                     while pos < bytes.len() && matches!(bytes[pos] as char, 'a'..='z' | 'A'..='Z' | '0'..='9' | '_') {
                         pos += 1;
                     }
-                    tokens.push(Token {
-                        kind: TokenKind::Operator,
-                        span: start..pos,
-                    });
+                    tokens.push(Token::new(TokenKind::Operator, start..pos));
                 }
 
                 '+' | '-' | '*' | '/' | '%' | '=' | '!' | '<' | '>' | '&' | '|' | '^' | '~' |
@@ -260,10 +373,7 @@ impl Lexer for PowerShellLexer {
                             pos += 1;
                         }
                     }
-                    tokens.push(Token {
-                        kind: TokenKind::Operator,
-                        span: start..pos,
-                    });
+                    tokens.push(Token::new(TokenKind::Operator, start..pos));
                 }
 
                 // Backtick (escape or line continuation)
@@ -272,23 +382,219 @@ impl Lexer for PowerShellLexer {
                     if pos < bytes.len() {
                         pos += 1;
                     }
-                    tokens.push(Token {
-                        kind: TokenKind::Operator,
-                        span: start..pos,
-                    });
+                    tokens.push(Token::new(TokenKind::Operator, start..pos));
                 }
 
-                // Unknown character
+                // Unknown character — advance a whole char so multi-byte input
+                // yields one `Error` token, not one per byte.
                 _ => {
-                    pos += 1;
-                    tokens.push(Token {
-                        kind: TokenKind::Error,
-                        span: start..pos,
-                    });
+                    pos += if pos < src.len() { ch.len_utf8() } else { 1 };
+                    log.report(LexMessage::UnexpectedCharacter(bytes[start]), start..pos);
+                    tokens.push(Token::new(TokenKind::Error, start..pos));
                 }
             }
         }
 
-        tokens
+        (tokens, exit)
     }
+
+    /// Split the string region `bytes[from..to]` into a flat run of tokens:
+    /// `String` segments interrupted by `VariableName` tokens for `$var`/
+    /// `${...}` and, for `$( ... )` subexpressions, the `$(`/`)` delimiters
+    /// plus a recursively lexed run of the inner expression. The emitted spans
+    /// tile `[from..to]` exactly so existing span-based consumers keep working.
+    ///
+    /// `depth` is forwarded to [`emit_interp`](Self::emit_interp) — see its doc
+    /// comment for how it bounds `$( ... )` recursion.
+    fn emit_interpolated(&self, bytes: &[u8], from: usize, to: usize, tokens: &mut Vec<Token>, depth: usize) {
+        let mut seg_start = from;
+        let mut pos = from;
+        while pos < to {
+            match bytes[pos] {
+                // Backtick escapes the next byte inside an expandable string.
+                b'`' => pos = (pos + 2).min(to),
+                b'$' => match interp_span(bytes, pos, to) {
+                    Some(end) => {
+                        if pos > seg_start {
+                            tokens.push(Token::new(TokenKind::String, seg_start..pos));
+                        }
+                        self.emit_interp(bytes, pos, end, tokens, depth);
+                        pos = end;
+                        seg_start = pos;
+                    }
+                    None => pos += 1,
+                },
+                _ => pos += 1,
+            }
+        }
+        if to > seg_start {
+            tokens.push(Token::new(TokenKind::String, seg_start..to));
+        }
+    }
+
+    /// Emit the token(s) for a single `$...` interpolation occupying
+    /// `bytes[start..end]`: a lone `VariableName`, or — for `$( ... )` — the
+    /// `$(`/`)` delimiters around a recursively lexed subexpression.
+    ///
+    /// `depth` counts how many enclosing `$( ... )` subexpressions this call is
+    /// already nested inside (`0` at the top level of a string); once it
+    /// reaches [`MAX_INTERP_DEPTH`] the subexpression's interior is kept as a
+    /// flat [`TokenKind::Error`] token instead of being re-lexed, bounding the
+    /// recursion through `self.run`.
+    fn emit_interp(&self, bytes: &[u8], start: usize, end: usize, tokens: &mut Vec<Token>, depth: usize) {
+        if bytes.get(start + 1) == Some(&b'(') {
+            tokens.push(Token::new(TokenKind::Operator, start..start + 2));
+            let closed = end > start + 2 && bytes[end - 1] == b')';
+            let inner_to = if closed { end - 1 } else { end };
+            let inner_from = start + 2;
+            if depth < MAX_INTERP_DEPTH {
+                for mut t in self.run(&bytes[inner_from..inner_to], LexerState::Normal, &mut Logger::new(), depth + 1).0 {
+                    t.span.start += inner_from;
+                    t.span.end += inner_from;
+                    tokens.push(t);
+                }
+            } else {
+                tokens.push(Token::new(TokenKind::Error, inner_from..inner_to));
+            }
+            if closed {
+                tokens.push(Token::new(TokenKind::Operator, inner_to..end));
+            }
+        } else {
+            tokens.push(Token::new(TokenKind::VariableName, start..end));
+        }
+    }
+}
+
+/// OR `flags` into the most recently pushed token, if any. Used to flag the
+/// trailing segment of an interpolated string as [`TokenFlags::UNTERMINATED`]
+/// without threading the flag through [`PowerShellLexer::emit_interpolated`].
+fn flag_last(tokens: &mut [Token], flags: TokenFlags) {
+    if let Some(last) = tokens.last_mut() {
+        last.flags |= flags;
+    }
+}
+
+/// Classify a `$...` interpolation starting at `pos` (a `$`) within the bound
+/// `to`. Returns the index one past the construct, or `None` when `$` is not
+/// followed by an interpolation introducer (so it stays part of the text).
+fn interp_span(bytes: &[u8], pos: usize, to: usize) -> Option<usize> {
+    let next = *bytes.get(pos + 1).filter(|_| pos + 1 < to)?;
+    match next {
+        // Special variables `$?`, `$^`, `$$`.
+        b'?' | b'^' | b'$' => Some(pos + 2),
+        // Braced `${...}`.
+        b'{' => {
+            let mut i = pos + 2;
+            while i < to && bytes[i] != b'}' {
+                i += 1;
+            }
+            Some((i + 1).min(to))
+        }
+        // Subexpression `$( ... )`, balancing parens and honoring backticks.
+        b'(' => {
+            let mut i = pos + 2;
+            let mut depth = 1;
+            while i < to && depth > 0 {
+                match bytes[i] {
+                    b'`' => i += 1,
+                    b'(' => depth += 1,
+                    b')' => depth -= 1,
+                    _ => {}
+                }
+                i += 1;
+            }
+            Some(i.min(to))
+        }
+        // Ordinary `$name` (identifier, allowing `:` scope separators).
+        b'a'..=b'z' | b'A'..=b'Z' | b'_' => {
+            let mut i = pos + 1;
+            while i < to && matches!(bytes[i], b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'_' | b':') {
+                i += 1;
+            }
+            Some(i)
+        }
+        _ => None,
+    }
+}
+
+/// Scan a double-quoted string starting at `start` (a `"`), honoring backtick
+/// escapes. Returns the index one past the closing quote (or end-of-input) and
+/// whether the string closed.
+fn scan_double_quoted_end(bytes: &[u8], start: usize) -> (usize, bool) {
+    let mut pos = start + 1;
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'`' => pos += 2,
+            b'"' => return (pos + 1, true),
+            _ => pos += 1,
+        }
+    }
+    (bytes.len(), false)
+}
+
+/// Scan a here-string body from `body_start`, looking for the closing
+/// `\n"@`/`\n'@` for the given `quote`, or a `"@`/`'@` right at `body_start`
+/// (whose line break precedes it). Returns the index past the terminator
+/// (or end-of-input) and whether it closed.
+fn scan_here_string_body(bytes: &[u8], body_start: usize, quote: u8) -> (usize, bool) {
+    let mut pos = body_start;
+    if bytes.get(pos) == Some(&quote) && bytes.get(pos + 1) == Some(&b'@') {
+        return (pos + 2, true);
+    }
+    while pos + 1 < bytes.len() {
+        if bytes[pos] == b'\n' && bytes[pos + 1] == quote && pos + 2 < bytes.len() && bytes[pos + 2] == b'@' {
+            return (pos + 3, true);
+        }
+        pos += 1;
+    }
+    (bytes.len(), false)
+}
+
+/// Render a whitespace-compressed (minified) version of the script described by
+/// `tokens` over `src`. `Comment` tokens are dropped, runs of `Whitespace` are
+/// collapsed to the single space needed to keep two word-like tokens (e.g.
+/// `Identifier`/`Keyword`/`Number`/`VariableName`) from merging and dropped
+/// elsewhere, and all other tokens — including `String`/here-string spans — are
+/// copied verbatim. Because the lexer already recorded precise kinds and spans,
+/// this is a single stream walk that decides separator-or-not from each adjacent
+/// pair of significant tokens.
+pub fn minify(tokens: &[Token], src: &[u8]) -> String {
+    let mut out = String::with_capacity(src.len());
+    let mut prev: Option<TokenKind> = None;
+    let mut pending_ws = false;
+    for token in tokens {
+        match token.kind {
+            k if k.is_comment() => continue,
+            TokenKind::Whitespace => {
+                pending_ws = true;
+                continue;
+            }
+            _ => {}
+        }
+        // Keep a single separator only where dropping it would fuse two
+        // word-like lexemes into one.
+        if pending_ws && prev.is_some_and(is_word_like) && is_word_like(token.kind) {
+            out.push(' ');
+        }
+        out.push_str(&String::from_utf8_lossy(&src[token.span.clone()]));
+        prev = Some(token.kind);
+        pending_ws = false;
+    }
+    out
+}
+
+/// Whether a token is an alphanumeric "word" lexeme that would merge with an
+/// adjacent word if no separator stood between them.
+fn is_word_like(kind: TokenKind) -> bool {
+    kind.is_keyword()
+        || matches!(
+            kind,
+            TokenKind::Identifier
+                | TokenKind::Number
+                | TokenKind::Boolean
+                | TokenKind::Null
+                | TokenKind::VariableName
+                | TokenKind::TypeName
+                | TokenKind::FunctionName
+        )
 }
@@ -3,17 +3,158 @@
 
 //! High-performance Python lexer.
 
-use crate::syntax::lexer::{Lexer, is_ident_start, is_ident_continue, is_ascii_digit};
+use crate::syntax::lexer::diagnostic::{LexMessage, Logger};
+use crate::syntax::lexer::interp::MAX_INTERP_DEPTH;
+use crate::syntax::lexer::{Diagnostic, Lexer, LexerState, is_ident_start, is_ident_continue, is_ascii_digit, ident_start_len, ident_continue_len, first_code_point};
 use crate::syntax::{Token, TokenKind};
 
 pub struct PythonLexer;
 
+/// One entry on the indentation stack: the leading tab and space counts of the
+/// logical line that opened the block. Keeping the two counts apart (rather
+/// than collapsing them to a single width) lets the lexer flag lines that mix
+/// tabs and spaces in a way that is deeper on one axis and shallower on the
+/// other — an ambiguity Python itself rejects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct IndentationLevel {
+    tabs: usize,
+    spaces: usize,
+}
+
+impl IndentationLevel {
+    /// Whether `self` is strictly deeper than `other`: neither count shrank
+    /// and at least one grew.
+    fn deeper_than(self, other: Self) -> bool {
+        self.tabs >= other.tabs
+            && self.spaces >= other.spaces
+            && (self.tabs > other.tabs || self.spaces > other.spaces)
+    }
+
+    /// Whether `self` is strictly shallower than `other`: neither count grew
+    /// and at least one shrank.
+    fn shallower_than(self, other: Self) -> bool {
+        self.tabs <= other.tabs
+            && self.spaces <= other.spaces
+            && (self.tabs < other.tabs || self.spaces < other.spaces)
+    }
+}
+
 impl Lexer for PythonLexer {
     fn tokenize(&self, text: &[u8]) -> Vec<Token> {
+        // An empty logger allocates nothing until something is reported.
+        self.run(text, LexerState::Normal, true, &mut Logger::new(), 0).0
+    }
+
+    fn tokenize_with_diagnostics(&self, text: &[u8]) -> (Vec<Token>, Vec<Diagnostic>) {
+        let mut log = Logger::new();
+        let (tokens, _) = self.run(text, LexerState::Normal, true, &mut log, 0);
+        (tokens, log.into_diagnostics())
+    }
+
+    fn tokenize_line(&self, line: &[u8], entry: LexerState) -> (Vec<Token>, LexerState) {
+        // A single line cannot see the whole block structure, so INDENT/DEDENT
+        // (which depend on the full indentation stack) are left to the
+        // whole-buffer `tokenize`; the line path resumes only the lexical state
+        // that actually crosses line boundaries — triple-quoted strings.
+        self.run(line, entry, false, &mut Logger::new(), 0)
+    }
+
+    fn tokenize_capped(&self, text: &[u8], depth: usize) -> Vec<Token> {
+        self.run(text, LexerState::Normal, true, &mut Logger::new(), depth).0
+    }
+}
+
+impl PythonLexer {
+    /// Tokenize `text` starting in `entry` state, recording an
+    /// [`UnclosedStringLiteral`] diagnostic into `log` wherever a quoted,
+    /// triple-quoted, or f-string run reaches end-of-input (or, for
+    /// single-line strings, a bare newline) before its closing quote.
+    ///
+    /// When `track_indent` is set, leading whitespace at each logical line
+    /// produces `Indent`/`Dedent` tokens; the line-resumable path disables this
+    /// because a lone line has no stack to compare against. Returns the tokens
+    /// and the [`LexerState`] the scan ended in — `InTripleString` when `text`
+    /// ends inside an unclosed triple-quoted string, else `Normal`. An unclosed
+    /// triple-quoted f-string is not carried: it ends the scan in `Normal`.
+    ///
+    /// `recursion_depth` is how many enclosing f-string interpolation holes
+    /// this call is already nested inside — see [`lex_fstring`](Self::lex_fstring)'s
+    /// own parameter of the same name for how it bounds the recursion.
+    ///
+    /// [`UnclosedStringLiteral`]: LexMessage::UnclosedStringLiteral
+    fn run(
+        &self,
+        text: &[u8],
+        entry: LexerState,
+        track_indent: bool,
+        log: &mut Logger,
+        recursion_depth: usize,
+    ) -> (Vec<Token>, LexerState) {
         let mut tokens = Vec::with_capacity(text.len() / 8);
         let mut pos = 0;
+        let mut exit = LexerState::Normal;
+
+        // Significant-indentation state. Each level records the `(tabs, spaces)`
+        // of the logical line that opened it; the base level is `(0, 0)`.
+        // `nesting` tracks open `(`/`[`/`{` so continuation lines inside
+        // brackets don't participate in indentation.
+        let mut indent_stack: Vec<IndentationLevel> = vec![IndentationLevel { tabs: 0, spaces: 0 }];
+        let mut nesting: usize = 0;
+
+        // Resume a triple-quoted string carried over from the previous line.
+        if let LexerState::InTripleString { quote } = entry {
+            let closed = scan_triple_close(text, &mut pos, quote);
+            if !closed {
+                exit = LexerState::InTripleString { quote };
+            }
+            tokens.push(Token::new(TokenKind::String, 0..pos));
+        }
 
         while pos < text.len() {
+            // At the start of a logical line (outside brackets), reconcile the
+            // leading whitespace against the indentation stack.
+            if track_indent && nesting == 0 && (pos == 0 || text[pos - 1] == b'\n') {
+                let mut p = pos;
+                let (mut tabs, mut spaces) = (0usize, 0usize);
+                while p < text.len() {
+                    match text[p] {
+                        b'\t' => tabs += 1,
+                        b' ' => spaces += 1,
+                        _ => break,
+                    }
+                    p += 1;
+                }
+                // Blank and comment-only lines never change the indentation.
+                let logical = p < text.len() && text[p] != b'\n' && text[p] != b'#';
+                if logical {
+                    let level = IndentationLevel { tabs, spaces };
+                    let top = *indent_stack.last().unwrap();
+                    if level.deeper_than(top) {
+                        indent_stack.push(level);
+                        tokens.push(Token::new(TokenKind::Indent, pos..p));
+                        pos = p;
+                        continue;
+                    } else if level.shallower_than(top) {
+                        // Unwind one level per `Dedent` until we reach a level
+                        // no deeper than this line's indentation.
+                        while {
+                            let t = *indent_stack.last().unwrap();
+                            t.tabs > tabs || t.spaces > spaces
+                        } {
+                            indent_stack.pop();
+                            tokens.push(Token::new(TokenKind::Dedent, pos..pos));
+                        }
+                        // The leading whitespace itself is emitted below.
+                    } else if level != top {
+                        // One of tabs/spaces grew while the other shrank:
+                        // ambiguous mixing we refuse to guess at.
+                        tokens.push(Token::new(TokenKind::Error, pos..p));
+                        pos = p;
+                        continue;
+                    }
+                }
+            }
+
             let start = pos;
             let b = text[pos];
 
@@ -41,65 +182,29 @@ impl Lexer for PythonLexer {
                     tokens.push(Token::new(TokenKind::Comment, start..pos));
                 }
 
-                // String literals
-                b'"' | b'\'' => {
-                    let quote = b;
-                    pos += 1;
-                    
-                    // Check for triple-quoted string
-                    let triple = pos + 1 < text.len() 
-                        && text[pos] == quote 
-                        && text[pos + 1] == quote;
-                    
-                    if triple {
-                        pos += 2;
-                        while pos + 2 < text.len() {
-                            if text[pos] == quote && text[pos + 1] == quote && text[pos + 2] == quote {
-                                pos += 3;
-                                break;
-                            }
-                            pos += 1;
-                        }
+                // Prefixed string literals: `r"…"`, `b"…"`, `rb"…"`, `f"…"`,
+                // `u"…"`, and their triple-quoted forms. The whole prefix-plus-
+                // literal is one token; f-string interpolations still highlight.
+                _ if string_prefix(&text[pos..]).is_some() => {
+                    let (plen, raw, fstr) = string_prefix(&text[pos..]).unwrap();
+                    if fstr {
+                        pos = self.lex_fstring(text, start, plen, raw, &mut tokens, log, recursion_depth);
                     } else {
-                        let mut escaped = false;
-                        while pos < text.len() {
-                            if escaped {
-                                escaped = false;
-                            } else if text[pos] == b'\\' {
-                                escaped = true;
-                            } else if text[pos] == quote {
-                                pos += 1;
-                                break;
-                            } else if text[pos] == b'\n' {
-                                break; // Unterminated string
-                            }
-                            pos += 1;
+                        let (end, e) = self.lex_string(text, start, plen, &mut tokens, log);
+                        pos = end;
+                        if e != LexerState::Normal {
+                            exit = e;
                         }
                     }
-                    
-                    tokens.push(Token::new(TokenKind::String, start..pos));
                 }
 
-                // F-strings
-                b'f' | b'F' if pos + 1 < text.len() && matches!(text[pos + 1], b'"' | b'\'') => {
-                    pos += 1;
-                    let quote = text[pos];
-                    pos += 1;
-                    
-                    let mut escaped = false;
-                    while pos < text.len() {
-                        if escaped {
-                            escaped = false;
-                        } else if text[pos] == b'\\' {
-                            escaped = true;
-                        } else if text[pos] == quote {
-                            pos += 1;
-                            break;
-                        }
-                        pos += 1;
+                // Unprefixed string literals.
+                b'"' | b'\'' => {
+                    let (end, e) = self.lex_string(text, start, 0, &mut tokens, log);
+                    pos = end;
+                    if e != LexerState::Normal {
+                        exit = e;
                     }
-                    
-                    tokens.push(Token::new(TokenKind::String, start..pos));
                 }
 
                 // Numbers
@@ -156,12 +261,15 @@ impl Lexer for PythonLexer {
                     tokens.push(Token::new(TokenKind::Number, start..pos));
                 }
 
-                // Identifiers and keywords
-                _ if is_ident_start(b) => {
-                    while pos < text.len() && is_ident_continue(text[pos]) {
-                        pos += 1;
+                // Identifiers and keywords (Unicode-aware: `café` and non-Latin
+                // names lex as a single identifier when the `unicode-ident`
+                // feature is enabled).
+                _ if ident_start_len(&text[pos..]).is_some() => {
+                    pos += ident_start_len(&text[pos..]).unwrap();
+                    while let Some(len) = ident_continue_len(&text[pos..]) {
+                        pos += len;
                     }
-                    
+
                     let word = &text[start..pos];
                     let kind = match word {
                         b"and" | b"or" | b"not" | b"in" | b"is" => TokenKind::KeywordOperator,
@@ -205,6 +313,11 @@ impl Lexer for PythonLexer {
 
                 // Delimiters
                 b'{' | b'}' | b'[' | b']' | b'(' | b')' => {
+                    if matches!(b, b'(' | b'[' | b'{') {
+                        nesting += 1;
+                    } else {
+                        nesting = nesting.saturating_sub(1);
+                    }
                     pos += 1;
                     tokens.push(Token::new(TokenKind::Delimiter, start..pos));
                 }
@@ -215,15 +328,225 @@ impl Lexer for PythonLexer {
                     tokens.push(Token::new(TokenKind::Punctuation, start..pos));
                 }
 
-                // Unknown
+                // Unknown: advance by a whole code point so an invalid
+                // multi-byte sequence becomes one Error token, not several.
                 _ => {
-                    pos += 1;
+                    pos += first_code_point(&text[pos..]).map_or(1, |(_, len)| len);
                     tokens.push(Token::new(TokenKind::Error, start..pos));
                 }
             }
         }
 
-        tokens
+        // Close any blocks still open at end-of-input with trailing `Dedent`s.
+        while track_indent && indent_stack.len() > 1 {
+            indent_stack.pop();
+            tokens.push(Token::new(TokenKind::Dedent, text.len()..text.len()));
+        }
+
+        (tokens, exit)
+    }
+}
+
+/// Recognize a string prefix at the start of `bytes`: one or two letters drawn
+/// from `{r, R, b, B, f, F, u, U}` immediately followed by a `"` or `'`.
+/// Returns the prefix length together with whether it denotes a raw string
+/// (contains `r`/`R`) and an f-string (contains `f`/`F`). Returns `None` when
+/// the leading bytes are not a valid prefix followed by a quote — in which case
+/// the run is an ordinary identifier, not a string.
+fn string_prefix(bytes: &[u8]) -> Option<(usize, bool, bool)> {
+    let is_prefix = |c: u8| matches!(c, b'r' | b'R' | b'b' | b'B' | b'f' | b'F' | b'u' | b'U');
+    let mut len = 0;
+    while len < 2 && len < bytes.len() && is_prefix(bytes[len]) {
+        len += 1;
+    }
+    if len == 0 || len >= bytes.len() || !matches!(bytes[len], b'"' | b'\'') {
+        return None;
+    }
+    let raw = bytes[..len].iter().any(|&c| matches!(c, b'r' | b'R'));
+    let fstring = bytes[..len].iter().any(|&c| matches!(c, b'f' | b'F'));
+    Some((len, raw, fstring))
+}
+
+/// Advance `pos` to just past a `"""`/`'''` triple-quote terminator made of
+/// `quote`, returning `true` if it was found. On an unterminated string `pos`
+/// lands at EOF and the result is `false`, so the caller can suspend into
+/// [`LexerState::InTripleString`].
+fn scan_triple_close(text: &[u8], pos: &mut usize, quote: u8) -> bool {
+    while *pos + 2 < text.len() {
+        if text[*pos] == quote && text[*pos + 1] == quote && text[*pos + 2] == quote {
+            *pos += 3;
+            return true;
+        }
+        *pos += 1;
+    }
+    *pos = text.len();
+    false
+}
+
+impl PythonLexer {
+    /// Scan a (possibly prefixed) non-f-string literal starting at `start`, with
+    /// `prefix_len` leading prefix bytes before the opening quote. Handles both
+    /// single- and triple-quoted forms and emits the whole prefix-plus-literal
+    /// as one `String` token. A backslash before a quote keeps that quote from
+    /// closing the literal in both raw and cooked strings (raw only changes the
+    /// string's *value*, not where it ends), so the terminator scan is the same
+    /// for `"…"`, `b"…"`, and `r"…"`. Returns the position just past the closing
+    /// quote (or end-of-input for an unterminated literal) and the
+    /// [`LexerState`] to suspend in — `InTripleString` for an unclosed triple,
+    /// else `Normal`. An unterminated literal is also reported to `log`.
+    fn lex_string(&self, text: &[u8], start: usize, prefix_len: usize, tokens: &mut Vec<Token>, log: &mut Logger) -> (usize, LexerState) {
+        let mut pos = start + prefix_len;
+        let quote = text[pos];
+        pos += 1;
+        let triple = pos + 1 < text.len() && text[pos] == quote && text[pos + 1] == quote;
+
+        let mut exit = LexerState::Normal;
+        let mut closed = false;
+        if triple {
+            pos += 2; // past the opening triple quote
+            closed = scan_triple_close(text, &mut pos, quote);
+            if !closed {
+                exit = LexerState::InTripleString { quote };
+            }
+        } else {
+            let mut escaped = false;
+            while pos < text.len() {
+                if escaped {
+                    escaped = false;
+                } else if text[pos] == b'\\' {
+                    escaped = true;
+                } else if text[pos] == quote {
+                    pos += 1;
+                    closed = true;
+                    break;
+                } else if text[pos] == b'\n' {
+                    break; // Unterminated string
+                }
+                pos += 1;
+            }
+        }
+        if !closed {
+            log.report(LexMessage::UnclosedStringLiteral, start..pos);
+        }
+        tokens.push(Token::new(TokenKind::String, start..pos));
+        (pos, exit)
+    }
+
+    /// Tokenize an f-string that begins at `start`, with `prefix_len` leading
+    /// prefix bytes (`f`, `rf`, `fr`, …) before the opening quote, pushing the
+    /// literal segments as `String` and recursively lexing each `{expr}`
+    /// interpolation with the same lexer so the expression highlights. When
+    /// `raw` is set (an `rf`/`fr` string) a backslash is an ordinary literal
+    /// byte rather than an escape. Returns the position just past the closing
+    /// quote (or end-of-input for an unterminated literal). Handles both single-
+    /// and triple-quoted forms; a doubled `{{`/`}}` stays literal, and a `:` at
+    /// the top brace level begins a format spec that is treated as literal text
+    /// (itself allowing nested `{...}` replacement fields).
+    ///
+    /// `recursion_depth` counts how many enclosing f-string holes this call is
+    /// already nested inside (`0` for a top-level f-string); once it reaches
+    /// [`MAX_INTERP_DEPTH`] a hole's interior is kept as literal text instead of
+    /// being re-lexed, bounding the recursion through `self.tokenize_capped`.
+    fn lex_fstring(
+        &self,
+        text: &[u8],
+        start: usize,
+        prefix_len: usize,
+        raw: bool,
+        tokens: &mut Vec<Token>,
+        log: &mut Logger,
+        recursion_depth: usize,
+    ) -> usize {
+        let mut pos = start + prefix_len; // past the prefix
+        let quote = text[pos];
+        let triple = pos + 2 < text.len() && text[pos + 1] == quote && text[pos + 2] == quote;
+        pos += if triple { 3 } else { 1 };
+
+        // Start of the literal run not yet emitted (covers the prefix + opener).
+        let mut lit_start = start;
+        let mut closed = false;
+        while pos < text.len() {
+            let c = text[pos];
+            // Closing quote(s).
+            if c == quote {
+                if triple {
+                    if pos + 2 < text.len() && text[pos + 1] == quote && text[pos + 2] == quote {
+                        pos += 3;
+                        closed = true;
+                        break;
+                    }
+                } else {
+                    pos += 1;
+                    closed = true;
+                    break;
+                }
+            }
+            // A bare newline ends an unterminated single-quoted f-string.
+            if !triple && c == b'\n' {
+                break;
+            }
+            // Backslash escapes the next byte, except in raw f-strings where it
+            // is a literal byte (so `rf"\{x}"` still sees `{x}` as a field).
+            if c == b'\\' && !raw {
+                pos = (pos + 2).min(text.len());
+                continue;
+            }
+            // Doubled braces are literal.
+            if (c == b'{' || c == b'}') && pos + 1 < text.len() && text[pos + 1] == c {
+                pos += 2;
+                continue;
+            }
+            // An interpolation: flush the literal run through the `{`, then
+            // recursively lex the balanced expression.
+            if c == b'{' {
+                pos += 1;
+                tokens.push(Token::new(TokenKind::String, lit_start..pos));
+                let expr_start = pos;
+                let mut depth = 1usize;
+                while pos < text.len() {
+                    match text[pos] {
+                        b'{' => depth += 1,
+                        b'}' if depth == 1 => break,
+                        b'}' => depth -= 1,
+                        b':' if depth == 1 => break,
+                        b'\'' | b'"' => {
+                            // Skip a nested string so its braces don't count.
+                            let q = text[pos];
+                            pos += 1;
+                            while pos < text.len() && text[pos] != q {
+                                if text[pos] == b'\\' {
+                                    pos += 1;
+                                }
+                                pos += 1;
+                            }
+                        }
+                        _ => {}
+                    }
+                    pos += 1;
+                }
+                let inner = if recursion_depth < MAX_INTERP_DEPTH {
+                    self.tokenize_capped(&text[expr_start..pos], recursion_depth + 1)
+                } else {
+                    vec![Token::new(TokenKind::String, 0..pos - expr_start)]
+                };
+                for mut t in inner {
+                    t.span.start += expr_start;
+                    t.span.end += expr_start;
+                    tokens.push(t);
+                }
+                // The format spec (`:` …) and closing `}` rejoin the literal run.
+                lit_start = pos;
+                continue;
+            }
+            pos += 1;
+        }
+        if pos > lit_start {
+            tokens.push(Token::new(TokenKind::String, lit_start..pos));
+        }
+        if !closed {
+            log.report(LexMessage::UnclosedStringLiteral, start..pos);
+        }
+        pos
     }
 }
 
@@ -257,6 +580,54 @@ mod tests {
         assert_eq!(strings.len(), 3);
     }
 
+    #[test]
+    fn test_python_fstring_interpolation() {
+        let lexer = PythonLexer;
+        let text = b"f\"a {x + 1} b\"";
+        let tokens = lexer.tokenize(text);
+
+        // The interpolated expression must surface its own tokens (the `+`
+        // operator and the `1` literal) rather than being swallowed by one
+        // flat String covering the whole f-string.
+        assert!(tokens.iter().any(|t| t.kind == TokenKind::Operator));
+        assert!(tokens.iter().any(|t| t.kind == TokenKind::Number));
+        // The literal segments are still strings.
+        assert!(tokens.iter().any(|t| t.kind == TokenKind::String));
+    }
+
+    #[test]
+    fn test_python_string_prefixes() {
+        let lexer = PythonLexer;
+        // Raw, byte, and combined prefixes each lex as a single String token
+        // spanning the prefix through the closing quote.
+        let text = br#"r"\n" b'x' rb"y""#;
+        let tokens = lexer.tokenize(text);
+        let strings: Vec<_> = tokens.iter().filter(|t| t.kind == TokenKind::String).collect();
+        assert_eq!(strings.len(), 3);
+        // The raw string keeps its backslash inside the one token.
+        assert_eq!(&text[strings[0].span.clone()], br#"r"\n""#);
+    }
+
+    #[test]
+    fn test_python_raw_string_unterminated() {
+        let lexer = PythonLexer;
+        // In a raw string a backslash does not start an escape for the value,
+        // but it still keeps the following quote from closing the literal, so
+        // `r"\"` is unterminated.
+        let (_, diags) = lexer.tokenize_with_diagnostics(br#"r"\""#);
+        assert!(!diags.is_empty());
+    }
+
+    #[test]
+    fn test_python_rfstring_interpolation() {
+        let lexer = PythonLexer;
+        // A raw f-string still interpolates `{expr}`.
+        let text = br#"rf"\d{n}""#;
+        let tokens = lexer.tokenize(text);
+        assert!(tokens.iter().any(|t| t.kind == TokenKind::Identifier));
+        assert!(tokens.iter().any(|t| t.kind == TokenKind::String));
+    }
+
     #[test]
     fn test_python_decorator() {
         let lexer = PythonLexer;
@@ -266,4 +637,39 @@ mod tests {
         let has_decorator = tokens.iter().any(|t| t.kind == TokenKind::Attribute);
         assert!(has_decorator);
     }
+
+    #[test]
+    fn test_python_indentation() {
+        let lexer = PythonLexer;
+        let text = b"def f():\n    x = 1\n    y = 2\nz = 3";
+        let tokens = lexer.tokenize(text);
+
+        let indents = tokens.iter().filter(|t| t.kind == TokenKind::Indent).count();
+        let dedents = tokens.iter().filter(|t| t.kind == TokenKind::Dedent).count();
+        // One Indent into the body, one Dedent back out to `z = 3`.
+        assert_eq!(indents, 1);
+        assert_eq!(dedents, 1);
+    }
+
+    #[test]
+    fn test_python_indent_ignores_blank_and_brackets() {
+        let lexer = PythonLexer;
+        // The blank line and the bracket-continuation line must not emit
+        // Indent/Dedent tokens.
+        let text = b"x = [\n    1,\n]\n\ny = 2";
+        let tokens = lexer.tokenize(text);
+
+        assert!(!tokens.iter().any(|t| matches!(t.kind, TokenKind::Indent | TokenKind::Dedent)));
+    }
+
+    #[test]
+    fn test_python_mixed_tabs_spaces() {
+        let lexer = PythonLexer;
+        // Second body line swaps a tab for spaces relative to the first,
+        // which is ambiguous and must surface as an error.
+        let text = b"if x:\n\tone\n        two";
+        let tokens = lexer.tokenize(text);
+
+        assert!(tokens.iter().any(|t| t.kind == TokenKind::Error));
+    }
 }
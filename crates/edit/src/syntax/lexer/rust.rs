@@ -3,8 +3,57 @@
 
 //! High-performance Rust lexer with full language support.
 
-use crate::syntax::lexer::{Lexer, is_whitespace, is_ident_start, is_ident_continue, is_ascii_digit};
-use crate::syntax::{Token, TokenKind};
+use crate::syntax::lexer::keyword::KeywordAutomaton;
+use crate::syntax::lexer::{Lexer, is_whitespace, is_ident_start, is_ident_continue, is_ascii_digit, c_line_comment_kind, c_block_comment_kind};
+use crate::syntax::{Modifiers, Token, TokenKind};
+
+/// Rust's reserved words as a flat `(word, kind)` table, classified through the
+/// shared Aho-Corasick automaton. Adding a keyword is a one-line edit here
+/// rather than a new arm in the scanning loop, and lookup stays O(word length)
+/// as the set grows; [`KeywordAutomaton::classify`] only matches when the whole
+/// identifier is a keyword, so `forkbomb` scans as an identifier, not `for`.
+static RUST_KEYWORDS: KeywordAutomaton = KeywordAutomaton::new(&[
+    (b"as", TokenKind::KeywordOperator),
+    (b"in", TokenKind::KeywordOperator),
+    (b"is", TokenKind::KeywordOperator),
+    (b"break", TokenKind::KeywordControl),
+    (b"continue", TokenKind::KeywordControl),
+    (b"else", TokenKind::KeywordControl),
+    (b"for", TokenKind::KeywordControl),
+    (b"if", TokenKind::KeywordControl),
+    (b"loop", TokenKind::KeywordControl),
+    (b"match", TokenKind::KeywordControl),
+    (b"return", TokenKind::KeywordControl),
+    (b"while", TokenKind::KeywordControl),
+    (b"fn", TokenKind::KeywordFunction),
+    (b"async", TokenKind::KeywordFunction),
+    (b"await", TokenKind::KeywordFunction),
+    (b"use", TokenKind::KeywordImport),
+    (b"mod", TokenKind::KeywordImport),
+    (b"extern", TokenKind::KeywordImport),
+    (b"crate", TokenKind::KeywordImport),
+    (b"let", TokenKind::KeywordStorage),
+    (b"const", TokenKind::KeywordStorage),
+    (b"static", TokenKind::KeywordStorage),
+    (b"mut", TokenKind::KeywordStorage),
+    (b"struct", TokenKind::KeywordType),
+    (b"enum", TokenKind::KeywordType),
+    (b"union", TokenKind::KeywordType),
+    (b"trait", TokenKind::KeywordType),
+    (b"type", TokenKind::KeywordType),
+    (b"impl", TokenKind::KeywordType),
+    (b"pub", TokenKind::Keyword),
+    (b"priv", TokenKind::Keyword),
+    (b"super", TokenKind::Keyword),
+    (b"self", TokenKind::Keyword),
+    (b"Self", TokenKind::Keyword),
+    (b"where", TokenKind::Keyword),
+    (b"unsafe", TokenKind::Keyword),
+    (b"ref", TokenKind::Keyword),
+    (b"move", TokenKind::Keyword),
+    (b"true", TokenKind::Boolean),
+    (b"false", TokenKind::Boolean),
+]);
 
 pub struct RustLexer;
 
@@ -32,7 +81,7 @@ impl Lexer for RustLexer {
                     while pos < text.len() && text[pos] != b'\n' {
                         pos += 1;
                     }
-                    tokens.push(Token::new(TokenKind::Comment, start..pos));
+                    tokens.push(Token::new(c_line_comment_kind(&text[start..pos]), start..pos));
                 }
 
                 // Block comment
@@ -50,7 +99,7 @@ impl Lexer for RustLexer {
                             pos += 1;
                         }
                     }
-                    tokens.push(Token::new(TokenKind::Comment, start..pos));
+                    tokens.push(Token::new(c_block_comment_kind(&text[start..pos]), start..pos));
                 }
 
                 // String literal
@@ -194,19 +243,17 @@ impl Lexer for RustLexer {
                     }
                     
                     let word = &text[start..pos];
-                    let kind = match word {
-                        b"as" | b"in" | b"is" => TokenKind::KeywordOperator,
-                        b"break" | b"continue" | b"else" | b"for" | b"if" | b"loop" | b"match" | b"return" | b"while" => TokenKind::KeywordControl,
-                        b"fn" | b"async" | b"await" => TokenKind::KeywordFunction,
-                        b"use" | b"mod" | b"extern" | b"crate" => TokenKind::KeywordImport,
-                        b"let" | b"const" | b"static" | b"mut" => TokenKind::KeywordStorage,
-                        b"struct" | b"enum" | b"union" | b"trait" | b"type" | b"impl" => TokenKind::KeywordType,
-                        b"pub" | b"priv" | b"super" | b"self" | b"Self" | b"where" | b"unsafe" | b"ref" | b"move" => TokenKind::Keyword,
-                        b"true" | b"false" => TokenKind::Boolean,
-                        _ => TokenKind::Identifier,
+                    let kind = RUST_KEYWORDS.classify(word);
+                    // Control-flow keywords get the `CONTROL` modifier so a
+                    // theme can render them distinctly from e.g. `fn`/`impl`
+                    // without needing a separate `TokenKind` for each.
+                    let modifiers = if kind == TokenKind::KeywordControl {
+                        Modifiers::CONTROL
+                    } else {
+                        Modifiers::NONE
                     };
-                    
-                    tokens.push(Token::new(kind, start..pos));
+
+                    tokens.push(Token::with_modifiers(kind, start..pos, modifiers));
                 }
 
                 // Operators and punctuation
@@ -258,6 +305,15 @@ mod tests {
         assert!(has_let);
     }
 
+    #[test]
+    fn test_rust_keyword_prefix_is_identifier() {
+        let lexer = RustLexer;
+        let tokens = lexer.tokenize(b"forkbomb");
+        // `forkbomb` must not be classified as the `for` keyword.
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].kind, TokenKind::Identifier);
+    }
+
     #[test]
     fn test_rust_lifetime() {
         let lexer = RustLexer;
@@ -3,35 +3,224 @@
 
 //! High-performance Shell/Bash lexer with full language support.
 
-use crate::syntax::lexer::{Lexer, is_whitespace, is_ident_start, is_ident_continue, is_ascii_digit};
+use crate::syntax::lexer::diagnostic::{LexMessage, Logger};
+use crate::syntax::lexer::interp::{InterpConfig, tokenize_interpolated};
+use crate::syntax::lexer::keyword::KeywordTable;
+use crate::syntax::lexer::{Diagnostic, Lexer, LexerState, is_ident_start, is_ident_continue, is_ascii_digit, hash_line_comment_kind, memchr, memchr2, first_non_whitespace};
 use crate::syntax::{Token, TokenKind};
 
 pub struct ShellLexer;
 
+/// Bash reserved words, boolean literals, and common builtins/commands,
+/// classified into the kinds the theme colors. Editing this list is the only
+/// thing needed to teach the lexer a new word; lookup stays O(word length)
+/// through the shared keyword hash table.
+static KEYWORDS: KeywordTable = KeywordTable::new(&[
+    // Bash keywords
+    (b"if", TokenKind::Keyword), (b"then", TokenKind::Keyword),
+    (b"else", TokenKind::Keyword), (b"elif", TokenKind::Keyword),
+    (b"fi", TokenKind::Keyword), (b"case", TokenKind::Keyword),
+    (b"esac", TokenKind::Keyword), (b"for", TokenKind::Keyword),
+    (b"while", TokenKind::Keyword), (b"until", TokenKind::Keyword),
+    (b"do", TokenKind::Keyword), (b"done", TokenKind::Keyword),
+    (b"in", TokenKind::Keyword), (b"select", TokenKind::Keyword),
+    (b"time", TokenKind::Keyword), (b"function", TokenKind::Keyword),
+    (b"declare", TokenKind::Keyword), (b"typeset", TokenKind::Keyword),
+    (b"local", TokenKind::Keyword), (b"readonly", TokenKind::Keyword),
+    (b"export", TokenKind::Keyword), (b"unset", TokenKind::Keyword),
+    (b"return", TokenKind::Keyword), (b"break", TokenKind::Keyword),
+    (b"continue", TokenKind::Keyword), (b"exit", TokenKind::Keyword),
+    (b"shift", TokenKind::Keyword), (b"eval", TokenKind::Keyword),
+    (b"exec", TokenKind::Keyword), (b"source", TokenKind::Keyword),
+    (b"alias", TokenKind::Keyword), (b"unalias", TokenKind::Keyword),
+    (b"test", TokenKind::Keyword),
+    // Boolean values
+    (b"true", TokenKind::Boolean), (b"false", TokenKind::Boolean),
+    // Common commands (builtins)
+    (b"echo", TokenKind::FunctionName), (b"printf", TokenKind::FunctionName),
+    (b"read", TokenKind::FunctionName), (b"cd", TokenKind::FunctionName),
+    (b"pwd", TokenKind::FunctionName), (b"pushd", TokenKind::FunctionName),
+    (b"popd", TokenKind::FunctionName), (b"ls", TokenKind::FunctionName),
+    (b"cat", TokenKind::FunctionName), (b"grep", TokenKind::FunctionName),
+    (b"sed", TokenKind::FunctionName), (b"awk", TokenKind::FunctionName),
+    (b"find", TokenKind::FunctionName), (b"sort", TokenKind::FunctionName),
+    (b"uniq", TokenKind::FunctionName), (b"head", TokenKind::FunctionName),
+    (b"tail", TokenKind::FunctionName), (b"cut", TokenKind::FunctionName),
+    (b"paste", TokenKind::FunctionName), (b"tr", TokenKind::FunctionName),
+    (b"wc", TokenKind::FunctionName), (b"chmod", TokenKind::FunctionName),
+    (b"chown", TokenKind::FunctionName), (b"chgrp", TokenKind::FunctionName),
+    (b"mkdir", TokenKind::FunctionName), (b"rm", TokenKind::FunctionName),
+    (b"cp", TokenKind::FunctionName), (b"mv", TokenKind::FunctionName),
+    (b"touch", TokenKind::FunctionName), (b"ln", TokenKind::FunctionName),
+    (b"dirname", TokenKind::FunctionName), (b"basename", TokenKind::FunctionName),
+    (b"tar", TokenKind::FunctionName), (b"gzip", TokenKind::FunctionName),
+    (b"gunzip", TokenKind::FunctionName), (b"zip", TokenKind::FunctionName),
+    (b"unzip", TokenKind::FunctionName), (b"ps", TokenKind::FunctionName),
+    (b"top", TokenKind::FunctionName), (b"kill", TokenKind::FunctionName),
+    (b"killall", TokenKind::FunctionName), (b"jobs", TokenKind::FunctionName),
+    (b"bg", TokenKind::FunctionName), (b"fg", TokenKind::FunctionName),
+    (b"man", TokenKind::FunctionName), (b"which", TokenKind::FunctionName),
+    (b"whereis", TokenKind::FunctionName), (b"type", TokenKind::FunctionName),
+    (b"command", TokenKind::FunctionName), (b"set", TokenKind::FunctionName),
+    (b"shopt", TokenKind::FunctionName), (b"let", TokenKind::FunctionName),
+    (b"wait", TokenKind::FunctionName), (b"sleep", TokenKind::FunctionName),
+    (b"trap", TokenKind::FunctionName),
+]);
+
 impl Lexer for ShellLexer {
     fn tokenize(&self, text: &[u8]) -> Vec<Token> {
+        // An empty logger allocates nothing until something is reported, so the
+        // diagnostic-free path pays no extra cost.
+        self.run(text, LexerState::Normal, &mut Logger::new(), 0).0
+    }
+
+    fn tokenize_with_diagnostics(&self, text: &[u8]) -> (Vec<Token>, Vec<Diagnostic>) {
+        let mut log = Logger::new();
+        let (tokens, _) = self.run(text, LexerState::Normal, &mut log, 0);
+        (tokens, log.into_diagnostics())
+    }
+
+    fn tokenize_line(&self, line: &[u8], entry: LexerState) -> (Vec<Token>, LexerState) {
+        self.run(line, entry, &mut Logger::new(), 0)
+    }
+
+    fn tokenize_capped(&self, text: &[u8], depth: usize) -> Vec<Token> {
+        self.run(text, LexerState::Normal, &mut Logger::new(), depth).0
+    }
+}
+
+impl ShellLexer {
+    /// Tokenize `text` starting in `entry` state, recording a [`Diagnostic`]
+    /// into `log` at the opener of any quoted string that runs to end-of-input
+    /// unclosed. Returns the tokens and the [`LexerState`] the scan ended in:
+    /// `Normal` unless `text` ends inside a heredoc body, in which case the next
+    /// line resumes from [`LexerState::InHeredoc`].
+    ///
+    /// `depth` is how many enclosing `"..."` interpolation holes this call is
+    /// already nested inside — see [`tokenize_interpolated`]'s own `depth`
+    /// parameter for how it bounds the recursion.
+    fn run(&self, text: &[u8], entry: LexerState, log: &mut Logger, depth: usize) -> (Vec<Token>, LexerState) {
+        // Resume a heredoc body carried over from the previous line. A line that
+        // is exactly the delimiter (after optional tab-stripping) ends the body
+        // and is lexed as ordinary code; any other line is part of the body.
+        if let LexerState::InHeredoc { delim, len, strip } = entry {
+            let terminator = &delim[..len as usize];
+            let line_end = memchr(b'\n', text).unwrap_or(text.len());
+            let mut content = 0;
+            if strip {
+                while content < line_end && text[content] == b'\t' {
+                    content += 1;
+                }
+            }
+            if &text[content..line_end] != terminator {
+                return (vec![Token::new(TokenKind::String, 0..text.len())], entry);
+            }
+            // Fall through: this line terminates the heredoc and is plain code.
+        }
+
         let mut tokens = Vec::with_capacity(text.len() / 8);
         let mut pos = 0;
+        let mut exit = LexerState::Normal;
+        // Set once a `<<`/`<<-` operator is seen; the carried `bool` is the
+        // `<<-` leading-tab-stripping flag. The next non-whitespace token on
+        // the line is the heredoc delimiter.
+        let mut expect_delim: Option<bool> = None;
+        // A delimiter captured and waiting for the current logical line to end;
+        // `(delimiter, strip_leading_tabs)`. When the line's terminating
+        // newline is reached the body is swallowed as one `String` token.
+        let mut pending: Option<(Vec<u8>, bool)> = None;
 
         while pos < text.len() {
             let start = pos;
             let b = text[pos];
 
+            // Capture the heredoc delimiter word that follows `<<`/`<<-`. It may
+            // be quoted (`'EOF'`/`"EOF"`), which in a real shell disables
+            // expansion inside the body; since the body is emitted as one opaque
+            // `String` either way, we only need the delimiter text here.
+            if let Some(strip) = expect_delim {
+                if b != b' ' && b != b'\t' {
+                    expect_delim = None;
+                    match b {
+                        b'\'' | b'"' => {
+                            pos += 1;
+                            let dstart = pos;
+                            pos += memchr(b, &text[pos..]).unwrap_or(text.len() - pos);
+                            let delim = text[dstart..pos].to_vec();
+                            if pos < text.len() {
+                                pos += 1;
+                            }
+                            pending = Some((delim, strip));
+                            tokens.push(Token::new(TokenKind::String, start..pos));
+                            continue;
+                        }
+                        _ if is_ident_start(b) || b == b'_' => {
+                            while pos < text.len()
+                                && (is_ident_continue(text[pos]) || text[pos] == b'_' || text[pos] == b'-')
+                            {
+                                pos += 1;
+                            }
+                            pending = Some((text[start..pos].to_vec(), strip));
+                            tokens.push(Token::new(TokenKind::Identifier, start..pos));
+                            continue;
+                        }
+                        // Not a valid delimiter (e.g. `<< |`): abandon and lex
+                        // the byte normally below.
+                        _ => {}
+                    }
+                }
+            }
+
             match b {
                 // Whitespace
                 b' ' | b'\t' | b'\n' | b'\r' => {
-                    while pos < text.len() && is_whitespace(text[pos]) {
-                        pos += 1;
+                    // When a heredoc delimiter is pending, stop the whitespace
+                    // run at the line-terminating newline and swallow the body.
+                    let run_end = text[pos..].iter().position(|&c| !matches!(c, b' ' | b'\t' | b'\r'));
+                    if let (Some(nl), true) =
+                        (run_end.filter(|&n| text[pos + n] == b'\n'), pending.is_some())
+                    {
+                        pos += nl + 1;
+                        tokens.push(Token::new(TokenKind::Whitespace, start..pos));
+                        let (delim, strip) = pending.take().unwrap();
+                        let body_start = pos;
+                        let mut found = false;
+                        while pos < text.len() {
+                            let line_end =
+                                pos + memchr(b'\n', &text[pos..]).unwrap_or(text.len() - pos);
+                            // `<<-` strips leading tabs before matching the
+                            // delimiter (but not spaces).
+                            let mut content = pos;
+                            if strip {
+                                while content < line_end && text[content] == b'\t' {
+                                    content += 1;
+                                }
+                            }
+                            if &text[content..line_end] == delim.as_slice() {
+                                found = true;
+                                break;
+                            }
+                            pos = (line_end + 1).min(text.len());
+                        }
+                        if pos > body_start {
+                            tokens.push(Token::new(TokenKind::String, body_start..pos));
+                        }
+                        // If the body ran to end-of-input without its terminator,
+                        // suspend so the next line resumes inside the heredoc.
+                        if !found {
+                            exit = pack_heredoc_state(&delim, strip);
+                        }
+                        // The closing delimiter line is tokenized as ordinary code.
+                        continue;
                     }
+                    pos += first_non_whitespace(&text[pos..]).unwrap_or(text.len() - pos);
                     tokens.push(Token::new(TokenKind::Whitespace, start..pos));
                 }
 
                 // Comment
                 b'#' => {
-                    while pos < text.len() && text[pos] != b'\n' {
-                        pos += 1;
-                    }
-                    tokens.push(Token::new(TokenKind::Comment, start..pos));
+                    pos += memchr(b'\n', &text[pos..]).unwrap_or(text.len() - pos);
+                    tokens.push(Token::new(hash_line_comment_kind(&text[start..pos]), start..pos));
                 }
 
                 // Variable expansion ($VAR, ${VAR}, $(...), $((...)))
@@ -83,47 +272,48 @@ impl Lexer for ShellLexer {
                 // Single-quoted string (no expansion)
                 b'\'' => {
                     pos += 1;
-                    while pos < text.len() && text[pos] != b'\'' {
-                        pos += 1;
-                    }
-                    if pos < text.len() {
-                        pos += 1;
+                    // Single quotes take no escapes, so jump straight to the close.
+                    match memchr(b'\'', &text[pos..]) {
+                        Some(off) => pos += off + 1,
+                        None => {
+                            pos = text.len();
+                            log.report(LexMessage::UnclosedStringLiteral, start..pos);
+                        }
                     }
                     tokens.push(Token::new(TokenKind::String, start..pos));
                 }
 
-                // Double-quoted string (with expansion)
+                // Double-quoted string (with `$var`/`${x}` expansion)
                 b'"' => {
-                    pos += 1;
-                    let mut escaped = false;
-                    while pos < text.len() {
-                        if escaped {
-                            escaped = false;
-                        } else if text[pos] == b'\\' {
-                            escaped = true;
-                        } else if text[pos] == b'"' {
-                            pos += 1;
-                            break;
-                        }
-                        pos += 1;
+                    let cfg = InterpConfig { quote: b'"', escape: true, dollar: true, format_specifier: false };
+                    pos = tokenize_interpolated(self, text, start, &cfg, &mut tokens, depth);
+                    // An unterminated expandable string runs to EOF without a
+                    // closing quote; the literal prefix already covers to `pos`.
+                    let closed = pos > start + 1
+                        && text[pos - 1] == b'"'
+                        && text[start + 1..pos - 1].iter().rev().take_while(|&&c| c == b'\\').count() % 2 == 0;
+                    if pos == text.len() && !closed {
+                        log.report(LexMessage::UnclosedStringLiteral, start..pos);
                     }
-                    tokens.push(Token::new(TokenKind::String, start..pos));
                 }
 
                 // Backtick command substitution `...`
                 b'`' => {
                     pos += 1;
-                    let mut escaped = false;
-                    while pos < text.len() {
-                        if escaped {
-                            escaped = false;
-                        } else if text[pos] == b'\\' {
-                            escaped = true;
-                        } else if text[pos] == b'`' {
+                    let mut closed = false;
+                    while let Some(off) = memchr2(b'`', b'\\', &text[pos..]) {
+                        pos += off;
+                        if text[pos] == b'`' {
                             pos += 1;
+                            closed = true;
                             break;
                         }
-                        pos += 1;
+                        // Backslash escape: skip it and the escaped byte.
+                        pos = (pos + 2).min(text.len());
+                    }
+                    if !closed {
+                        pos = text.len();
+                        log.report(LexMessage::UnclosedStringLiteral, start..pos);
                     }
                     tokens.push(Token::new(TokenKind::String, start..pos));
                 }
@@ -149,6 +339,16 @@ impl Lexer for ShellLexer {
                             _ => {}
                         }
                     }
+                    // A `<<` that is not a `<<<` here-string opens a heredoc; the
+                    // trailing `-` of the `<<-` form (which strips leading tabs
+                    // from the body and delimiter) belongs to the operator.
+                    if &text[start..pos] == b"<<" && !(pos < text.len() && text[pos] == b'<') {
+                        let strip = pos < text.len() && text[pos] == b'-';
+                        if strip {
+                            pos += 1;
+                        }
+                        expect_delim = Some(strip);
+                    }
                     tokens.push(Token::new(TokenKind::Operator, start..pos));
                 }
 
@@ -176,34 +376,7 @@ impl Lexer for ShellLexer {
                         pos += 1;
                     }
                     let word = &text[start..pos];
-                    let kind = match word {
-                        // Bash keywords
-                        b"if" | b"then" | b"else" | b"elif" | b"fi" |
-                        b"case" | b"esac" | b"for" | b"while" | b"until" | b"do" | b"done" |
-                        b"in" | b"select" | b"time" | b"function" |
-                        b"declare" | b"typeset" | b"local" | b"readonly" | b"export" |
-                        b"unset" | b"return" | b"break" | b"continue" | b"exit" |
-                        b"shift" | b"eval" | b"exec" | b"source" | b"alias" | b"unalias" => TokenKind::Keyword,
-                        
-                        // Conditional expressions
-                        b"test" => TokenKind::Keyword,
-                        
-                        // Boolean values
-                        b"true" | b"false" => TokenKind::Boolean,
-                        
-                        // Common commands (builtins)
-                        b"echo" | b"printf" | b"read" | b"cd" | b"pwd" | b"pushd" | b"popd" |
-                        b"ls" | b"cat" | b"grep" | b"sed" | b"awk" | b"find" | b"sort" | b"uniq" |
-                        b"head" | b"tail" | b"cut" | b"paste" | b"tr" | b"wc" |
-                        b"chmod" | b"chown" | b"chgrp" | b"mkdir" | b"rm" | b"cp" | b"mv" |
-                        b"touch" | b"ln" | b"dirname" | b"basename" |
-                        b"tar" | b"gzip" | b"gunzip" | b"zip" | b"unzip" |
-                        b"ps" | b"top" | b"kill" | b"killall" | b"jobs" | b"bg" | b"fg" |
-                        b"man" | b"which" | b"whereis" | b"type" | b"command" |
-                        b"set" | b"shopt" | b"let" | b"wait" | b"sleep" | b"trap" => TokenKind::FunctionName,
-                        
-                        _ => TokenKind::Identifier,
-                    };
+                    let kind = KEYWORDS.lookup(word).unwrap_or(TokenKind::Identifier);
                     tokens.push(Token::new(kind, start..pos));
                 }
 
@@ -215,6 +388,15 @@ impl Lexer for ShellLexer {
             }
         }
 
-        tokens
+        (tokens, exit)
     }
 }
+
+/// Pack a heredoc `delimiter` into the fixed buffer carried by
+/// [`LexerState::InHeredoc`], truncating delimiters longer than the buffer.
+fn pack_heredoc_state(delimiter: &[u8], strip: bool) -> LexerState {
+    let mut delim = [0u8; 32];
+    let len = delimiter.len().min(delim.len());
+    delim[..len].copy_from_slice(&delimiter[..len]);
+    LexerState::InHeredoc { delim, len: len as u8, strip }
+}
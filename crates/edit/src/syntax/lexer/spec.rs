@@ -0,0 +1,368 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Data-driven lexer specifications.
+//!
+//! A hand-written `Lexer::tokenize` like [`JavaLexer`](super::java::JavaLexer)
+//! spends hundreds of lines matching bytes that look the same for every curly-
+//! brace language: the same whitespace run, the same `//` / `/* */` comments,
+//! the same quote-and-escape string scan. A [`LanguageSpec`] captures those
+//! differences as data and [`SpecLexer`] runs a single position loop against
+//! it, so a new language is a table rather than a new file of control flow.
+//!
+//! The loop mirrors the order a hand lexer uses — comments before operators so
+//! `/` disambiguates, triple quotes before single — and classifies
+//! identifiers through the shared [`KeywordAutomaton`]. The [`Lexer`] trait is
+//! untouched, so hand-written lexers keep working alongside spec-driven ones.
+
+use crate::syntax::lexer::keyword::KeywordAutomaton;
+use crate::syntax::lexer::{Lexer, is_ascii_digit, is_ident_continue, is_ident_start, is_whitespace};
+use crate::syntax::{Token, TokenKind};
+
+/// A block-comment delimiter pair.
+pub(crate) struct BlockComment {
+    /// The opening delimiter, e.g. `b"/*"`.
+    pub open: &'static [u8],
+    /// The closing delimiter, e.g. `b"*/"`.
+    pub close: &'static [u8],
+    /// Whether the comment nests (a second `open` requires a second `close`).
+    pub nests: bool,
+}
+
+/// A string- or character-literal delimiter rule.
+pub(crate) struct StringRule {
+    /// The byte that opens and closes the literal.
+    pub quote: u8,
+    /// The escape byte (`\\` for most languages), or `0` for none.
+    pub escape: u8,
+    /// The token kind the literal produces.
+    pub kind: TokenKind,
+    /// Whether a run of three `quote` bytes opens a triple-quoted variant
+    /// (Java text blocks, Python triple strings) closed by the matching triple.
+    pub triple: bool,
+}
+
+/// How numeric literals are spelled.
+pub(crate) struct NumberRules {
+    /// Whether `0x`/`0X` introduces a hexadecimal literal.
+    pub hex: bool,
+    /// Whether `0b`/`0B` introduces a binary literal.
+    pub binary: bool,
+    /// Whether a leading `0` followed by an octal digit introduces an octal
+    /// literal.
+    pub octal: bool,
+    /// The digit-group separator (`_`), or `0` for none.
+    pub separator: u8,
+    /// Exponent-marker bytes, e.g. `b"eE"`.
+    pub exponent: &'static [u8],
+    /// Trailing type-suffix bytes, e.g. `b"fFdDlL"`.
+    pub suffix: &'static [u8],
+}
+
+/// A declarative description of one language's lexical grammar.
+pub(crate) struct LanguageSpec {
+    /// Keyword/type/literal classification for identifier-shaped runs.
+    pub keywords: KeywordAutomaton,
+    /// Line-comment prefixes, e.g. `b"//"`.
+    pub line_comments: &'static [&'static [u8]],
+    /// Block-comment delimiter pairs.
+    pub block_comments: &'static [BlockComment],
+    /// String and character literal rules, tried in order.
+    pub strings: &'static [StringRule],
+    /// How numbers are spelled.
+    pub numbers: NumberRules,
+    /// Annotation/attribute sigil (`@`) producing [`TokenKind::Attribute`], or
+    /// `0` for none.
+    pub annotation: u8,
+    /// Operator and punctuation spellings, matched greedily longest-first and
+    /// all emitted as [`TokenKind::Operator`].
+    pub operators: &'static [&'static [u8]],
+}
+
+/// A [`Lexer`] that interprets a [`LanguageSpec`].
+pub(crate) struct SpecLexer {
+    spec: &'static LanguageSpec,
+}
+
+impl SpecLexer {
+    /// Wrap a static spec.
+    pub(crate) const fn new(spec: &'static LanguageSpec) -> Self {
+        Self { spec }
+    }
+}
+
+impl Lexer for SpecLexer {
+    fn tokenize(&self, text: &[u8]) -> Vec<Token> {
+        let spec = self.spec;
+        let mut tokens = Vec::with_capacity(text.len() / 8);
+        let mut pos = 0;
+
+        while pos < text.len() {
+            let start = pos;
+            let b = text[pos];
+
+            // Whitespace
+            if is_whitespace(b) {
+                while pos < text.len() && is_whitespace(text[pos]) {
+                    pos += 1;
+                }
+                tokens.push(Token::new(TokenKind::Whitespace, start..pos));
+                continue;
+            }
+
+            // Line comment
+            if let Some(prefix) = spec.line_comments.iter().find(|p| text[pos..].starts_with(p)) {
+                pos += prefix.len();
+                while pos < text.len() && text[pos] != b'\n' {
+                    pos += 1;
+                }
+                tokens.push(Token::new(TokenKind::Comment, start..pos));
+                continue;
+            }
+
+            // Block comment
+            if let Some(block) = spec.block_comments.iter().find(|c| text[pos..].starts_with(c.open)) {
+                pos = scan_block_comment(text, pos, block);
+                tokens.push(Token::new(TokenKind::Comment, start..pos));
+                continue;
+            }
+
+            // Annotation / attribute
+            if spec.annotation != 0 && b == spec.annotation {
+                pos += 1;
+                while pos < text.len() && is_ident_continue(text[pos]) {
+                    pos += 1;
+                }
+                tokens.push(Token::new(TokenKind::Attribute, start..pos));
+                continue;
+            }
+
+            // String / character literals
+            if let Some(rule) = spec.strings.iter().find(|r| r.quote == b) {
+                pos = scan_string(text, pos, rule);
+                tokens.push(Token::new(rule.kind, start..pos));
+                continue;
+            }
+
+            // Numbers
+            if is_ascii_digit(b) {
+                pos = scan_number(text, pos, &spec.numbers);
+                tokens.push(Token::new(TokenKind::Number, start..pos));
+                continue;
+            }
+
+            // Identifier or keyword
+            if is_ident_start(b) {
+                while pos < text.len() && is_ident_continue(text[pos]) {
+                    pos += 1;
+                }
+                let kind = spec.keywords.classify(&text[start..pos]);
+                tokens.push(Token::new(kind, start..pos));
+                continue;
+            }
+
+            // Operators and punctuation, greedy longest-match
+            if let Some(len) = match_operator(spec.operators, &text[pos..]) {
+                pos += len;
+                tokens.push(Token::new(TokenKind::Operator, start..pos));
+                continue;
+            }
+
+            // Unknown character
+            pos += 1;
+            tokens.push(Token::new(TokenKind::Error, start..pos));
+        }
+
+        tokens
+    }
+}
+
+/// Consume a block comment starting at `open`, honoring nesting, and return the
+/// position just past its close (or end of text if unterminated).
+fn scan_block_comment(text: &[u8], mut pos: usize, block: &BlockComment) -> usize {
+    pos += block.open.len();
+    let mut depth = 1usize;
+    while pos < text.len() {
+        if block.nests && text[pos..].starts_with(block.open) {
+            depth += 1;
+            pos += block.open.len();
+        } else if text[pos..].starts_with(block.close) {
+            pos += block.close.len();
+            depth -= 1;
+            if depth == 0 {
+                break;
+            }
+        } else {
+            pos += 1;
+        }
+    }
+    pos
+}
+
+/// Consume a string or character literal starting at its opening quote and
+/// return the position just past its close. An unterminated triple-quoted
+/// literal runs to the end of text; any other unterminated literal stops at
+/// the end of its line.
+fn scan_string(text: &[u8], mut pos: usize, rule: &StringRule) -> usize {
+    let quote = rule.quote;
+    // Triple-quoted variant.
+    if rule.triple && pos + 2 < text.len() && text[pos + 1] == quote && text[pos + 2] == quote {
+        pos += 3;
+        while pos + 2 < text.len() {
+            if text[pos] == quote && text[pos + 1] == quote && text[pos + 2] == quote {
+                return pos + 3;
+            }
+            pos += 1;
+        }
+        return text.len();
+    }
+
+    pos += 1;
+    let mut escaped = false;
+    while pos < text.len() {
+        if escaped {
+            escaped = false;
+        } else if rule.escape != 0 && text[pos] == rule.escape {
+            escaped = true;
+        } else if text[pos] == quote {
+            return pos + 1;
+        } else if text[pos] == b'\n' {
+            break;
+        }
+        pos += 1;
+    }
+    pos
+}
+
+/// Consume a numeric literal starting at a digit and return the position just
+/// past it.
+fn scan_number(text: &[u8], mut pos: usize, rules: &NumberRules) -> usize {
+    let sep = rules.separator;
+    let is_sep = |b: u8| sep != 0 && b == sep;
+    let lead = text[pos];
+    let next = text.get(pos + 1).copied();
+
+    if lead == b'0' && rules.hex && matches!(next, Some(b'x' | b'X')) {
+        pos += 2;
+        while pos < text.len()
+            && (matches!(text[pos], b'0'..=b'9' | b'a'..=b'f' | b'A'..=b'F') || is_sep(text[pos]))
+        {
+            pos += 1;
+        }
+    } else if lead == b'0' && rules.binary && matches!(next, Some(b'b' | b'B')) {
+        pos += 2;
+        while pos < text.len() && (matches!(text[pos], b'0' | b'1') || is_sep(text[pos])) {
+            pos += 1;
+        }
+    } else if lead == b'0' && rules.octal && matches!(next, Some(b'0'..=b'7')) {
+        pos += 1;
+        while pos < text.len() && (matches!(text[pos], b'0'..=b'7') || is_sep(text[pos])) {
+            pos += 1;
+        }
+    } else {
+        while pos < text.len() && (is_ascii_digit(text[pos]) || is_sep(text[pos])) {
+            pos += 1;
+        }
+        // Fraction
+        if pos < text.len() && text[pos] == b'.' && pos + 1 < text.len() && is_ascii_digit(text[pos + 1]) {
+            pos += 1;
+            while pos < text.len() && (is_ascii_digit(text[pos]) || is_sep(text[pos])) {
+                pos += 1;
+            }
+        }
+        // Exponent
+        if pos < text.len() && rules.exponent.contains(&text[pos]) {
+            pos += 1;
+            if pos < text.len() && matches!(text[pos], b'+' | b'-') {
+                pos += 1;
+            }
+            while pos < text.len() && (is_ascii_digit(text[pos]) || is_sep(text[pos])) {
+                pos += 1;
+            }
+        }
+    }
+    // Type suffix
+    if pos < text.len() && rules.suffix.contains(&text[pos]) {
+        pos += 1;
+    }
+    pos
+}
+
+/// Return the length of the longest operator in `ops` that is a prefix of
+/// `rest`, or `None` if none match.
+fn match_operator(ops: &[&[u8]], rest: &[u8]) -> Option<usize> {
+    ops.iter()
+        .filter(|op| rest.starts_with(op))
+        .map(|op| op.len())
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syntax::lexer::keyword::KeywordAutomaton;
+
+    static TINY: LanguageSpec = LanguageSpec {
+        keywords: KeywordAutomaton::new(&[
+            (b"if", TokenKind::Keyword),
+            (b"int", TokenKind::TypeName),
+        ]),
+        line_comments: &[b"//"],
+        block_comments: &[BlockComment { open: b"/*", close: b"*/", nests: false }],
+        strings: &[
+            StringRule { quote: b'"', escape: b'\\', kind: TokenKind::String, triple: true },
+        ],
+        numbers: NumberRules {
+            hex: true,
+            binary: false,
+            octal: false,
+            separator: b'_',
+            exponent: b"eE",
+            suffix: b"fF",
+        },
+        annotation: b'@',
+        operators: &[b">>=", b">>", b">", b"="],
+    };
+
+    fn kinds(text: &[u8]) -> Vec<TokenKind> {
+        SpecLexer::new(&TINY)
+            .tokenize(text)
+            .into_iter()
+            .filter(|t| t.kind != TokenKind::Whitespace)
+            .map(|t| t.kind)
+            .collect()
+    }
+
+    #[test]
+    fn test_spec_keywords_and_types() {
+        assert_eq!(kinds(b"if int foo"), [TokenKind::Keyword, TokenKind::TypeName, TokenKind::Identifier]);
+    }
+
+    #[test]
+    fn test_spec_greedy_operators() {
+        assert_eq!(kinds(b">>= >> >"), [TokenKind::Operator, TokenKind::Operator, TokenKind::Operator]);
+    }
+
+    #[test]
+    fn test_spec_strings_and_comments() {
+        let toks = SpecLexer::new(&TINY).tokenize(b"/* c */ \"s\" @Ann 0xFF_0 1e3f");
+        let kinds: Vec<_> = toks.iter().map(|t| t.kind).filter(|k| *k != TokenKind::Whitespace).collect();
+        assert_eq!(
+            kinds,
+            [
+                TokenKind::Comment,
+                TokenKind::String,
+                TokenKind::Attribute,
+                TokenKind::Number,
+                TokenKind::Number,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_spec_triple_quote() {
+        let toks = SpecLexer::new(&TINY).tokenize(b"\"\"\"a \"b\" c\"\"\"");
+        assert_eq!(toks.len(), 1);
+        assert_eq!(toks[0].kind, TokenKind::String);
+    }
+}
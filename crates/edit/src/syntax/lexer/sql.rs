@@ -3,15 +3,281 @@
 
 //! High-performance SQL lexer with full language support.
 
-use crate::syntax::lexer::{Lexer, is_whitespace, is_ident_start, is_ident_continue, is_ascii_digit};
+use crate::syntax::lexer::keyword::KeywordTable;
+use crate::syntax::lexer::{Diagnostic, LexMessage, Logger};
+use crate::syntax::lexer::{Lexer, LexerState, is_whitespace, is_ident_start, is_ident_continue, is_ascii_digit};
 use crate::syntax::{Token, TokenKind};
 
-pub struct SqlLexer;
+/// The SQL dialect a [`SqlLexer`] targets. Dialects differ in which quoting
+/// forms, comment leaders, and variable sigils are valid, so a lexer that knows
+/// the server can avoid mis-highlighting a character that means something else
+/// elsewhere.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SqlDialect {
+    /// Standard SQL with no vendor extensions.
+    #[default]
+    Ansi,
+    /// MySQL/MariaDB: backtick identifiers and `#` line comments.
+    MySql,
+    /// PostgreSQL: `$tag$…$tag$` dollar quoting and `E'…'` escape strings.
+    Postgres,
+    /// Microsoft T-SQL: `[bracket]` identifiers and `@`/`@@` variables.
+    TSql,
+    /// SQLite.
+    Sqlite,
+}
+
+impl SqlDialect {
+    /// MySQL-style `` `backtick` `` quoted identifiers.
+    fn backtick_ident(self) -> bool {
+        matches!(self, SqlDialect::MySql)
+    }
+
+    /// SQL Server `[bracket]` quoted identifiers.
+    fn bracket_ident(self) -> bool {
+        matches!(self, SqlDialect::TSql)
+    }
+
+    /// `#` line comments (MySQL).
+    fn hash_comment(self) -> bool {
+        matches!(self, SqlDialect::MySql)
+    }
+
+    /// `@name`/`@@name` variables (T-SQL).
+    fn at_variable(self) -> bool {
+        matches!(self, SqlDialect::TSql)
+    }
+
+    /// PostgreSQL `$tag$…$tag$` dollar quoting and `E'…'` escape strings.
+    fn dollar_quote(self) -> bool {
+        matches!(self, SqlDialect::Postgres)
+    }
+}
+
+/// A SQL lexer parameterized by [`SqlDialect`].
+///
+/// [`SqlLexer::new`] is permissive — it recognizes every dialect's quoting and
+/// comment forms, preserving the behavior of the original dialect-agnostic
+/// lexer — while [`SqlLexer::with_dialect`] restricts recognition to a single
+/// server's grammar.
+pub struct SqlLexer {
+    dialect: SqlDialect,
+    /// When set, every dialect's quirks are accepted regardless of `dialect`.
+    permissive: bool,
+}
+
+impl Default for SqlLexer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SqlLexer {
+    /// Create a permissive lexer that accepts every dialect's syntax.
+    pub fn new() -> Self {
+        Self { dialect: SqlDialect::default(), permissive: true }
+    }
+
+    /// Create a lexer restricted to a single `dialect`.
+    pub fn with_dialect(dialect: SqlDialect) -> Self {
+        Self { dialect, permissive: false }
+    }
+
+    fn backtick_ident(&self) -> bool {
+        self.permissive || self.dialect.backtick_ident()
+    }
+
+    fn bracket_ident(&self) -> bool {
+        self.permissive || self.dialect.bracket_ident()
+    }
+
+    fn hash_comment(&self) -> bool {
+        self.permissive || self.dialect.hash_comment()
+    }
+
+    fn at_variable(&self) -> bool {
+        self.permissive || self.dialect.at_variable()
+    }
+
+    fn dollar_quote(&self) -> bool {
+        self.permissive || self.dialect.dollar_quote()
+    }
+}
+
+/// SQL keywords, types, boolean literals and built-in functions, stored
+/// uppercased and matched case-insensitively. Editing this table is the only
+/// thing needed to teach the lexer a new word.
+static KEYWORDS: KeywordTable = KeywordTable::new_ignore_ascii_case(&[
+    // DDL
+    (b"CREATE", TokenKind::Keyword), (b"ALTER", TokenKind::Keyword),
+    (b"DROP", TokenKind::Keyword), (b"TRUNCATE", TokenKind::Keyword),
+    (b"RENAME", TokenKind::Keyword), (b"TABLE", TokenKind::Keyword),
+    (b"VIEW", TokenKind::Keyword), (b"INDEX", TokenKind::Keyword),
+    (b"DATABASE", TokenKind::Keyword), (b"SCHEMA", TokenKind::Keyword),
+    (b"PROCEDURE", TokenKind::Keyword), (b"FUNCTION", TokenKind::Keyword),
+    (b"TRIGGER", TokenKind::Keyword), (b"SEQUENCE", TokenKind::Keyword),
+    // DML
+    (b"SELECT", TokenKind::Keyword), (b"INSERT", TokenKind::Keyword),
+    (b"UPDATE", TokenKind::Keyword), (b"DELETE", TokenKind::Keyword),
+    (b"MERGE", TokenKind::Keyword), (b"FROM", TokenKind::Keyword),
+    (b"WHERE", TokenKind::Keyword), (b"JOIN", TokenKind::Keyword),
+    (b"INNER", TokenKind::Keyword), (b"LEFT", TokenKind::Keyword),
+    (b"RIGHT", TokenKind::Keyword), (b"FULL", TokenKind::Keyword),
+    (b"CROSS", TokenKind::Keyword), (b"ON", TokenKind::Keyword),
+    (b"USING", TokenKind::Keyword), (b"GROUP", TokenKind::Keyword),
+    (b"HAVING", TokenKind::Keyword), (b"ORDER", TokenKind::Keyword),
+    (b"BY", TokenKind::Keyword), (b"LIMIT", TokenKind::Keyword),
+    (b"OFFSET", TokenKind::Keyword), (b"FETCH", TokenKind::Keyword),
+    (b"TOP", TokenKind::Keyword), (b"UNION", TokenKind::Keyword),
+    (b"INTERSECT", TokenKind::Keyword), (b"EXCEPT", TokenKind::Keyword),
+    (b"MINUS", TokenKind::Keyword), (b"INTO", TokenKind::Keyword),
+    (b"VALUES", TokenKind::Keyword), (b"SET", TokenKind::Keyword),
+    // DCL
+    (b"GRANT", TokenKind::Keyword), (b"REVOKE", TokenKind::Keyword),
+    (b"DENY", TokenKind::Keyword),
+    // TCL
+    (b"COMMIT", TokenKind::Keyword), (b"ROLLBACK", TokenKind::Keyword),
+    (b"SAVEPOINT", TokenKind::Keyword), (b"BEGIN", TokenKind::Keyword),
+    (b"END", TokenKind::Keyword), (b"TRANSACTION", TokenKind::Keyword),
+    (b"START", TokenKind::Keyword),
+    // Constraints
+    (b"PRIMARY", TokenKind::Keyword), (b"FOREIGN", TokenKind::Keyword),
+    (b"KEY", TokenKind::Keyword), (b"UNIQUE", TokenKind::Keyword),
+    (b"CHECK", TokenKind::Keyword), (b"DEFAULT", TokenKind::Keyword),
+    (b"NOT", TokenKind::Keyword), (b"NULL", TokenKind::Keyword),
+    (b"CONSTRAINT", TokenKind::Keyword), (b"REFERENCES", TokenKind::Keyword),
+    // Other keywords
+    (b"AS", TokenKind::Keyword), (b"DISTINCT", TokenKind::Keyword),
+    (b"ALL", TokenKind::Keyword), (b"ANY", TokenKind::Keyword),
+    (b"SOME", TokenKind::Keyword), (b"EXISTS", TokenKind::Keyword),
+    (b"IN", TokenKind::Keyword), (b"BETWEEN", TokenKind::Keyword),
+    (b"LIKE", TokenKind::Keyword), (b"IS", TokenKind::Keyword),
+    (b"AND", TokenKind::Keyword), (b"OR", TokenKind::Keyword),
+    (b"CASE", TokenKind::Keyword), (b"WHEN", TokenKind::Keyword),
+    (b"THEN", TokenKind::Keyword), (b"ELSE", TokenKind::Keyword),
+    (b"IF", TokenKind::Keyword), (b"WHILE", TokenKind::Keyword),
+    (b"LOOP", TokenKind::Keyword), (b"REPEAT", TokenKind::Keyword),
+    (b"GOTO", TokenKind::Keyword), (b"RETURN", TokenKind::Keyword),
+    (b"DECLARE", TokenKind::Keyword), (b"CURSOR", TokenKind::Keyword),
+    (b"OPEN", TokenKind::Keyword), (b"CLOSE", TokenKind::Keyword),
+    (b"WITH", TokenKind::Keyword), (b"RECURSIVE", TokenKind::Keyword),
+    (b"OVER", TokenKind::Keyword), (b"PARTITION", TokenKind::Keyword),
+    (b"WINDOW", TokenKind::Keyword), (b"ROWS", TokenKind::Keyword),
+    (b"RANGE", TokenKind::Keyword), (b"PRECEDING", TokenKind::Keyword),
+    (b"FOLLOWING", TokenKind::Keyword), (b"CURRENT", TokenKind::Keyword),
+    (b"ROW", TokenKind::Keyword), (b"UNBOUNDED", TokenKind::Keyword),
+    // Data types
+    (b"INT", TokenKind::TypeName), (b"INTEGER", TokenKind::TypeName),
+    (b"BIGINT", TokenKind::TypeName), (b"SMALLINT", TokenKind::TypeName),
+    (b"TINYINT", TokenKind::TypeName), (b"DECIMAL", TokenKind::TypeName),
+    (b"NUMERIC", TokenKind::TypeName), (b"FLOAT", TokenKind::TypeName),
+    (b"REAL", TokenKind::TypeName), (b"DOUBLE", TokenKind::TypeName),
+    (b"CHAR", TokenKind::TypeName), (b"VARCHAR", TokenKind::TypeName),
+    (b"TEXT", TokenKind::TypeName), (b"NCHAR", TokenKind::TypeName),
+    (b"NVARCHAR", TokenKind::TypeName), (b"NTEXT", TokenKind::TypeName),
+    (b"DATE", TokenKind::TypeName), (b"TIME", TokenKind::TypeName),
+    (b"DATETIME", TokenKind::TypeName), (b"TIMESTAMP", TokenKind::TypeName),
+    (b"YEAR", TokenKind::TypeName), (b"BOOLEAN", TokenKind::TypeName),
+    (b"BOOL", TokenKind::TypeName), (b"BIT", TokenKind::TypeName),
+    (b"BLOB", TokenKind::TypeName), (b"CLOB", TokenKind::TypeName),
+    (b"BINARY", TokenKind::TypeName), (b"VARBINARY", TokenKind::TypeName),
+    (b"JSON", TokenKind::TypeName), (b"XML", TokenKind::TypeName),
+    (b"UUID", TokenKind::TypeName), (b"SERIAL", TokenKind::TypeName),
+    (b"AUTO_INCREMENT", TokenKind::TypeName),
+    // Boolean literals
+    (b"TRUE", TokenKind::Boolean), (b"FALSE", TokenKind::Boolean),
+    // Aggregate functions
+    (b"COUNT", TokenKind::FunctionName), (b"SUM", TokenKind::FunctionName),
+    (b"AVG", TokenKind::FunctionName), (b"MIN", TokenKind::FunctionName),
+    (b"MAX", TokenKind::FunctionName), (b"STDDEV", TokenKind::FunctionName),
+    (b"VARIANCE", TokenKind::FunctionName), (b"GROUP_CONCAT", TokenKind::FunctionName),
+    (b"STRING_AGG", TokenKind::FunctionName),
+    // String functions
+    (b"CONCAT", TokenKind::FunctionName), (b"SUBSTRING", TokenKind::FunctionName),
+    (b"SUBSTR", TokenKind::FunctionName), (b"LENGTH", TokenKind::FunctionName),
+    (b"UPPER", TokenKind::FunctionName), (b"LOWER", TokenKind::FunctionName),
+    (b"TRIM", TokenKind::FunctionName), (b"LTRIM", TokenKind::FunctionName),
+    (b"RTRIM", TokenKind::FunctionName), (b"REPLACE", TokenKind::FunctionName),
+    (b"COALESCE", TokenKind::FunctionName),
+    // Date functions
+    (b"NOW", TokenKind::FunctionName), (b"CURRENT_DATE", TokenKind::FunctionName),
+    (b"CURRENT_TIME", TokenKind::FunctionName), (b"CURRENT_TIMESTAMP", TokenKind::FunctionName),
+    (b"DATEADD", TokenKind::FunctionName), (b"DATEDIFF", TokenKind::FunctionName),
+    (b"EXTRACT", TokenKind::FunctionName),
+    // Conversion functions
+    (b"CAST", TokenKind::FunctionName), (b"CONVERT", TokenKind::FunctionName),
+    (b"TO_CHAR", TokenKind::FunctionName), (b"TO_DATE", TokenKind::FunctionName),
+    (b"TO_NUMBER", TokenKind::FunctionName),
+]);
+
+/// Classify a scanned identifier case-insensitively, returning its keyword
+/// kind or `None` for a plain identifier. One hash lookup over a short probe
+/// chain, with no per-word uppercase allocation.
+pub(crate) fn lookup_keyword(word: &[u8]) -> Option<TokenKind> {
+    KEYWORDS.lookup(word)
+}
 
 impl Lexer for SqlLexer {
     fn tokenize(&self, text: &[u8]) -> Vec<Token> {
+        self.run(text, LexerState::Normal, &mut Logger::new()).0
+    }
+
+    fn tokenize_with_diagnostics(&self, text: &[u8]) -> (Vec<Token>, Vec<Diagnostic>) {
+        let mut log = Logger::new();
+        let (tokens, _) = self.run(text, LexerState::Normal, &mut log);
+        (tokens, log.into_diagnostics())
+    }
+
+    fn tokenize_line(&self, line: &[u8], entry: LexerState) -> (Vec<Token>, LexerState) {
+        self.run(line, entry, &mut Logger::new())
+    }
+}
+
+impl SqlLexer {
+    /// Scan `text` starting in `entry` state, returning the tokens and the
+    /// [`LexerState`] the scan ended in — `InBlockComment` or `InString` when
+    /// `text` ends inside an unterminated `/* */` comment or `'...'` string.
+    ///
+    /// Malformed constructs are reported into `log`; the `tokenize` fast path
+    /// passes a throwaway logger so nothing is allocated unless a caller asks
+    /// for diagnostics.
+    fn run(&self, text: &[u8], entry: LexerState, log: &mut Logger) -> (Vec<Token>, LexerState) {
         let mut tokens = Vec::with_capacity(text.len() / 8);
         let mut pos = 0;
+        let mut exit = LexerState::Normal;
+
+        // Resume a multi-line construct carried in from the previous line.
+        match entry {
+            LexerState::InBlockComment => {
+                let mut closed = false;
+                while pos + 1 < text.len() {
+                    if text[pos] == b'*' && text[pos + 1] == b'/' {
+                        pos += 2;
+                        closed = true;
+                        break;
+                    }
+                    pos += 1;
+                }
+                if !closed {
+                    pos = text.len();
+                    exit = LexerState::InBlockComment;
+                    log.report(LexMessage::UnclosedBlockComment, 0..pos);
+                }
+                tokens.push(Token::new(TokenKind::Comment, 0..pos));
+            }
+            LexerState::InString { quote } => {
+                // No opening quote on this line — resume scanning the body.
+                let (end, closed) = scan_quoted(text, 0, quote);
+                pos = end;
+                if !closed {
+                    exit = LexerState::InString { quote };
+                    log.report(LexMessage::UnclosedStringLiteral, 0..pos);
+                }
+                let kind = if quote == b'"' { TokenKind::Identifier } else { TokenKind::String };
+                tokens.push(Token::new(kind, 0..pos));
+            }
+            _ => {}
+        }
 
         while pos < text.len() {
             let start = pos;
@@ -35,7 +301,7 @@ impl Lexer for SqlLexer {
                     tokens.push(Token::new(TokenKind::Comment, start..pos));
                 }
                 
-                b'#' => {
+                b'#' if self.hash_comment() => {
                     pos += 1;
                     while pos < text.len() && text[pos] != b'\n' {
                         pos += 1;
@@ -46,68 +312,100 @@ impl Lexer for SqlLexer {
                 // Block comment /* ... */
                 b'/' if pos + 1 < text.len() && text[pos + 1] == b'*' => {
                     pos += 2;
+                    let mut closed = false;
                     while pos + 1 < text.len() {
                         if text[pos] == b'*' && text[pos + 1] == b'/' {
                             pos += 2;
+                            closed = true;
                             break;
                         }
                         pos += 1;
                     }
+                    if !closed {
+                        pos = text.len();
+                        exit = LexerState::InBlockComment;
+                        log.report(LexMessage::UnclosedBlockComment, start..pos);
+                    }
                     tokens.push(Token::new(TokenKind::Comment, start..pos));
                 }
 
-                // Single-quoted string
-                b'\'' => {
-                    pos += 1;
+                // PostgreSQL escape string E'...' with C-style backslash escapes
+                b'E' | b'e' if self.dollar_quote() && pos + 1 < text.len() && text[pos + 1] == b'\'' => {
+                    pos += 2;
+                    let mut closed = false;
                     while pos < text.len() {
-                        if text[pos] == b'\'' {
-                            pos += 1;
-                            // Handle doubled single quotes (SQL escape)
-                            if pos < text.len() && text[pos] == b'\'' {
+                        match text[pos] {
+                            b'\\' if pos + 1 < text.len() => pos += 2,
+                            b'\'' => {
                                 pos += 1;
-                            } else {
+                                closed = true;
                                 break;
                             }
-                        } else {
-                            pos += 1;
+                            _ => pos += 1,
                         }
                     }
+                    if !closed {
+                        log.report(LexMessage::UnclosedStringLiteral, start..pos);
+                    }
                     tokens.push(Token::new(TokenKind::String, start..pos));
                 }
 
-                // Double-quoted identifier (or string in some SQL dialects)
-                b'"' => {
-                    pos += 1;
-                    while pos < text.len() {
-                        if text[pos] == b'"' {
-                            pos += 1;
-                            // Handle doubled double quotes
-                            if pos < text.len() && text[pos] == b'"' {
-                                pos += 1;
-                            } else {
-                                break;
+                // PostgreSQL dollar-quoted string $tag$ ... $tag$
+                b'$' if self.dollar_quote() => {
+                    match scan_dollar_quote(text, pos) {
+                        Some((end, closed)) => {
+                            pos = end;
+                            if !closed {
+                                log.report(LexMessage::UnclosedStringLiteral, start..pos);
                             }
-                        } else {
+                            tokens.push(Token::new(TokenKind::String, start..pos));
+                        }
+                        // Not a dollar-quote opener — treat as a lone operator.
+                        None => {
                             pos += 1;
+                            tokens.push(Token::new(TokenKind::Operator, start..pos));
                         }
                     }
+                }
+
+                // Single-quoted string
+                b'\'' => {
+                    let (end, closed) = scan_quoted(text, pos + 1, b'\'');
+                    pos = end;
+                    if !closed {
+                        exit = LexerState::InString { quote: b'\'' };
+                        log.report(LexMessage::UnclosedStringLiteral, start..pos);
+                    }
+                    tokens.push(Token::new(TokenKind::String, start..pos));
+                }
+
+                // Double-quoted identifier (or string in some SQL dialects)
+                b'"' => {
+                    let (end, closed) = scan_quoted(text, pos + 1, b'"');
+                    pos = end;
+                    if !closed {
+                        exit = LexerState::InString { quote: b'"' };
+                        log.report(LexMessage::UnclosedStringLiteral, start..pos);
+                    }
                     tokens.push(Token::new(TokenKind::Identifier, start..pos));
                 }
 
                 // Backtick-quoted identifier (MySQL)
-                b'`' => {
+                b'`' if self.backtick_ident() => {
                     pos += 1;
                     while pos < text.len() && text[pos] != b'`' {
                         pos += 1;
                     }
                     if pos < text.len() {
                         pos += 1;
+                    } else {
+                        log.report(LexMessage::UnclosedStringLiteral, start..pos);
                     }
                     tokens.push(Token::new(TokenKind::Identifier, start..pos));
                 }
 
                 // Bracket-quoted identifier (SQL Server) [identifier]
-                b'[' => {
+                b'[' if self.bracket_ident() => {
                     pos += 1;
                     let mut is_identifier = false;
                     while pos < text.len() && text[pos] != b']' {
@@ -118,6 +416,8 @@ impl Lexer for SqlLexer {
                     }
                     if pos < text.len() {
                         pos += 1;
+                    } else {
+                        log.report(LexMessage::UnclosedStringLiteral, start..pos);
                     }
                     tokens.push(Token::new(
                         if is_identifier { TokenKind::Identifier } else { TokenKind::Operator },
@@ -161,7 +461,7 @@ impl Lexer for SqlLexer {
                 }
 
                 // Identifier or keyword
-                _ if is_ident_start(b) || b == b'_' || b == b'@' => {
+                _ if is_ident_start(b) || b == b'_' || (b == b'@' && self.at_variable()) => {
                     // Variable (T-SQL @variable or @@system_variable)
                     if b == b'@' {
                         pos += 1;
@@ -175,83 +475,16 @@ impl Lexer for SqlLexer {
                     }
                     
                     let word = &text[start..pos];
-                    
+
                     // Skip if it's a variable
                     if word.starts_with(b"@") {
                         tokens.push(Token::new(TokenKind::VariableName, start..pos));
                         continue;
                     }
-                    
-                    // Convert to uppercase for comparison (SQL is case-insensitive)
-                    let mut upper = Vec::with_capacity(word.len());
-                    for &byte in word {
-                        upper.push(byte.to_ascii_uppercase());
-                    }
-                    
-                    let kind = match upper.as_slice() {
-                        // SQL Keywords - DDL
-                        b"CREATE" | b"ALTER" | b"DROP" | b"TRUNCATE" | b"RENAME" |
-                        b"TABLE" | b"VIEW" | b"INDEX" | b"DATABASE" | b"SCHEMA" |
-                        b"PROCEDURE" | b"FUNCTION" | b"TRIGGER" | b"SEQUENCE" => TokenKind::Keyword,
-                        
-                        // SQL Keywords - DML
-                        b"SELECT" | b"INSERT" | b"UPDATE" | b"DELETE" | b"MERGE" |
-                        b"FROM" | b"WHERE" | b"JOIN" | b"INNER" | b"LEFT" | b"RIGHT" | b"FULL" | b"CROSS" |
-                        b"ON" | b"USING" | b"GROUP" | b"HAVING" | b"ORDER" | b"BY" |
-                        b"LIMIT" | b"OFFSET" | b"FETCH" | b"TOP" |
-                        b"UNION" | b"INTERSECT" | b"EXCEPT" | b"MINUS" |
-                        b"INTO" | b"VALUES" | b"SET" => TokenKind::Keyword,
-                        
-                        // SQL Keywords - DCL
-                        b"GRANT" | b"REVOKE" | b"DENY" => TokenKind::Keyword,
-                        
-                        // SQL Keywords - TCL
-                        b"COMMIT" | b"ROLLBACK" | b"SAVEPOINT" | b"BEGIN" | b"END" |
-                        b"TRANSACTION" | b"START" => TokenKind::Keyword,
-                        
-                        // SQL Keywords - Constraints
-                        b"PRIMARY" | b"FOREIGN" | b"KEY" | b"UNIQUE" | b"CHECK" |
-                        b"DEFAULT" | b"NOT" | b"NULL" | b"CONSTRAINT" | b"REFERENCES" => TokenKind::Keyword,
-                        
-                        // SQL Keywords - Other
-                        b"AS" | b"DISTINCT" | b"ALL" | b"ANY" | b"SOME" | b"EXISTS" |
-                        b"IN" | b"BETWEEN" | b"LIKE" | b"IS" | b"AND" | b"OR" |
-                        b"CASE" | b"WHEN" | b"THEN" | b"ELSE" |
-                        b"IF" | b"WHILE" | b"LOOP" | b"REPEAT" | b"GOTO" | b"RETURN" |
-                        b"DECLARE" | b"CURSOR" | b"OPEN" | b"CLOSE" |
-                        b"WITH" | b"RECURSIVE" | b"OVER" | b"PARTITION" |
-                        b"WINDOW" | b"ROWS" | b"RANGE" | b"PRECEDING" | b"FOLLOWING" |
-                        b"CURRENT" | b"ROW" | b"UNBOUNDED" => TokenKind::Keyword,
-                        
-                        // Data types
-                        b"INT" | b"INTEGER" | b"BIGINT" | b"SMALLINT" | b"TINYINT" |
-                        b"DECIMAL" | b"NUMERIC" | b"FLOAT" | b"REAL" | b"DOUBLE" |
-                        b"CHAR" | b"VARCHAR" | b"TEXT" | b"NCHAR" | b"NVARCHAR" | b"NTEXT" |
-                        b"DATE" | b"TIME" | b"DATETIME" | b"TIMESTAMP" | b"YEAR" |
-                        b"BOOLEAN" | b"BOOL" | b"BIT" |
-                        b"BLOB" | b"CLOB" | b"BINARY" | b"VARBINARY" |
-                        b"JSON" | b"XML" | b"UUID" | b"SERIAL" | b"AUTO_INCREMENT" => TokenKind::TypeName,
-                        
-                        // Boolean literals
-                        b"TRUE" | b"FALSE" => TokenKind::Boolean,
-                        
-                        // Aggregate functions
-                        b"COUNT" | b"SUM" | b"AVG" | b"MIN" | b"MAX" |
-                        b"STDDEV" | b"VARIANCE" | b"GROUP_CONCAT" | b"STRING_AGG" => TokenKind::FunctionName,
-                        
-                        // String functions
-                        b"CONCAT" | b"SUBSTRING" | b"SUBSTR" | b"LENGTH" | b"UPPER" | b"LOWER" |
-                        b"TRIM" | b"LTRIM" | b"RTRIM" | b"REPLACE" | b"COALESCE" => TokenKind::FunctionName,
-                        
-                        // Date functions
-                        b"NOW" | b"CURRENT_DATE" | b"CURRENT_TIME" | b"CURRENT_TIMESTAMP" |
-                        b"DATEADD" | b"DATEDIFF" | b"EXTRACT" => TokenKind::FunctionName,
-                        
-                        // Conversion functions
-                        b"CAST" | b"CONVERT" | b"TO_CHAR" | b"TO_DATE" | b"TO_NUMBER" => TokenKind::FunctionName,
-                        
-                        _ => TokenKind::Identifier,
-                    };
+
+                    // SQL is case-insensitive, so the table folds case in place
+                    // — no per-word uppercase buffer is allocated.
+                    let kind = lookup_keyword(word).unwrap_or(TokenKind::Identifier);
                     tokens.push(Token::new(kind, start..pos));
                 }
 
@@ -275,12 +508,64 @@ impl Lexer for SqlLexer {
 
                 // Unknown character
                 _ => {
+                    log.report(LexMessage::UnexpectedCharacter(b), start..pos + 1);
                     pos += 1;
                     tokens.push(Token::new(TokenKind::Error, start..pos));
                 }
             }
         }
 
-        tokens
+        (tokens, exit)
+    }
+}
+
+/// Scan the body of a quoted run starting at `body_start` (just past the
+/// opening `quote`), honouring SQL's doubled-quote escape (`''` / `""`).
+/// Returns the index one past the closing quote and whether the run closed
+/// before the end of `text`.
+fn scan_quoted(text: &[u8], body_start: usize, quote: u8) -> (usize, bool) {
+    let mut pos = body_start;
+    while pos < text.len() {
+        if text[pos] == quote {
+            pos += 1;
+            // A doubled quote is an escaped quote, not the terminator.
+            if pos < text.len() && text[pos] == quote {
+                pos += 1;
+            } else {
+                return (pos, true);
+            }
+        } else {
+            pos += 1;
+        }
+    }
+    (pos, false)
+}
+
+/// Scan a PostgreSQL dollar-quoted string opening at `start` (a `$`). The
+/// delimiter is `$tag$` where `tag` is an optional identifier, and the body
+/// runs up to the next identical `$tag$`. Returns `None` when `start` does not
+/// begin a well-formed opening delimiter (so the caller can treat `$` as a
+/// plain operator), otherwise the index past the closing delimiter — or
+/// end-of-text — and whether it closed.
+fn scan_dollar_quote(text: &[u8], start: usize) -> Option<(usize, bool)> {
+    let mut tag_end = start + 1;
+    while tag_end < text.len() && text[tag_end] != b'$' {
+        if !(is_ident_continue(text[tag_end])) {
+            return None;
+        }
+        tag_end += 1;
+    }
+    if tag_end >= text.len() {
+        return None;
+    }
+    // `text[start..=tag_end]` is the full `$tag$` delimiter.
+    let delim = &text[start..tag_end + 1];
+    let mut pos = tag_end + 1;
+    while pos + delim.len() <= text.len() {
+        if &text[pos..pos + delim.len()] == delim {
+            return Some((pos + delim.len(), true));
+        }
+        pos += 1;
     }
+    Some((text.len(), false))
 }
@@ -43,3 +43,19 @@ fn test_cpp_lexer_basic() {
     println!("Tokens: {}", tokens.len());
     assert!(!tokens.is_empty(), "C++ lexer should produce tokens");
 }
+
+#[test]
+fn test_tokenize_positioned_line_col() {
+    let source = b"let x = 1\nlet y = 2";
+    let lexer = LexerRegistry::get_lexer(Language::Rust);
+    let positioned = lexer.tokenize_positioned(source);
+
+    assert!(!positioned.is_empty(), "Rust lexer should produce tokens");
+    // Every token's position must agree with its byte span start.
+    assert!(positioned.iter().all(|(token, pos)| {
+        if token.span.start < 10 { pos.line == 0 } else { pos.line == 1 }
+    }));
+    // The first token starts at the very top-left.
+    let (_, first) = &positioned[0];
+    assert_eq!((first.line, first.col), (0, 0));
+}
@@ -90,39 +90,77 @@ impl Lexer for TomlLexer {
                     tokens.push(Token::new(TokenKind::String, start..pos));
                 }
 
-                // Numbers (integers and floats)
-                b'0'..=b'9' | b'+' | b'-' if matches!(b, b'+' | b'-') && pos + 1 < text.len() && is_ascii_digit(text[pos + 1]) || is_ascii_digit(b) => {
-                    if matches!(b, b'+' | b'-') {
+                // Numbers, datetimes, and inf/nan (optionally signed)
+                b'0'..=b'9' | b'+' | b'-'
+                    if is_ascii_digit(b)
+                        || (matches!(b, b'+' | b'-')
+                            && pos + 1 < text.len()
+                            && (is_ascii_digit(text[pos + 1])
+                                || matches_word(text, pos + 1, b"inf")
+                                || matches_word(text, pos + 1, b"nan"))) =>
+                {
+                    let signed = matches!(b, b'+' | b'-');
+                    if signed {
                         pos += 1;
                     }
-                    
-                    while pos < text.len() && (is_ascii_digit(text[pos]) || text[pos] == b'_') {
-                        pos += 1;
-                    }
-                    
-                    // Float
-                    if pos < text.len() && text[pos] == b'.' {
-                        pos += 1;
-                        while pos < text.len() && (is_ascii_digit(text[pos]) || text[pos] == b'_') {
-                            pos += 1;
-                        }
-                    }
-                    
-                    // Exponent
-                    if pos < text.len() && matches!(text[pos], b'e' | b'E') {
-                        pos += 1;
-                        if pos < text.len() && matches!(text[pos], b'+' | b'-') {
+
+                    let mut kind = TokenKind::Number;
+
+                    if matches_word(text, pos, b"inf") || matches_word(text, pos, b"nan") {
+                        // Special floating-point values (possibly signed).
+                        pos += 3;
+                    } else if !signed
+                        && text[pos] == b'0'
+                        && pos + 1 < text.len()
+                        && matches!(text[pos + 1], b'x' | b'o' | b'b')
+                    {
+                        // Non-decimal integer: 0x.., 0o.., 0b..
+                        let radix = text[pos + 1];
+                        pos += 2;
+                        while pos < text.len() && is_radix_digit(text[pos], radix) {
                             pos += 1;
                         }
+                    } else {
+                        // Leading integer digit run.
+                        let digits_start = pos;
                         while pos < text.len() && (is_ascii_digit(text[pos]) || text[pos] == b'_') {
                             pos += 1;
                         }
+                        let digit_count = pos - digits_start;
+
+                        if !signed && digit_count == 4 && pos < text.len() && text[pos] == b'-' {
+                            // RFC 3339 (offset/local) date or date-time: YYYY-...
+                            pos = consume_datetime(text, pos);
+                            kind = TokenKind::DateTime;
+                        } else if !signed && digit_count == 2 && pos < text.len() && text[pos] == b':' {
+                            // Bare local time: HH:MM:SS...
+                            pos = consume_datetime(text, pos);
+                            kind = TokenKind::DateTime;
+                        } else {
+                            // Fractional part.
+                            if pos < text.len() && text[pos] == b'.' {
+                                pos += 1;
+                                while pos < text.len() && (is_ascii_digit(text[pos]) || text[pos] == b'_') {
+                                    pos += 1;
+                                }
+                            }
+                            // Exponent.
+                            if pos < text.len() && matches!(text[pos], b'e' | b'E') {
+                                pos += 1;
+                                if pos < text.len() && matches!(text[pos], b'+' | b'-') {
+                                    pos += 1;
+                                }
+                                while pos < text.len() && (is_ascii_digit(text[pos]) || text[pos] == b'_') {
+                                    pos += 1;
+                                }
+                            }
+                        }
                     }
-                    
-                    tokens.push(Token::new(TokenKind::Number, start..pos));
+
+                    tokens.push(Token::new(kind, start..pos));
                 }
 
-                // Keywords (true, false)
+                // Keywords (true, false) and the bare inf/nan floats
                 b't' if pos + 4 <= text.len() && &text[pos..pos + 4] == b"true" => {
                     pos += 4;
                     tokens.push(Token::new(TokenKind::Boolean, start..pos));
@@ -131,6 +169,14 @@ impl Lexer for TomlLexer {
                     pos += 5;
                     tokens.push(Token::new(TokenKind::Boolean, start..pos));
                 }
+                b'i' if matches_word(text, pos, b"inf") => {
+                    pos += 3;
+                    tokens.push(Token::new(TokenKind::Number, start..pos));
+                }
+                b'n' if matches_word(text, pos, b"nan") => {
+                    pos += 3;
+                    tokens.push(Token::new(TokenKind::Number, start..pos));
+                }
 
                 // Keys (identifiers)
                 _ if is_ident_start(b) => {
@@ -166,10 +212,136 @@ impl Lexer for TomlLexer {
     }
 }
 
+/// Whether `word` appears at `pos` as a whole token (not followed by another
+/// identifier byte), so `inf` matches but `information` does not.
+fn matches_word(text: &[u8], pos: usize, word: &[u8]) -> bool {
+    text.len() >= pos + word.len()
+        && &text[pos..pos + word.len()] == word
+        && (pos + word.len() >= text.len() || !is_ident_continue(text[pos + word.len()]))
+}
+
+/// Whether `b` is a valid digit (or `_` separator) for a `0x`/`0o`/`0b` prefix,
+/// selected by the prefix byte `radix`.
+fn is_radix_digit(b: u8, radix: u8) -> bool {
+    match radix {
+        b'x' => b.is_ascii_hexdigit() || b == b'_',
+        b'o' => matches!(b, b'0'..=b'7' | b'_'),
+        b'b' => matches!(b, b'0' | b'1' | b'_'),
+        _ => false,
+    }
+}
+
+/// Consume exactly up to `count` ASCII digits from `pos`, returning the new
+/// position.
+fn eat_n_digits(text: &[u8], mut pos: usize, count: usize) -> usize {
+    let mut n = 0;
+    while n < count && pos < text.len() && is_ascii_digit(text[pos]) {
+        pos += 1;
+        n += 1;
+    }
+    pos
+}
+
+/// Consume the rest of an RFC 3339 date-time (or a bare local time) from `pos`,
+/// which points just past the leading `YYYY` (at the `-`) or just past the
+/// leading `HH` (at the `:`). The scan is deliberately tolerant — it colors
+/// whatever is date/time shaped rather than fully validating the literal.
+fn consume_datetime(text: &[u8], mut pos: usize) -> usize {
+    let at = |p: usize, b: u8| p < text.len() && text[p] == b;
+
+    // Date part `-MM-DD`, present only when entered on `-`.
+    if at(pos, b'-') {
+        pos += 1;
+        pos = eat_n_digits(text, pos, 2);
+        if at(pos, b'-') {
+            pos += 1;
+            pos = eat_n_digits(text, pos, 2);
+        }
+        // Optional date-time separator: `T`, `t`, or a space before a time.
+        let sep = at(pos, b'T')
+            || at(pos, b't')
+            || (at(pos, b' ') && pos + 1 < text.len() && is_ascii_digit(text[pos + 1]));
+        if sep {
+            pos += 1;
+            pos = eat_n_digits(text, pos, 2); // HH
+        } else {
+            return pos; // local date only
+        }
+    }
+
+    // Time part `:MM:SS`, positioned at the `:` after `HH`.
+    if at(pos, b':') {
+        pos += 1;
+        pos = eat_n_digits(text, pos, 2);
+        if at(pos, b':') {
+            pos += 1;
+            pos = eat_n_digits(text, pos, 2);
+        }
+        // Optional fractional seconds.
+        if at(pos, b'.') {
+            pos += 1;
+            while pos < text.len() && is_ascii_digit(text[pos]) {
+                pos += 1;
+            }
+        }
+    }
+
+    // Optional offset: `Z`/`z` or `±HH:MM`.
+    if at(pos, b'Z') || at(pos, b'z') {
+        pos += 1;
+    } else if (at(pos, b'+') || at(pos, b'-'))
+        && pos + 1 < text.len()
+        && is_ascii_digit(text[pos + 1])
+    {
+        pos += 1;
+        pos = eat_n_digits(text, pos, 2);
+        if at(pos, b':') {
+            pos += 1;
+            pos = eat_n_digits(text, pos, 2);
+        }
+    }
+
+    pos
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Return the single value token that follows `key = `.
+    fn value_token(text: &[u8]) -> Token {
+        let tokens = TomlLexer.tokenize(text);
+        tokens
+            .into_iter()
+            .rev()
+            .find(|t| !matches!(t.kind, TokenKind::Whitespace))
+            .expect("a value token")
+    }
+
+    #[test]
+    fn test_toml_datetime() {
+        let text = b"ts = 1979-05-27T07:32:00Z";
+        let tok = value_token(text);
+        assert_eq!(tok.kind, TokenKind::DateTime);
+        assert_eq!(&text[tok.span], b"1979-05-27T07:32:00Z");
+    }
+
+    #[test]
+    fn test_toml_local_time() {
+        let text = b"t = 07:32:00";
+        let tok = value_token(text);
+        assert_eq!(tok.kind, TokenKind::DateTime);
+        assert_eq!(&text[tok.span], b"07:32:00");
+    }
+
+    #[test]
+    fn test_toml_hex_integer() {
+        let text = b"hex = 0xDEAD_BEEF";
+        let tok = value_token(text);
+        assert_eq!(tok.kind, TokenKind::Number);
+        assert_eq!(&text[tok.span], b"0xDEAD_BEEF");
+    }
+
     #[test]
     fn test_toml_section() {
         let lexer = TomlLexer;
@@ -0,0 +1,238 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! WebAssembly Text format (WAT) lexer.
+//!
+//! The text format is S-expression based: `(` and `)` group instructions,
+//! `;;` starts a line comment and `(; ... ;)` a nestable block comment, and
+//! identifiers that name locals/labels begin with `$`. Anything else that is a
+//! maximal run of idchars is either a recognized keyword or — per the grammar's
+//! reserved-token rule — a plain identifier, never an error.
+
+use crate::syntax::lexer::{Diagnostic, LexMessage, Logger};
+use crate::syntax::lexer::{Lexer, is_ascii_digit};
+use crate::syntax::{Token, TokenKind};
+
+pub struct WatLexer;
+
+impl Lexer for WatLexer {
+    fn tokenize(&self, text: &[u8]) -> Vec<Token> {
+        self.run(text, &mut Logger::new())
+    }
+
+    fn tokenize_with_diagnostics(&self, text: &[u8]) -> (Vec<Token>, Vec<Diagnostic>) {
+        let mut log = Logger::new();
+        let tokens = self.run(text, &mut log);
+        (tokens, log.into_diagnostics())
+    }
+}
+
+impl WatLexer {
+    /// Scan `text`, reporting unterminated strings and block comments into
+    /// `log`; the `tokenize` fast path passes a throwaway logger so nothing is
+    /// collected unless a caller asks.
+    fn run(&self, text: &[u8], log: &mut Logger) -> Vec<Token> {
+        let mut tokens = Vec::with_capacity(text.len() / 8);
+        let mut pos = 0;
+
+        while pos < text.len() {
+            let start = pos;
+            let b = text[pos];
+
+            match b {
+                // Whitespace
+                b' ' | b'\t' | b'\n' | b'\r' => {
+                    while pos < text.len() && matches!(text[pos], b' ' | b'\t' | b'\n' | b'\r') {
+                        pos += 1;
+                    }
+                    tokens.push(Token::new(TokenKind::Whitespace, start..pos));
+                }
+
+                // Line comment `;; ...`
+                b';' if pos + 1 < text.len() && text[pos + 1] == b';' => {
+                    pos += 2;
+                    while pos < text.len() && text[pos] != b'\n' {
+                        pos += 1;
+                    }
+                    tokens.push(Token::new(TokenKind::Comment, start..pos));
+                }
+
+                // Block comment `(; ... ;)`, which nests.
+                b'(' if pos + 1 < text.len() && text[pos + 1] == b';' => {
+                    pos += 2;
+                    let mut depth = 1usize;
+                    while pos + 1 < text.len() && depth > 0 {
+                        if text[pos] == b'(' && text[pos + 1] == b';' {
+                            depth += 1;
+                            pos += 2;
+                        } else if text[pos] == b';' && text[pos + 1] == b')' {
+                            depth -= 1;
+                            pos += 2;
+                        } else {
+                            pos += 1;
+                        }
+                    }
+                    if depth > 0 {
+                        pos = text.len();
+                        log.report(LexMessage::UnclosedBlockComment, start..pos);
+                    }
+                    tokens.push(Token::new(TokenKind::Comment, start..pos));
+                }
+
+                // Delimiters
+                b'(' | b')' => {
+                    pos += 1;
+                    tokens.push(Token::new(TokenKind::Delimiter, start..pos));
+                }
+
+                // String literals
+                b'"' => {
+                    pos += 1;
+                    let mut escaped = false;
+                    let mut closed = false;
+                    while pos < text.len() {
+                        if escaped {
+                            escaped = false;
+                        } else if text[pos] == b'\\' {
+                            escaped = true;
+                        } else if text[pos] == b'"' {
+                            pos += 1;
+                            closed = true;
+                            break;
+                        } else if text[pos] == b'\n' {
+                            break;
+                        }
+                        pos += 1;
+                    }
+                    if !closed {
+                        log.report(LexMessage::UnclosedStringLiteral, start..pos);
+                    }
+                    tokens.push(Token::new(TokenKind::String, start..pos));
+                }
+
+                // Local/label names `$foo`
+                b'$' => {
+                    pos += 1;
+                    while pos < text.len() && is_idchar(text[pos]) {
+                        pos += 1;
+                    }
+                    tokens.push(Token::new(TokenKind::Label, start..pos));
+                }
+
+                // Numbers, including `0x...` and float forms (optionally signed).
+                b'+' | b'-' | b'0'..=b'9'
+                    if is_ascii_digit(b)
+                        || (matches!(b, b'+' | b'-')
+                            && pos + 1 < text.len()
+                            && is_ascii_digit(text[pos + 1])) =>
+                {
+                    if matches!(b, b'+' | b'-') {
+                        pos += 1;
+                    }
+                    let hex = pos + 1 < text.len()
+                        && text[pos] == b'0'
+                        && matches!(text[pos + 1], b'x' | b'X');
+                    if hex {
+                        pos += 2;
+                    }
+                    while pos < text.len() && is_number_char(text[pos], hex) {
+                        pos += 1;
+                    }
+                    tokens.push(Token::new(TokenKind::Number, start..pos));
+                }
+
+                // Keywords and reserved tokens (bare idchar runs).
+                _ if is_idchar(b) => {
+                    while pos < text.len() && is_idchar(text[pos]) {
+                        pos += 1;
+                    }
+                    let kind = keyword_kind(&text[start..pos]);
+                    tokens.push(Token::new(kind, start..pos));
+                }
+
+                // Anything else
+                _ => {
+                    pos += 1;
+                    tokens.push(Token::new(TokenKind::Error, start..pos));
+                }
+            }
+        }
+
+        tokens
+    }
+}
+
+/// Whether `b` is an idchar: the characters a WAT keyword, number, or reserved
+/// token may contain.
+fn is_idchar(b: u8) -> bool {
+    b.is_ascii_alphanumeric()
+        || matches!(
+            b,
+            b'!' | b'#' | b'$' | b'%' | b'&' | b'\'' | b'*' | b'+' | b'-' | b'.' | b'/'
+                | b':' | b'<' | b'=' | b'>' | b'?' | b'@' | b'\\' | b'^' | b'_' | b'`'
+                | b'|' | b'~'
+        )
+}
+
+/// Whether `b` continues a numeric literal; hex literals additionally admit the
+/// `a`–`f` digits. Underscores are permitted as group separators either way.
+fn is_number_char(b: u8, hex: bool) -> bool {
+    is_ascii_digit(b)
+        || matches!(b, b'_' | b'.' | b'+' | b'-')
+        || (hex && matches!(b, b'a'..=b'f' | b'A'..=b'F' | b'p' | b'P'))
+        || (!hex && matches!(b, b'e' | b'E'))
+}
+
+/// Classify a bare idchar run. Unrecognized runs are reserved tokens, which the
+/// text format treats as identifiers rather than errors.
+fn keyword_kind(word: &[u8]) -> TokenKind {
+    match word {
+        b"i32" | b"i64" | b"f32" | b"f64" | b"v128" | b"funcref" | b"externref" => {
+            TokenKind::KeywordType
+        }
+        b"block" | b"loop" | b"if" | b"else" | b"end" | b"br" | b"br_if" | b"br_table"
+        | b"call" | b"call_indirect" | b"return" | b"unreachable" => TokenKind::KeywordControl,
+        b"module" | b"func" | b"param" | b"result" | b"local" | b"global" | b"table"
+        | b"memory" | b"type" | b"import" | b"export" | b"start" | b"elem" | b"data"
+        | b"mut" | b"offset" | b"align" => TokenKind::Keyword,
+        _ => TokenKind::Identifier,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wat_keywords_and_labels() {
+        let lexer = WatLexer;
+        let text = b"(func $add (param $x i32) (result i32))";
+        let tokens = lexer.tokenize(text);
+
+        assert!(tokens.iter().any(|t| t.kind == TokenKind::Keyword));
+        assert!(tokens.iter().any(|t| t.kind == TokenKind::KeywordType));
+        assert!(tokens.iter().any(|t| t.kind == TokenKind::Label));
+    }
+
+    #[test]
+    fn test_wat_nested_block_comment() {
+        let lexer = WatLexer;
+        let text = b"(; outer (; inner ;) still ;) module";
+        let tokens = lexer.tokenize(text);
+
+        // The nested comment is a single token; `module` after it is a keyword.
+        let comments = tokens.iter().filter(|t| t.kind == TokenKind::Comment).count();
+        assert_eq!(comments, 1);
+        assert!(tokens.iter().any(|t| t.kind == TokenKind::Keyword));
+    }
+
+    #[test]
+    fn test_wat_numbers() {
+        let lexer = WatLexer;
+        let text = b"i32.const 0xDEAD i64.const -42";
+        let tokens = lexer.tokenize(text);
+
+        let numbers = tokens.iter().filter(|t| t.kind == TokenKind::Number).count();
+        assert_eq!(numbers, 2);
+    }
+}
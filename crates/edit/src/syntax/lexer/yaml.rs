@@ -3,17 +3,77 @@
 
 //! YAML configuration file lexer.
 
-use crate::syntax::lexer::{Lexer, is_ident_start, is_ident_continue, is_ascii_digit};
+use crate::syntax::lexer::{Diagnostic, LexMessage, Logger};
+use crate::syntax::lexer::{Lexer, LexerState, is_ident_start, is_ident_continue, is_ascii_digit};
 use crate::syntax::{Token, TokenKind};
 
 pub struct YamlLexer;
 
 impl Lexer for YamlLexer {
     fn tokenize(&self, text: &[u8]) -> Vec<Token> {
+        self.run(text, LexerState::Normal, &mut Logger::new()).0
+    }
+
+    fn tokenize_with_diagnostics(&self, text: &[u8]) -> (Vec<Token>, Vec<Diagnostic>) {
+        let mut log = Logger::new();
+        let (tokens, _) = self.run(text, LexerState::Normal, &mut log);
+        (tokens, log.into_diagnostics())
+    }
+
+    fn tokenize_line(&self, line: &[u8], entry: LexerState) -> (Vec<Token>, LexerState) {
+        self.run(line, entry, &mut Logger::new())
+    }
+}
+
+impl YamlLexer {
+    /// Scan `text` starting in `entry` state, returning the tokens and the exit
+    /// [`LexerState`] — `InString` when a quoted scalar runs past the end of
+    /// `text` without its closing quote. Unterminated scalars are reported into
+    /// `log`; the fast `tokenize` path passes a throwaway sink.
+    fn run(&self, text: &[u8], entry: LexerState, log: &mut Logger) -> (Vec<Token>, LexerState) {
         let mut tokens = Vec::with_capacity(text.len() / 8);
         let mut pos = 0;
+        let mut exit = LexerState::Normal;
+        // Parent indentation of an open block scalar, if we are inside one.
+        let mut block: Option<u16> = match entry {
+            LexerState::InBlockScalar { parent_indent } => Some(parent_indent),
+            _ => None,
+        };
+
+        // Resume a quoted scalar carried in from the previous line.
+        if let LexerState::InString { quote } = entry {
+            let (end, closed) = scan_quoted(text, 0, quote);
+            pos = end;
+            if !closed {
+                exit = LexerState::InString { quote };
+                log.report(LexMessage::UnclosedStringLiteral, 0..pos);
+            }
+            tokens.push(Token::new(TokenKind::String, 0..pos));
+        }
 
         while pos < text.len() {
+            // A block scalar swallows every following line that is blank or
+            // indented deeper than the parent key, as one `String` span per
+            // line, until the indentation returns to the parent level.
+            if let Some(parent) = block {
+                if pos == 0 || text[pos - 1] == b'\n' {
+                    let (indent, blank) = line_indent(text, pos);
+                    if blank || indent > parent as usize {
+                        let mut end = pos;
+                        while end < text.len() && text[end] != b'\n' {
+                            end += 1;
+                        }
+                        if end < text.len() {
+                            end += 1; // include the trailing newline
+                        }
+                        tokens.push(Token::new(TokenKind::String, pos..end));
+                        pos = end;
+                        continue;
+                    }
+                    block = None;
+                }
+            }
+
             let start = pos;
             let b = text[pos];
 
@@ -52,19 +112,11 @@ impl Lexer for YamlLexer {
 
                 // Strings (quoted)
                 b'"' | b'\'' => {
-                    let quote = b;
-                    pos += 1;
-                    let mut escaped = false;
-                    while pos < text.len() {
-                        if escaped {
-                            escaped = false;
-                        } else if text[pos] == b'\\' {
-                            escaped = true;
-                        } else if text[pos] == quote {
-                            pos += 1;
-                            break;
-                        }
-                        pos += 1;
+                    let (end, closed) = scan_quoted(text, pos + 1, b);
+                    pos = end;
+                    if !closed {
+                        exit = LexerState::InString { quote: b };
+                        log.report(LexMessage::UnclosedStringLiteral, start..pos);
                     }
                     tokens.push(Token::new(TokenKind::String, start..pos));
                 }
@@ -167,24 +219,89 @@ impl Lexer for YamlLexer {
                     tokens.push(Token::new(TokenKind::Punctuation, start..pos));
                 }
 
-                // Pipe and fold markers
+                // Block scalar header (`|`/`>`, optional chomping `+`/`-` and
+                // explicit indentation digit). Opens a block whose body is
+                // captured line-by-line above until the indentation drops back
+                // to the parent key's column.
                 b'|' | b'>' => {
                     pos += 1;
+                    while pos < text.len() && matches!(text[pos], b'+' | b'-' | b'0'..=b'9') {
+                        pos += 1;
+                    }
                     tokens.push(Token::new(TokenKind::Operator, start..pos));
+                    let (parent, _) = line_indent(text, line_start(text, start));
+                    block = Some(parent as u16);
                 }
 
                 // Unknown
                 _ => {
+                    log.report(LexMessage::UnexpectedCharacter(b), start..pos + 1);
                     pos += 1;
                     tokens.push(Token::new(TokenKind::Error, start..pos));
                 }
             }
         }
 
-        tokens
+        // An open block scalar survives the line boundary for incremental
+        // re-lexing; a quoted-string exit (set above) takes precedence since
+        // the two contexts never nest.
+        if exit == LexerState::Normal {
+            if let Some(parent) = block {
+                exit = LexerState::InBlockScalar { parent_indent: parent };
+            }
+        }
+
+        (tokens, exit)
     }
 }
 
+/// Byte offset of the start of the line containing `pos`.
+fn line_start(text: &[u8], pos: usize) -> usize {
+    let mut s = pos;
+    while s > 0 && text[s - 1] != b'\n' {
+        s -= 1;
+    }
+    s
+}
+
+/// The indentation (leading-space count) of the line beginning at `start`, and
+/// whether that line is blank (only spaces before its newline, a `\r`, or the
+/// end of `text`).
+fn line_indent(text: &[u8], start: usize) -> (usize, bool) {
+    let mut i = start;
+    while i < text.len() && text[i] == b' ' {
+        i += 1;
+    }
+    let blank = i >= text.len() || matches!(text[i], b'\n' | b'\r');
+    (i - start, blank)
+}
+
+/// Scan a YAML quoted scalar body starting at `body_start` (just past the
+/// opening `quote`). Double-quoted scalars honour `\`-escapes; single-quoted
+/// scalars use the doubled-quote (`''`) escape. Returns the index one past the
+/// closing quote and whether the scalar closed before the end of `text`.
+fn scan_quoted(text: &[u8], body_start: usize, quote: u8) -> (usize, bool) {
+    let mut pos = body_start;
+    let mut escaped = false;
+    while pos < text.len() {
+        if quote == b'"' && escaped {
+            escaped = false;
+        } else if quote == b'"' && text[pos] == b'\\' {
+            escaped = true;
+        } else if text[pos] == quote {
+            pos += 1;
+            // A doubled quote inside a single-quoted scalar is an escape.
+            if quote == b'\'' && pos < text.len() && text[pos] == b'\'' {
+                pos += 1;
+                continue;
+            }
+            return (pos, true);
+        }
+        pos += 1;
+    }
+    (pos, false)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -211,4 +328,18 @@ mod tests {
         let bools: Vec<_> = tokens.iter().filter(|t| t.kind == TokenKind::Boolean).collect();
         assert_eq!(bools.len(), 2);
     }
+
+    #[test]
+    fn test_yaml_block_scalar() {
+        let lexer = YamlLexer;
+        let text = b"script: |\n  line one\n  true 42\nnext: ok";
+        let tokens = lexer.tokenize(text);
+
+        // The indented block body is a single string per line, not re-lexed as
+        // booleans/numbers, and lexing resumes normally at `next`.
+        let strings: Vec<_> = tokens.iter().filter(|t| t.kind == TokenKind::String).collect();
+        assert_eq!(strings.len(), 2);
+        assert!(!tokens.iter().any(|t| t.kind == TokenKind::Boolean));
+        assert!(tokens.iter().any(|t| t.kind == TokenKind::Identifier));
+    }
 }
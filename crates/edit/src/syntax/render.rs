@@ -0,0 +1,258 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Rendering token streams to ANSI terminal escape sequences.
+//!
+//! The syntax layer otherwise only exposes [`TokenStyle`] colors and leaves it
+//! to the TUI to paint cells. For piping highlighted source to a plain terminal
+//! — `cat`-style previews, test fixtures, `--color` output — an [`AnsiRenderer`]
+//! wraps each token's source slice in SGR escapes taken from a [`Theme`].
+//!
+//! Terminals vary in color fidelity, so the renderer supports three
+//! [`ColorDepth`]s. Downsampling a truecolor theme to the sparse 256-color or
+//! 16-color palettes is done by nearest-neighbor search in OkLab space rather
+//! than sRGB: the perceptual metric keeps hues recognizable, where naive RGB
+//! distance snaps, say, a muted green to a garish one on the coarse cube.
+
+use crate::oklab::StraightRgba;
+use crate::syntax::{Theme, Token, TokenKind, TokenStyle};
+
+/// The color fidelity of the target terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorDepth {
+    /// 24-bit direct color (`\x1b[38;2;R;G;Bm`).
+    TrueColor,
+    /// The xterm 256-color palette (`\x1b[38;5;Nm`).
+    Palette256,
+    /// The 16 ANSI colors (`\x1b[30-37m` / `\x1b[90-97m`).
+    Ansi16,
+}
+
+/// Wraps highlighted source in ANSI escape sequences using a [`Theme`].
+pub struct AnsiRenderer<'a> {
+    theme: &'a Theme,
+    depth: ColorDepth,
+}
+
+impl<'a> AnsiRenderer<'a> {
+    /// Create a renderer that colors with `theme` at the given `depth`.
+    pub fn new(theme: &'a Theme, depth: ColorDepth) -> Self {
+        Self { theme, depth }
+    }
+
+    /// Render `tokens` over `text` into a string of source interleaved with SGR
+    /// escapes. Bytes not covered by any token (and whitespace-only tokens) are
+    /// emitted verbatim, so the output reproduces the input exactly once the
+    /// escapes are stripped. Invalid UTF-8 is replaced lossily, matching how the
+    /// editor already treats on-disk bytes.
+    pub fn render(&self, text: &[u8], tokens: &[Token]) -> String {
+        let mut out = String::with_capacity(text.len() + text.len() / 4);
+        let mut pos = 0;
+        for token in tokens {
+            if token.span.start > pos {
+                out.push_str(&String::from_utf8_lossy(&text[pos..token.span.start]));
+            }
+            let slice = &text[token.span.start..token.span.end];
+            if token.kind == TokenKind::Whitespace {
+                out.push_str(&String::from_utf8_lossy(slice));
+            } else {
+                let style = self.theme.get_style(token.kind);
+                self.push_styled(&mut out, style, slice);
+            }
+            pos = token.span.end;
+        }
+        if pos < text.len() {
+            out.push_str(&String::from_utf8_lossy(&text[pos..]));
+        }
+        out
+    }
+
+    /// Emit `slice` wrapped in the SGR sequence for `style`. Whitespace tokens
+    /// never reach here: [`render`](Self::render) emits them verbatim.
+    fn push_styled(&self, out: &mut String, style: TokenStyle, slice: &[u8]) {
+        let text = String::from_utf8_lossy(slice);
+        let mut params = String::new();
+        if style.bold {
+            params.push_str("1;");
+        }
+        if style.italic {
+            params.push_str("3;");
+        }
+        if style.underline {
+            params.push_str("4;");
+        }
+        self.push_color(&mut params, style.fg, false);
+        if let Some(bg) = style.bg {
+            params.push(';');
+            self.push_color(&mut params, bg, true);
+        }
+        out.push_str("\x1b[");
+        out.push_str(&params);
+        out.push('m');
+        out.push_str(&text);
+        out.push_str("\x1b[0m");
+    }
+
+    /// Append the SGR parameters selecting `color` as a foreground (`bg` false)
+    /// or background, honoring the renderer's [`ColorDepth`].
+    fn push_color(&self, params: &mut String, color: StraightRgba, bg: bool) {
+        let (r, g, b) = (color.red() as u32, color.green() as u32, color.blue() as u32);
+        match self.depth {
+            ColorDepth::TrueColor => {
+                let lead = if bg { 48 } else { 38 };
+                params.push_str(&format!("{lead};2;{r};{g};{b}"));
+            }
+            ColorDepth::Palette256 => {
+                let lead = if bg { 48 } else { 38 };
+                params.push_str(&format!("{lead};5;{}", nearest_256(color)));
+            }
+            ColorDepth::Ansi16 => {
+                let idx = nearest_16(color);
+                let code = match (idx < 8, bg) {
+                    (true, false) => 30 + idx,
+                    (false, false) => 90 + (idx - 8),
+                    (true, true) => 40 + idx,
+                    (false, true) => 100 + (idx - 8),
+                };
+                params.push_str(&format!("{code}"));
+            }
+        }
+    }
+}
+
+/// Find the xterm 256-color index whose color is perceptually closest to
+/// `color`, searching the 6×6×6 cube (16–231) and the grayscale ramp (232–255)
+/// in OkLab space.
+fn nearest_256(color: StraightRgba) -> u32 {
+    let target = oklab_of(color);
+    let mut best = 16;
+    let mut best_dist = f32::INFINITY;
+    for n in 16..=255 {
+        let dist = oklab_dist(target, oklab_of(xterm256(n)));
+        if dist < best_dist {
+            best_dist = dist;
+            best = n;
+        }
+    }
+    best
+}
+
+/// Find the ANSI 16-color index perceptually closest to `color` in OkLab space.
+fn nearest_16(color: StraightRgba) -> u32 {
+    let target = oklab_of(color);
+    let mut best = 0;
+    let mut best_dist = f32::INFINITY;
+    for n in 0..16 {
+        let dist = oklab_dist(target, oklab_of(ansi16(n)));
+        if dist < best_dist {
+            best_dist = dist;
+            best = n;
+        }
+    }
+    best
+}
+
+/// Squared Euclidean distance between two OkLab triples; monotonic in the true
+/// distance, so it suffices for nearest-neighbor selection.
+fn oklab_dist(a: (f32, f32, f32), b: (f32, f32, f32)) -> f32 {
+    let dl = a.0 - b.0;
+    let da = a.1 - b.1;
+    let db = a.2 - b.2;
+    dl * dl + da * da + db * db
+}
+
+/// Convert an sRGB color to OkLab `(L, a, b)`. Mirrors the forward transform the
+/// settings color parser uses, kept local so the renderer depends only on
+/// [`StraightRgba`].
+fn oklab_of(color: StraightRgba) -> (f32, f32, f32) {
+    let r = decode_srgb(color.red());
+    let g = decode_srgb(color.green());
+    let b = decode_srgb(color.blue());
+
+    let l = 0.412_221_47 * r + 0.536_332_55 * g + 0.051_445_995 * b;
+    let m = 0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b;
+    let s = 0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b;
+
+    let (l_, m_, s_) = (l.cbrt(), m.cbrt(), s.cbrt());
+
+    let ll = 0.210_454_26 * l_ + 0.793_617_8 * m_ - 0.004_072_047 * s_;
+    let aa = 1.977_998_5 * l_ - 2.428_592_2 * m_ + 0.450_593_7 * s_;
+    let bb = 0.025_904_037 * l_ + 0.782_771_77 * m_ - 0.808_675_77 * s_;
+    (ll, aa, bb)
+}
+
+/// Decode an 8-bit sRGB channel to linear light.
+fn decode_srgb(c: u8) -> f32 {
+    let c = c as f32 / 255.0;
+    if c <= 0.040_45 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+}
+
+/// The sRGB value of one of the 16 ANSI colors (`0`–`7` normal, `8`–`15`
+/// bright).
+fn ansi16(index: u32) -> StraightRgba {
+    const PALETTE: [u32; 16] = [
+        0x000000, 0x800000, 0x008000, 0x808000, 0x000080, 0x800080, 0x008080, 0xC0C0C0,
+        0x808080, 0xFF0000, 0x00FF00, 0xFFFF00, 0x0000FF, 0xFF00FF, 0x00FFFF, 0xFFFFFF,
+    ];
+    rgb(PALETTE[(index as usize) & 0xF])
+}
+
+/// The sRGB value of an xterm 256-color index in the 6×6×6 cube (16–231) or the
+/// 24-step grayscale ramp (232–255).
+fn xterm256(n: u32) -> StraightRgba {
+    if n < 232 {
+        const LEVELS: [u32; 6] = [0, 95, 135, 175, 215, 255];
+        let n = n - 16;
+        let r = LEVELS[(n / 36) as usize];
+        let g = LEVELS[((n / 6) % 6) as usize];
+        let b = LEVELS[(n % 6) as usize];
+        return rgb((r << 16) | (g << 8) | b);
+    }
+    let v = 8 + 10 * (n - 232);
+    rgb((v << 16) | (v << 8) | v)
+}
+
+/// Helper to create an RGB color from a hex value.
+fn rgb(hex: u32) -> StraightRgba {
+    let r = (hex >> 16) & 0xFF;
+    let g = (hex >> 8) & 0xFF;
+    let b = hex & 0xFF;
+    StraightRgba::from_le(r | (g << 8) | (b << 16) | (0xFF << 24))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syntax::{Language, LexerRegistry};
+
+    #[test]
+    fn test_truecolor_escape() {
+        let theme = Theme::default_dark();
+        let renderer = AnsiRenderer::new(&theme, ColorDepth::TrueColor);
+        let text = b"true";
+        let tokens = LexerRegistry::get_lexer(Language::Json).tokenize(text);
+        let out = renderer.render(text, &tokens);
+        // Booleans are bold blue (0x569CD6) in the dark theme.
+        assert!(out.contains("\x1b[1;38;2;86;156;214m"));
+        assert!(out.contains("true"));
+        assert!(out.ends_with("\x1b[0m"));
+    }
+
+    #[test]
+    fn test_256_prefers_oklab_match() {
+        // Pure red maps to cube index 196 (5,0,0), not a desaturated neighbor.
+        assert_eq!(nearest_256(rgb(0xFF0000)), 196);
+        // Mid gray snaps onto the grayscale ramp, not the cube.
+        assert!(nearest_256(rgb(0x808080)) >= 232);
+    }
+
+    #[test]
+    fn test_16_color_codes() {
+        let theme = Theme::default_dark();
+        let renderer = AnsiRenderer::new(&theme, ColorDepth::Ansi16);
+        let mut params = String::new();
+        renderer.push_color(&mut params, rgb(0xFF0000), false);
+        // Bright red is index 9 -> 90 + 1.
+        assert_eq!(params, "91");
+    }
+}
@@ -4,12 +4,20 @@
 //! Color themes for syntax highlighting.
 
 use crate::oklab::StraightRgba;
-use crate::syntax::TokenKind;
+use crate::syntax::{Modifiers, Token, TokenKind};
 
 /// A complete color theme for syntax highlighting.
 #[derive(Clone)]
 pub struct Theme {
     styles: Vec<TokenStyle>,
+    /// Overlay styles keyed by `(kind, modifiers)`, consulted by
+    /// [`Theme::get_style_with_modifiers`] before the built-in modifier
+    /// overlay and before falling back to the bare `kind` style.
+    modifier_styles: Vec<(TokenKind, Modifiers, TokenStyle)>,
+    /// Scope-stack rules, kept sorted by descending `scopes.len()` so
+    /// [`Theme::get_style_for_token`] can return the first (and therefore
+    /// most specific) match — see [`Theme::add_scope_rule`].
+    scope_rules: Vec<(Vec<String>, TokenStyle)>,
 }
 
 /// The visual style for a token.
@@ -62,6 +70,69 @@ impl TokenStyle {
         self.bg = Some(bg);
         self
     }
+
+    /// Build a style from a semicolon-separated list of ANSI SGR codes, the
+    /// same encoding `LS_COLORS` and `ls` use.
+    ///
+    /// `1`/`3`/`4` set bold/italic/underline; `30`–`37`/`90`–`97` pick the
+    /// 8/16-color foreground palette and `40`–`47` the background; the extended
+    /// `38;5;N`/`48;5;N` (256-color) and `38;2;R;G;B`/`48;2;R;G;B` (truecolor)
+    /// sequences set `fg`/`bg` directly. Leading zeros are stripped and any
+    /// code we don't model is skipped. Returns `None` only when no code in the
+    /// string is recognized.
+    pub fn from_sgr(spec: &str) -> Option<TokenStyle> {
+        let codes: Vec<Option<u32>> = spec.split(';').map(parse_sgr_code).collect();
+        let mut style = TokenStyle::new(rgb(0xD4D4D4));
+        let mut recognized = false;
+        let mut i = 0;
+        while i < codes.len() {
+            let Some(code) = codes[i] else {
+                i += 1;
+                continue;
+            };
+            match code {
+                1 => style.bold = true,
+                3 => style.italic = true,
+                4 => style.underline = true,
+                30..=37 => style.fg = ansi16(code - 30),
+                90..=97 => style.fg = ansi16(8 + code - 90),
+                40..=47 => style.bg = Some(ansi16(code - 40)),
+                38 | 48 => {
+                    let bg = code == 48;
+                    let (color, consumed) = match codes.get(i + 1).copied().flatten() {
+                        Some(5) => (codes.get(i + 2).copied().flatten().map(xterm256), 2),
+                        Some(2) => {
+                            let r = codes.get(i + 2).copied().flatten();
+                            let g = codes.get(i + 3).copied().flatten();
+                            let b = codes.get(i + 4).copied().flatten();
+                            let c = match (r, g, b) {
+                                (Some(r), Some(g), Some(b)) => Some(rgb((r << 16) | (g << 8) | b)),
+                                _ => None,
+                            };
+                            (c, 4)
+                        }
+                        _ => (None, 0),
+                    };
+                    i += consumed;
+                    match color {
+                        Some(c) if bg => style.bg = Some(c),
+                        Some(c) => style.fg = c,
+                        None => {
+                            i += 1;
+                            continue;
+                        }
+                    }
+                }
+                _ => {
+                    i += 1;
+                    continue;
+                }
+            }
+            recognized = true;
+            i += 1;
+        }
+        recognized.then_some(style)
+    }
 }
 
 impl Theme {
@@ -71,14 +142,20 @@ impl Theme {
 
         // Comments - green
         styles[TokenKind::Comment as usize] = TokenStyle::new(rgb(0x6A9955)).italic();
+        styles[TokenKind::LineComment as usize] = TokenStyle::new(rgb(0x6A9955)).italic();
+        styles[TokenKind::BlockComment as usize] = TokenStyle::new(rgb(0x6A9955)).italic();
+        // Doc comments get the slightly darker VS Code doc-comment green.
+        styles[TokenKind::DocComment as usize] = TokenStyle::new(rgb(0x608B4E)).italic();
 
         // Strings - orange/brown
         styles[TokenKind::String as usize] = TokenStyle::new(rgb(0xCE9178));
         styles[TokenKind::Char as usize] = TokenStyle::new(rgb(0xCE9178));
         styles[TokenKind::Escape as usize] = TokenStyle::new(rgb(0xD7BA7D));
+        styles[TokenKind::StringInterpolationDelim as usize] = TokenStyle::new(rgb(0xD7BA7D)).bold();
 
         // Numbers - light green
         styles[TokenKind::Number as usize] = TokenStyle::new(rgb(0xB5CEA8));
+        styles[TokenKind::DateTime as usize] = TokenStyle::new(rgb(0xB5CEA8));
 
         // Booleans and null - blue
         styles[TokenKind::Boolean as usize] = TokenStyle::new(rgb(0x569CD6)).bold();
@@ -125,16 +202,23 @@ impl Theme {
         styles[TokenKind::RustAttribute as usize] = TokenStyle::new(rgb(0x4EC9B0));
 
         // Markdown specific
-        styles[TokenKind::MarkdownHeading as usize] = TokenStyle::new(rgb(0x569CD6)).bold();
+        for level in 1..=6 {
+            styles[TokenKind::markdown_heading(level) as usize] =
+                TokenStyle::new(rgb(0x569CD6)).bold();
+        }
         styles[TokenKind::MarkdownBold as usize] = TokenStyle::new(rgb(0xD4D4D4)).bold();
         styles[TokenKind::MarkdownItalic as usize] = TokenStyle::new(rgb(0xD4D4D4)).italic();
         styles[TokenKind::MarkdownCode as usize] = TokenStyle::new(rgb(0xCE9178));
         styles[TokenKind::MarkdownLink as usize] = TokenStyle::new(rgb(0x4EC9B0)).underline();
+        styles[TokenKind::MarkdownListMarker as usize] = TokenStyle::new(rgb(0x569CD6));
+        styles[TokenKind::MarkdownTaskBox as usize] = TokenStyle::new(rgb(0x4EC9B0));
+        styles[TokenKind::MarkdownBlockQuote as usize] = TokenStyle::new(rgb(0x6A9955)).italic();
+        styles[TokenKind::MarkdownTableDelimiter as usize] = TokenStyle::new(rgb(0x808080));
 
         // Errors - red
         styles[TokenKind::Error as usize] = TokenStyle::new(rgb(0xF44747)).underline();
 
-        Self { styles }
+        Self { styles, modifier_styles: Vec::new(), scope_rules: Vec::new() }
     }
 
     /// Create a new theme with default light colors (inspired by VS Code Light+).
@@ -143,6 +227,9 @@ impl Theme {
 
         // Comments - green
         styles[TokenKind::Comment as usize] = TokenStyle::new(rgb(0x008000)).italic();
+        styles[TokenKind::LineComment as usize] = TokenStyle::new(rgb(0x008000)).italic();
+        styles[TokenKind::BlockComment as usize] = TokenStyle::new(rgb(0x008000)).italic();
+        styles[TokenKind::DocComment as usize] = TokenStyle::new(rgb(0x008000)).italic();
 
         // Strings - brown/red
         styles[TokenKind::String as usize] = TokenStyle::new(rgb(0xA31515));
@@ -173,7 +260,155 @@ impl Theme {
         // Errors - red
         styles[TokenKind::Error as usize] = TokenStyle::new(rgb(0xFF0000)).underline();
 
-        Self { styles }
+        Self { styles, modifier_styles: Vec::new(), scope_rules: Vec::new() }
+    }
+
+    /// Look up a built-in theme by name (case-insensitive), returning `None`
+    /// for an unknown name so callers can fall back to [`Theme::default`].
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "dark+" | "dark" => Some(Self::default_dark()),
+            "light+" | "light" => Some(Self::default_light()),
+            _ => None,
+        }
+    }
+
+    /// Load a user theme from `path`.
+    ///
+    /// A file whose first non-whitespace character is `{` is JSON, parsed in
+    /// two passes: first as our own [`Theme::to_json`] round-trip format via
+    /// [`Theme::from_json`] (gated on the `serde` feature), since it can
+    /// represent a background color and round-trips losslessly; a document
+    /// that isn't in that shape (or when the feature is off) is parsed as a
+    /// VS Code / TextMate color-theme via [`Theme::from_vscode_json`] instead.
+    /// Anything else is parsed as the plain
+    /// `TokenKind: #RRGGBB[AA] [bold] [italic] [underline]` override format
+    /// via [`Theme::from_overrides`], so an existing theme file written
+    /// before JSON support keeps working unchanged.
+    pub fn load(path: &std::path::Path) -> std::io::Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        if contents.trim_start().starts_with('{') {
+            #[cfg(feature = "serde")]
+            if let Ok(theme) = Self::from_json(&contents) {
+                return Ok(theme);
+            }
+            Ok(Self::from_vscode_json(&contents))
+        } else {
+            Ok(Self::from_overrides(&contents))
+        }
+    }
+
+    /// Parse theme overrides from a string (see [`Theme::load`]). Lines that are
+    /// blank, start with `;`/`//`, or don't name a known [`TokenKind`] are
+    /// ignored, so a malformed entry never discards the rest of the theme.
+    pub fn from_overrides(contents: &str) -> Self {
+        let mut theme = Self::default_dark();
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(';') || line.starts_with("//") {
+                continue;
+            }
+            let Some((name, spec)) = line.split_once(':') else { continue };
+            let Some(kind) = token_kind_from_name(name.trim()) else { continue };
+            let Some(style) = parse_style(spec.trim()) else { continue };
+            theme.set_style(kind, style);
+        }
+        theme
+    }
+
+    /// Build a theme from an `LS_COLORS`-style string: colon-separated
+    /// `TokenKind=sgr` entries, where each value is a semicolon-separated SGR
+    /// code list (see [`TokenStyle::from_sgr`]). Starts from the dark defaults
+    /// and overlays only the named kinds, so a user's tuned palette drives the
+    /// highlighter without hand-editing Rust.
+    pub fn from_ls_colors(spec: &str) -> Self {
+        let mut theme = Self::default_dark();
+        for entry in spec.split(':') {
+            let Some((key, value)) = entry.split_once('=') else { continue };
+            let Some(kind) = token_kind_from_name(key.trim()) else { continue };
+            let Some(style) = TokenStyle::from_sgr(value.trim()) else { continue };
+            theme.set_style(kind, style);
+        }
+        theme
+    }
+
+    /// Build a theme from a VS Code / TextMate color-theme JSON document.
+    ///
+    /// The defaults shipped here are hand-transcribed from "Dark+"; this lets a
+    /// user drop in any downloaded `.json` theme instead. It reads the
+    /// `tokenColors` array of `{ scope, settings: { foreground, fontStyle } }`
+    /// rules and the top-level `colors["editor.foreground"]` default, resolves
+    /// each TextMate scope selector to a [`TokenKind`] through [`SCOPE_TABLE`]
+    /// (honoring the most specific — longest — matching selector when several
+    /// apply), and translates `fontStyle` into the bold/italic/underline flags.
+    /// Kinds no rule touches keep the resolved default foreground, so the
+    /// returned table is always complete; a document without an
+    /// `editor.foreground` uses our own default foreground. A document that
+    /// fails to parse, or whose root is not an object, yields
+    /// [`Theme::default_dark`].
+    pub fn from_vscode_json(json_str: &str) -> Self {
+        use crate::json;
+        use stdext::arena::scratch_arena;
+
+        let arena = scratch_arena(None);
+        let root = match json::parse(&arena, json_str) {
+            Ok(root) => root,
+            Err(_) => return Self::default_dark(),
+        };
+        let Some(obj) = root.as_object() else { return Self::default_dark() };
+
+        // Resolve the editor default foreground; every unstyled kind inherits it.
+        let default_fg = obj
+            .get("colors")
+            .and_then(|c| c.as_object())
+            .and_then(|c| c.get_str("editor.foreground"))
+            .and_then(parse_hex_color)
+            .unwrap_or_else(|| rgb(0xD4D4D4));
+
+        let mut styles = vec![TokenStyle::new(default_fg); 256];
+        // Specificity of the selector that last wrote each slot, so a more
+        // specific scope (`keyword.control`) overrides a broader one (`keyword`).
+        let mut specificity = vec![0usize; 256];
+
+        let rules = obj.get("tokenColors").and_then(|v| v.as_array());
+        for rule in rules.into_iter().flatten() {
+            let Some(rule) = rule.as_object() else { continue };
+            let Some(settings) = rule.get("settings").and_then(|s| s.as_object()) else {
+                continue;
+            };
+            let style = {
+                let fg = settings.get_str("foreground").and_then(parse_hex_color);
+                let mut style = TokenStyle::new(fg.unwrap_or(default_fg));
+                if let Some(font) = settings.get_str("fontStyle") {
+                    for word in font.split_whitespace() {
+                        match word {
+                            "bold" => style.bold = true,
+                            "italic" => style.italic = true,
+                            "underline" => style.underline = true,
+                            _ => {}
+                        }
+                    }
+                }
+                style
+            };
+
+            // `scope` is either a string or an array of strings.
+            match rule.get("scope") {
+                Some(scope) if scope.as_str().is_some() => {
+                    apply_scope(&mut styles, &mut specificity, scope.as_str().unwrap(), style);
+                }
+                Some(scope) => {
+                    for s in scope.as_array().into_iter().flatten() {
+                        if let Some(s) = s.as_str() {
+                            apply_scope(&mut styles, &mut specificity, s, style);
+                        }
+                    }
+                }
+                None => {}
+            }
+        }
+
+        Self { styles, modifier_styles: Vec::new(), scope_rules: Vec::new() }
     }
 
     /// Get the style for a given token kind.
@@ -181,12 +416,164 @@ impl Theme {
         self.styles.get(kind as usize).copied().unwrap_or(TokenStyle::new(rgb(0xD4D4D4)))
     }
 
+    /// The dense `kind as usize`-indexed style table backing [`get_style`](Self::get_style),
+    /// exposed so [`HighlightMap::new`] can snapshot it once per theme change
+    /// instead of re-resolving a style per token.
+    pub(crate) fn style_table(&self) -> &[TokenStyle] {
+        &self.styles
+    }
+
     /// Set the style for a given token kind.
     pub fn set_style(&mut self, kind: TokenKind, style: TokenStyle) {
         if (kind as usize) < self.styles.len() {
             self.styles[kind as usize] = style;
         }
     }
+
+    /// Register a theme-specific style for `kind` qualified by `modifiers`,
+    /// consulted by [`Theme::get_style_with_modifiers`] ahead of the built-in
+    /// modifier overlay. Replaces any existing entry for the same exact
+    /// `(kind, modifiers)` pair.
+    pub fn set_modifier_style(&mut self, kind: TokenKind, modifiers: Modifiers, style: TokenStyle) {
+        if let Some(entry) = self
+            .modifier_styles
+            .iter_mut()
+            .find(|(k, m, _)| *k == kind && *m == modifiers)
+        {
+            entry.2 = style;
+        } else {
+            self.modifier_styles.push((kind, modifiers, style));
+        }
+    }
+
+    /// Resolve a token's style from its base `kind` plus any [`Modifiers`] a
+    /// lexer attached to it.
+    ///
+    /// Looks for the most specific registered [`set_modifier_style`](Self::set_modifier_style)
+    /// entry whose modifiers are a (non-empty) subset of `modifiers` — "most
+    /// specific" meaning the entry with the most modifier bits matched, ties
+    /// broken by registration order. Absent a match, applies
+    /// [`default_modifier_overlay`] to [`get_style`](Self::get_style)'s result
+    /// so a theme that never mentions modifiers still renders them distinctly
+    /// (e.g. underlining anything flagged [`Modifiers::DEPRECATED`]).
+    pub fn get_style_with_modifiers(&self, kind: TokenKind, modifiers: Modifiers) -> TokenStyle {
+        let base = self.get_style(kind);
+        if modifiers.is_empty() {
+            return base;
+        }
+
+        let mut best: Option<(u32, TokenStyle)> = None;
+        for &(k, required, style) in &self.modifier_styles {
+            if k != kind || required.is_empty() || !modifiers.contains(required) {
+                continue;
+            }
+            let specificity = required.count();
+            if best.is_none_or(|(b, _)| specificity > b) {
+                best = Some((specificity, style));
+            }
+        }
+
+        match best {
+            Some((_, style)) => style,
+            None => default_modifier_overlay(base, modifiers),
+        }
+    }
+
+    /// Register a theme rule that applies `style` to any token whose
+    /// [`Token::scopes`] stack contains every scope in `scopes` (order within
+    /// `scopes` doesn't matter; the token's stack may contain more). Keeps
+    /// [`scope_rules`](Self::scope_rules) sorted by descending scope count so
+    /// [`get_style_for_token`](Self::get_style_for_token) can stop at the
+    /// first ("biggest union") match.
+    pub fn add_scope_rule<I, S>(&mut self, scopes: I, style: TokenStyle)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let scopes: Vec<String> = scopes.into_iter().map(Into::into).collect();
+        self.scope_rules.push((scopes, style));
+        self.scope_rules.sort_by_key(|(required, _)| std::cmp::Reverse(required.len()));
+    }
+
+    /// Resolve a token's rendered style, preferring its scope stack (see
+    /// [`add_scope_rule`](Self::add_scope_rule)) over its bare
+    /// [`TokenKind`]/[`Modifiers`] pair when the token carries one.
+    ///
+    /// Scope rules are tried first, longest (most specific) required set
+    /// first; the first rule whose entire required set is contained in
+    /// [`token.scopes`](Token::scopes) wins. A token with no scopes, or one
+    /// that matches no rule, falls back to
+    /// [`get_style_with_modifiers`](Self::get_style_with_modifiers).
+    pub fn get_style_for_token(&self, token: &Token) -> TokenStyle {
+        if !token.scopes.is_empty() {
+            for (required, style) in &self.scope_rules {
+                if !required.is_empty()
+                    && required.iter().all(|r| token.scopes.contains(&r.as_str()))
+                {
+                    return *style;
+                }
+            }
+        }
+        self.get_style_with_modifiers(token.kind, token.modifiers)
+    }
+}
+
+/// The built-in rendering tweak for each [`Modifiers`] bit, applied on top of
+/// a token's base style when no theme entry overrides it for that kind.
+/// Multiple set modifiers compose (e.g. `MUTABLE | DEPRECATED` is both
+/// italic and underlined).
+fn default_modifier_overlay(mut style: TokenStyle, modifiers: Modifiers) -> TokenStyle {
+    if modifiers.contains(Modifiers::DEPRECATED) {
+        style.underline = true;
+    }
+    if modifiers.contains(Modifiers::MUTABLE) {
+        style.italic = true;
+    }
+    if modifiers.contains(Modifiers::UNSAFE) {
+        style.bold = true;
+    }
+    style
+}
+
+impl Theme {
+    /// Serialize the resolved theme to a TOML document: a table keyed by
+    /// [`TokenKind`] name, each value carrying a `#RRGGBB` foreground, an
+    /// optional background, and the bold/italic/underline flags. Keying by name
+    /// (rather than numeric index) keeps hand-written files readable and lets
+    /// them survive a reordering of the `TokenKind` enum.
+    #[cfg(feature = "serde")]
+    pub fn to_toml(&self) -> Result<String, toml::ser::Error> {
+        toml::to_string(self)
+    }
+
+    /// Load a theme from a TOML document produced by [`Theme::to_toml`] (or
+    /// hand-written). Parsing starts from [`Theme::default_dark`] and overlays
+    /// only the kinds the file names, mirroring how [`Theme::from_overrides`]
+    /// and [`Theme::set_style`] apply partial overrides.
+    #[cfg(feature = "serde")]
+    pub fn from_toml(contents: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(contents)
+    }
+
+    /// Serialize the resolved theme to a JSON document, so a `.theme.json`
+    /// file can be shipped and hand-edited alongside (or instead of) the TOML
+    /// form. Shape and semantics mirror [`Theme::to_toml`]: a table keyed by
+    /// [`TokenKind`] name, each value an object with a `#RRGGBB[AA]` `fg`, an
+    /// optional `bg`, and the bold/italic/underline flags.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Load a theme from a JSON document produced by [`Theme::to_json`] (or
+    /// hand-written). Parsing starts from [`Theme::default_dark`] and overlays
+    /// only the kinds the file names, mirroring [`Theme::from_toml`]. Colors
+    /// must be written as `#RRGGBB` or `#RRGGBBAA`; anything else is rejected
+    /// with an "expected #RRGGBB[AA]" error instead of silently falling back.
+    #[cfg(feature = "serde")]
+    pub fn from_json(contents: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(contents)
+    }
 }
 
 impl Default for Theme {
@@ -195,6 +582,427 @@ impl Default for Theme {
     }
 }
 
+/// The [`TokenKind`]s that carry a stable, theme-file-visible name, in the order
+/// [`Theme::to_toml`] emits them. Kept alongside [`token_kind_from_name`] so the
+/// serialized form and the parser agree on exactly which kinds round-trip.
+#[cfg(feature = "serde")]
+const NAMED_KINDS: &[TokenKind] = {
+    use TokenKind::*;
+    &[
+        Whitespace, Comment, Error, String, Number, Boolean, Null, Char, DateTime, Keyword,
+        KeywordControl, KeywordFunction, KeywordImport, KeywordStorage, KeywordType,
+        KeywordOperator, Identifier, TypeName, FunctionName, VariableName, PropertyName,
+        ParameterName, Operator, Punctuation, Delimiter, Separator, Attribute, Macro, Label,
+        Escape, JsonKey, JsonBrace, JsonBracket, JsonColon, JsonComma, RustLifetime, RustMacro,
+        RustAttribute, MarkdownHeading1, MarkdownHeading2, MarkdownHeading3, MarkdownHeading4,
+        MarkdownHeading5, MarkdownHeading6, MarkdownBold, MarkdownItalic, MarkdownCode,
+        MarkdownLink, MarkdownListMarker, MarkdownTaskBox, MarkdownBlockQuote,
+        MarkdownTableDelimiter,
+    ]
+};
+
+/// The theme-file name for a [`TokenKind`] (the inverse of
+/// [`token_kind_from_name`], matching the variant's `Debug` spelling).
+#[cfg(feature = "serde")]
+fn token_kind_name(kind: TokenKind) -> &'static str {
+    use TokenKind::*;
+    match kind {
+        Whitespace => "Whitespace",
+        Comment => "Comment",
+        Error => "Error",
+        String => "String",
+        Number => "Number",
+        Boolean => "Boolean",
+        Null => "Null",
+        Char => "Char",
+        DateTime => "DateTime",
+        Keyword => "Keyword",
+        KeywordControl => "KeywordControl",
+        KeywordFunction => "KeywordFunction",
+        KeywordImport => "KeywordImport",
+        KeywordStorage => "KeywordStorage",
+        KeywordType => "KeywordType",
+        KeywordOperator => "KeywordOperator",
+        Identifier => "Identifier",
+        TypeName => "TypeName",
+        FunctionName => "FunctionName",
+        VariableName => "VariableName",
+        PropertyName => "PropertyName",
+        ParameterName => "ParameterName",
+        Operator => "Operator",
+        Punctuation => "Punctuation",
+        Delimiter => "Delimiter",
+        Separator => "Separator",
+        Attribute => "Attribute",
+        Macro => "Macro",
+        Label => "Label",
+        Escape => "Escape",
+        JsonKey => "JsonKey",
+        JsonBrace => "JsonBrace",
+        JsonBracket => "JsonBracket",
+        JsonColon => "JsonColon",
+        JsonComma => "JsonComma",
+        RustLifetime => "RustLifetime",
+        RustMacro => "RustMacro",
+        RustAttribute => "RustAttribute",
+        MarkdownHeading1 => "MarkdownHeading1",
+        MarkdownHeading2 => "MarkdownHeading2",
+        MarkdownHeading3 => "MarkdownHeading3",
+        MarkdownHeading4 => "MarkdownHeading4",
+        MarkdownHeading5 => "MarkdownHeading5",
+        MarkdownHeading6 => "MarkdownHeading6",
+        MarkdownBold => "MarkdownBold",
+        MarkdownItalic => "MarkdownItalic",
+        MarkdownCode => "MarkdownCode",
+        MarkdownLink => "MarkdownLink",
+        MarkdownListMarker => "MarkdownListMarker",
+        MarkdownTaskBox => "MarkdownTaskBox",
+        MarkdownBlockQuote => "MarkdownBlockQuote",
+        MarkdownTableDelimiter => "MarkdownTableDelimiter",
+        // Structural kinds never surface in a theme file; fold them into the
+        // catch-all foreground so a round-trip stays lossless for the rest.
+        _ => "Unknown",
+    }
+}
+
+/// Format a color as a `#RRGGBB` hex string, dropping the (always-opaque) alpha.
+#[cfg(feature = "serde")]
+fn hex_of(color: StraightRgba) -> String {
+    format!("#{:02X}{:02X}{:02X}", color.red(), color.green(), color.blue())
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::*;
+    use serde::de::{Error as _, MapAccess, Visitor};
+    use serde::ser::SerializeMap;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    /// The on-disk shape of a [`TokenStyle`]: a hex foreground, an optional hex
+    /// background, and the three attribute flags (omitted when `false`).
+    #[derive(Serialize, Deserialize)]
+    struct TokenStyleRepr {
+        fg: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        bg: Option<String>,
+        #[serde(default, skip_serializing_if = "is_false")]
+        bold: bool,
+        #[serde(default, skip_serializing_if = "is_false")]
+        italic: bool,
+        #[serde(default, skip_serializing_if = "is_false")]
+        underline: bool,
+    }
+
+    fn is_false(b: &bool) -> bool {
+        !*b
+    }
+
+    impl Serialize for TokenStyle {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            TokenStyleRepr {
+                fg: hex_of(self.fg),
+                bg: self.bg.map(hex_of),
+                bold: self.bold,
+                italic: self.italic,
+                underline: self.underline,
+            }
+            .serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for TokenStyle {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let repr = TokenStyleRepr::deserialize(deserializer)?;
+            let fg = parse_hex_color(&repr.fg).ok_or_else(|| {
+                D::Error::custom(format!("expected #RRGGBB[AA], got `{}`", repr.fg))
+            })?;
+            let bg = match repr.bg {
+                Some(ref s) => Some(parse_hex_color(s).ok_or_else(|| {
+                    D::Error::custom(format!("expected #RRGGBB[AA], got `{s}`"))
+                })?),
+                None => None,
+            };
+            Ok(TokenStyle {
+                fg,
+                bg,
+                bold: repr.bold,
+                italic: repr.italic,
+                underline: repr.underline,
+            })
+        }
+    }
+
+    impl Serialize for Theme {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut map = serializer.serialize_map(Some(NAMED_KINDS.len()))?;
+            for &kind in NAMED_KINDS {
+                map.serialize_entry(token_kind_name(kind), &self.get_style(kind))?;
+            }
+            map.end()
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Theme {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            struct ThemeVisitor;
+
+            impl<'de> Visitor<'de> for ThemeVisitor {
+                type Value = Theme;
+
+                fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    f.write_str("a table of TokenKind name to style")
+                }
+
+                fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<Theme, A::Error> {
+                    let mut theme = Theme::default_dark();
+                    while let Some((name, style)) = access.next_entry::<String, TokenStyle>()? {
+                        if let Some(kind) = token_kind_from_name(&name) {
+                            theme.set_style(kind, style);
+                        }
+                    }
+                    Ok(theme)
+                }
+            }
+
+            deserializer.deserialize_map(ThemeVisitor)
+        }
+    }
+}
+
+/// Names of the built-in themes, in the order a picker should list them.
+pub const BUILTIN_THEMES: &[&str] = &["Dark+", "Light+"];
+
+/// A dense index into a [`HighlightMap`]'s precomputed style table — cheaper
+/// to carry on a render-side token than resolving through [`Theme::get_style`]
+/// again, and trivial to derive: it's just the [`TokenKind`] discriminant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HighlightId(u8);
+
+impl HighlightId {
+    /// The `HighlightId` for a bare [`TokenKind`], ignoring any
+    /// [`Modifiers`]/scope overlay — use [`HighlightMap::style`] to resolve it
+    /// back to a [`TokenStyle`].
+    pub fn from_kind(kind: TokenKind) -> Self {
+        HighlightId(kind as u8)
+    }
+}
+
+/// A precomputed `TokenKind → TokenStyle` table, so the hot per-token/per-pixel
+/// repaint path is a single array index instead of re-walking
+/// [`Theme::get_style`]'s lookup (or the modifier/scope overlays in
+/// [`Theme::get_style_for_token`]) on every call.
+///
+/// Rebuild it whenever the [`Theme`] changes (e.g. from
+/// [`SyntaxHighlighter::set_theme`](crate::syntax::SyntaxHighlighter::set_theme));
+/// it does not track further theme mutations on its own. Tokens carrying
+/// [`Modifiers`] or [`Token::scopes`](crate::syntax::Token::scopes) still need
+/// [`Theme::get_style_for_token`] for the full resolution — this map only
+/// covers the base-kind fast path.
+pub struct HighlightMap {
+    styles: Vec<TokenStyle>,
+}
+
+impl HighlightMap {
+    /// Snapshot `theme`'s dense kind→style table.
+    pub fn new(theme: &Theme) -> Self {
+        Self { styles: theme.style_table().to_vec() }
+    }
+
+    /// Resolve a [`HighlightId`] to its precomputed style.
+    pub fn style(&self, id: HighlightId) -> TokenStyle {
+        self.styles.get(id.0 as usize).copied().unwrap_or(TokenStyle::new(rgb(0xD4D4D4)))
+    }
+}
+
+/// Map a [`TokenKind`]'s name (as written in a theme file, matching its `Debug`
+/// spelling) to the variant. Returns `None` for unknown names.
+fn token_kind_from_name(name: &str) -> Option<TokenKind> {
+    use TokenKind::*;
+    Some(match name {
+        "Whitespace" => Whitespace,
+        "Comment" => Comment,
+        "Error" => Error,
+        "String" => String,
+        "Number" => Number,
+        "Boolean" => Boolean,
+        "Null" => Null,
+        "Char" => Char,
+        "DateTime" => DateTime,
+        "Keyword" => Keyword,
+        "KeywordControl" => KeywordControl,
+        "KeywordFunction" => KeywordFunction,
+        "KeywordImport" => KeywordImport,
+        "KeywordStorage" => KeywordStorage,
+        "KeywordType" => KeywordType,
+        "KeywordOperator" => KeywordOperator,
+        "Identifier" => Identifier,
+        "TypeName" => TypeName,
+        "FunctionName" => FunctionName,
+        "VariableName" => VariableName,
+        "PropertyName" => PropertyName,
+        "ParameterName" => ParameterName,
+        "Operator" => Operator,
+        "Punctuation" => Punctuation,
+        "Delimiter" => Delimiter,
+        "Separator" => Separator,
+        "Attribute" => Attribute,
+        "Macro" => Macro,
+        "Label" => Label,
+        "Escape" => Escape,
+        "JsonKey" => JsonKey,
+        "JsonBrace" => JsonBrace,
+        "JsonBracket" => JsonBracket,
+        "JsonColon" => JsonColon,
+        "JsonComma" => JsonComma,
+        "RustLifetime" => RustLifetime,
+        "RustMacro" => RustMacro,
+        "RustAttribute" => RustAttribute,
+        "MarkdownHeading1" => MarkdownHeading1,
+        "MarkdownHeading2" => MarkdownHeading2,
+        "MarkdownHeading3" => MarkdownHeading3,
+        "MarkdownHeading4" => MarkdownHeading4,
+        "MarkdownHeading5" => MarkdownHeading5,
+        "MarkdownHeading6" => MarkdownHeading6,
+        "MarkdownBold" => MarkdownBold,
+        "MarkdownItalic" => MarkdownItalic,
+        "MarkdownCode" => MarkdownCode,
+        "MarkdownLink" => MarkdownLink,
+        "MarkdownListMarker" => MarkdownListMarker,
+        "MarkdownTaskBox" => MarkdownTaskBox,
+        "MarkdownBlockQuote" => MarkdownBlockQuote,
+        "MarkdownTableDelimiter" => MarkdownTableDelimiter,
+        _ => return None,
+    })
+}
+
+/// Maps TextMate scope selectors to [`TokenKind`]. Ordered from most to least
+/// specific only for readability; [`apply_scope`] picks the longest matching
+/// entry regardless of position. A selector matches an entry when it equals the
+/// entry or extends it on a `.` boundary (so `keyword.control.flow` matches
+/// `keyword.control`, but `keyword` alone does not match `keyword.control`).
+const SCOPE_TABLE: &[(&str, TokenKind)] = &[
+    ("comment", TokenKind::Comment),
+    ("string", TokenKind::String),
+    ("constant.numeric", TokenKind::Number),
+    ("constant.language", TokenKind::Boolean),
+    ("constant.character.escape", TokenKind::Escape),
+    ("constant.character", TokenKind::Char),
+    ("keyword.control", TokenKind::KeywordControl),
+    ("keyword.operator", TokenKind::KeywordOperator),
+    ("keyword.other.import", TokenKind::KeywordImport),
+    ("keyword", TokenKind::Keyword),
+    ("storage.type", TokenKind::KeywordType),
+    ("storage", TokenKind::KeywordStorage),
+    ("entity.name.function", TokenKind::FunctionName),
+    ("entity.name.type", TokenKind::TypeName),
+    ("entity.other.attribute-name", TokenKind::Attribute),
+    ("support.function", TokenKind::FunctionName),
+    ("support.type", TokenKind::TypeName),
+    ("variable.parameter", TokenKind::ParameterName),
+    ("variable.other.property", TokenKind::PropertyName),
+    ("variable", TokenKind::VariableName),
+    ("punctuation", TokenKind::Punctuation),
+];
+
+/// Resolve one TextMate scope selector and, if it names a known kind, record
+/// `style` for that kind — but only when the selector is at least as specific
+/// as whatever rule last wrote the slot.
+///
+/// A selector may be a comma-separated list, and each item a space-separated
+/// descendant path whose rightmost component is the governing scope.
+fn apply_scope(
+    styles: &mut [TokenStyle],
+    specificity: &mut [usize],
+    selector: &str,
+    style: TokenStyle,
+) {
+    for part in selector.split(',') {
+        let Some(scope) = part.trim().split_whitespace().last() else { continue };
+        let mut best: Option<(usize, TokenKind)> = None;
+        for &(prefix, kind) in SCOPE_TABLE {
+            let matches = scope == prefix
+                || scope.strip_prefix(prefix).is_some_and(|rest| rest.starts_with('.'));
+            if matches && best.is_none_or(|(len, _)| prefix.len() > len) {
+                best = Some((prefix.len(), kind));
+            }
+        }
+        if let Some((len, kind)) = best {
+            let slot = kind as usize;
+            if len >= specificity[slot] {
+                styles[slot] = style;
+                specificity[slot] = len;
+            }
+        }
+    }
+}
+
+/// Parse a `#RRGGBB[AA] [bold] [italic] [underline]` style spec.
+fn parse_style(spec: &str) -> Option<TokenStyle> {
+    let mut words = spec.split_whitespace();
+    let mut style = TokenStyle::new(parse_hex_color(words.next()?)?);
+    for word in words {
+        match word.to_ascii_lowercase().as_str() {
+            "bold" => style.bold = true,
+            "italic" => style.italic = true,
+            "underline" => style.underline = true,
+            _ => return None,
+        }
+    }
+    Some(style)
+}
+
+/// Parse a `#RRGGBB` or `#RRGGBBAA` color.
+fn parse_hex_color(s: &str) -> Option<StraightRgba> {
+    let hex = s.strip_prefix('#')?;
+    if hex.len() != 6 && hex.len() != 8 {
+        return None;
+    }
+    let r = u32::from_str_radix(&hex[0..2], 16).ok()?;
+    let g = u32::from_str_radix(&hex[2..4], 16).ok()?;
+    let b = u32::from_str_radix(&hex[4..6], 16).ok()?;
+    let a = if hex.len() == 8 { u32::from_str_radix(&hex[6..8], 16).ok()? } else { 0xFF };
+    Some(StraightRgba::from_le(r | (g << 8) | (b << 16) | (a << 24)))
+}
+
+/// Parse one SGR field, stripping leading zeros (`01` → `1`, `00` → `0`).
+/// Returns `None` for a field that is not a number.
+fn parse_sgr_code(field: &str) -> Option<u32> {
+    let field = field.trim();
+    if field.is_empty() {
+        return None;
+    }
+    let trimmed = field.trim_start_matches('0');
+    if trimmed.is_empty() { Some(0) } else { trimmed.parse().ok() }
+}
+
+/// The sRGB value of one of the 16 ANSI colors (`0`–`7` normal, `8`–`15`
+/// bright).
+fn ansi16(index: u32) -> StraightRgba {
+    const PALETTE: [u32; 16] = [
+        0x000000, 0x800000, 0x008000, 0x808000, 0x000080, 0x800080, 0x008080, 0xC0C0C0,
+        0x808080, 0xFF0000, 0x00FF00, 0xFFFF00, 0x0000FF, 0xFF00FF, 0x00FFFF, 0xFFFFFF,
+    ];
+    rgb(PALETTE[(index as usize) & 0xF])
+}
+
+/// The sRGB value of an xterm 256-color index: the 16 ANSI colors, the 6×6×6
+/// color cube (16–231), then the 24-step grayscale ramp (232–255).
+fn xterm256(n: u32) -> StraightRgba {
+    if n < 16 {
+        return ansi16(n);
+    }
+    if n < 232 {
+        const LEVELS: [u32; 6] = [0, 95, 135, 175, 215, 255];
+        let n = n - 16;
+        let r = LEVELS[(n / 36) as usize];
+        let g = LEVELS[((n / 6) % 6) as usize];
+        let b = LEVELS[(n % 6) as usize];
+        return rgb((r << 16) | (g << 8) | b);
+    }
+    let v = 8 + 10 * (n - 232);
+    rgb((v << 16) | (v << 8) | v)
+}
+
 /// Helper to create an RGB color from a hex value.
 const fn rgb(hex: u32) -> StraightRgba {
     // StraightRgba stores colors as 0xAABBGGRR (little-endian)
@@ -216,6 +1024,192 @@ mod tests {
         assert!(style.fg.red() > 0 || style.fg.green() > 0 || style.fg.blue() > 0);
     }
 
+    #[test]
+    fn test_builtin_lookup() {
+        assert!(Theme::builtin("Light+").is_some());
+        assert!(Theme::builtin("dark").is_some());
+        assert!(Theme::builtin("nonesuch").is_none());
+    }
+
+    #[test]
+    fn test_from_overrides() {
+        let theme = Theme::from_overrides(
+            "; my theme\nKeyword: #112233 bold\nbogus line\nComment: #445566",
+        );
+        let kw = theme.get_style(TokenKind::Keyword);
+        assert_eq!((kw.fg.red(), kw.fg.green(), kw.fg.blue()), (0x11, 0x22, 0x33));
+        assert!(kw.bold);
+        assert_eq!(theme.get_style(TokenKind::Comment).fg.blue(), 0x66);
+    }
+
+    #[test]
+    fn test_from_sgr() {
+        let s = TokenStyle::from_sgr("01;38;2;255;0;0").unwrap();
+        assert!(s.bold);
+        assert_eq!((s.fg.red(), s.fg.green(), s.fg.blue()), (255, 0, 0));
+
+        let palette = TokenStyle::from_sgr("3;34").unwrap();
+        assert!(palette.italic);
+        assert_eq!((palette.fg.red(), palette.fg.green(), palette.fg.blue()), (0, 0, 0x80));
+
+        // A 256-color background from the grayscale ramp.
+        let gray = TokenStyle::from_sgr("48;5;232").unwrap();
+        let bg = gray.bg.unwrap();
+        assert_eq!((bg.red(), bg.green(), bg.blue()), (8, 8, 8));
+
+        assert!(TokenStyle::from_sgr("notacode").is_none());
+    }
+
+    #[test]
+    fn test_from_ls_colors() {
+        let theme = Theme::from_ls_colors("Keyword=1;35:Comment=3;32:bogus=1:String=");
+        let kw = theme.get_style(TokenKind::Keyword);
+        assert!(kw.bold);
+        assert_eq!((kw.fg.red(), kw.fg.green(), kw.fg.blue()), (0x80, 0x00, 0x80));
+        assert!(theme.get_style(TokenKind::Comment).italic);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_theme_toml_round_trip() {
+        let theme = Theme::default_dark();
+        let toml = theme.to_toml().unwrap();
+        let parsed = Theme::from_toml(&toml).unwrap();
+        for &kind in NAMED_KINDS {
+            assert_eq!(theme.get_style(kind), parsed.get_style(kind));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_from_toml_partial_overlay() {
+        // Only Keyword is listed; every other kind keeps the dark default.
+        let theme = Theme::from_toml("[Keyword]\nfg = \"#112233\"\nbold = true\n").unwrap();
+        let kw = theme.get_style(TokenKind::Keyword);
+        assert_eq!((kw.fg.red(), kw.fg.green(), kw.fg.blue()), (0x11, 0x22, 0x33));
+        assert!(kw.bold);
+        assert_eq!(theme.get_style(TokenKind::Comment), Theme::default_dark().get_style(TokenKind::Comment));
+    }
+
+    #[test]
+    fn test_from_vscode_json() {
+        let json = r#"{
+            "colors": { "editor.foreground": "#abcdef" },
+            "tokenColors": [
+                { "scope": "comment", "settings": { "foreground": "#6A9955", "fontStyle": "italic" } },
+                { "scope": ["keyword", "keyword.other"], "settings": { "foreground": "#C586C0" } },
+                { "scope": "keyword.control", "settings": { "foreground": "#FF0000", "fontStyle": "bold" } }
+            ]
+        }"#;
+        let theme = Theme::from_vscode_json(json);
+
+        let comment = theme.get_style(TokenKind::Comment);
+        assert!(comment.italic);
+        assert_eq!((comment.fg.red(), comment.fg.green(), comment.fg.blue()), (0x6A, 0x99, 0x55));
+
+        // The more specific `keyword.control` wins over the broader `keyword`.
+        let ctrl = theme.get_style(TokenKind::KeywordControl);
+        assert!(ctrl.bold);
+        assert_eq!((ctrl.fg.red(), ctrl.fg.green(), ctrl.fg.blue()), (0xFF, 0, 0));
+
+        // An untouched kind inherits the editor default foreground.
+        let ident = theme.get_style(TokenKind::Identifier);
+        assert_eq!((ident.fg.red(), ident.fg.green(), ident.fg.blue()), (0xAB, 0xCD, 0xEF));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_theme_json_round_trip() {
+        let theme = Theme::default_dark();
+        let json = theme.to_json().unwrap();
+        let parsed = Theme::from_json(&json).unwrap();
+        for &kind in NAMED_KINDS {
+            assert_eq!(theme.get_style(kind), parsed.get_style(kind));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_from_json_partial_overlay() {
+        let theme = Theme::from_json(r#"{"Keyword": {"fg": "#112233ff", "bold": true}}"#).unwrap();
+        let kw = theme.get_style(TokenKind::Keyword);
+        assert_eq!((kw.fg.red(), kw.fg.green(), kw.fg.blue()), (0x11, 0x22, 0x33));
+        assert!(kw.bold);
+        assert_eq!(theme.get_style(TokenKind::Comment), Theme::default_dark().get_style(TokenKind::Comment));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_from_json_rejects_bad_color() {
+        let err = Theme::from_json(r#"{"Keyword": {"fg": "112233"}}"#).unwrap_err();
+        assert!(err.to_string().contains("expected #RRGGBB[AA]"));
+    }
+
+    #[test]
+    fn test_modifier_overlay_defaults() {
+        let theme = Theme::default_dark();
+        let base = theme.get_style(TokenKind::Identifier);
+
+        let deprecated = theme.get_style_with_modifiers(TokenKind::Identifier, Modifiers::DEPRECATED);
+        assert!(deprecated.underline);
+        assert_eq!(deprecated.fg, base.fg);
+
+        let mutable = theme.get_style_with_modifiers(TokenKind::Identifier, Modifiers::MUTABLE);
+        assert!(mutable.italic);
+
+        // No modifiers set falls straight through to the bare-kind style.
+        assert_eq!(theme.get_style_with_modifiers(TokenKind::Identifier, Modifiers::NONE), base);
+    }
+
+    #[test]
+    fn test_modifier_style_override_prefers_most_specific() {
+        let mut theme = Theme::default_dark();
+        let both = Modifiers::MUTABLE | Modifiers::DEPRECATED;
+        theme.set_modifier_style(TokenKind::Identifier, Modifiers::MUTABLE, TokenStyle::new(rgb(0x111111)));
+        theme.set_modifier_style(TokenKind::Identifier, both, TokenStyle::new(rgb(0x222222)));
+
+        // A token with both modifiers matches the more specific two-bit entry.
+        let style = theme.get_style_with_modifiers(TokenKind::Identifier, both);
+        assert_eq!((style.fg.red(), style.fg.green(), style.fg.blue()), (0x22, 0x22, 0x22));
+
+        // A token with only `MUTABLE` matches the single-bit entry instead.
+        let style = theme.get_style_with_modifiers(TokenKind::Identifier, Modifiers::MUTABLE);
+        assert_eq!((style.fg.red(), style.fg.green(), style.fg.blue()), (0x11, 0x11, 0x11));
+    }
+
+    #[test]
+    fn test_scope_rule_prefers_biggest_union() {
+        let mut theme = Theme::default_dark();
+        theme.add_scope_rule(["string"], TokenStyle::new(rgb(0x111111)));
+        theme.add_scope_rule(["string", "string.quoted"], TokenStyle::new(rgb(0x222222)));
+
+        let token = Token::with_scopes(
+            TokenKind::String,
+            0..3,
+            &["string", "string.quoted", "source.json"],
+        );
+        let style = theme.get_style_for_token(&token);
+        assert_eq!((style.fg.red(), style.fg.green(), style.fg.blue()), (0x22, 0x22, 0x22));
+
+        // A token missing `string.quoted` only matches the broader rule.
+        let plain = Token::with_scopes(TokenKind::String, 0..3, &["string"]);
+        let style = theme.get_style_for_token(&plain);
+        assert_eq!((style.fg.red(), style.fg.green(), style.fg.blue()), (0x11, 0x11, 0x11));
+    }
+
+    #[test]
+    fn test_scope_rule_falls_back_without_match() {
+        let mut theme = Theme::default_dark();
+        theme.add_scope_rule(["comment.line"], TokenStyle::new(rgb(0x111111)));
+
+        let token = Token::with_scopes(TokenKind::String, 0..3, &["string"]);
+        assert_eq!(theme.get_style_for_token(&token), theme.get_style(TokenKind::String));
+
+        // No scopes at all: resolves through kind/modifiers as before.
+        let bare = Token::new(TokenKind::Keyword, 0..2);
+        assert_eq!(theme.get_style_for_token(&bare), theme.get_style(TokenKind::Keyword));
+    }
+
     #[test]
     fn test_rgb_helper() {
         let color = rgb(0xFF0000);
@@ -3,27 +3,161 @@
 
 //! Token types and structures for syntax highlighting.
 
+use std::fmt::Write;
 use std::ops::Range;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 /// A single token from the lexer.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Token {
     /// The kind of token
     pub kind: TokenKind,
     /// The byte span in the source text
     pub span: TokenSpan,
+    /// Recoverable problems recorded on the token. A lexer can keep emitting a
+    /// semantically correct [`TokenKind`] for coloring (e.g. `String`) while
+    /// still signaling that it was, say, unterminated — instead of collapsing
+    /// the whole token to [`TokenKind::Error`].
+    pub flags: TokenFlags,
+    /// Orthogonal semantic qualifiers (declaration site, mutability, …) a
+    /// lexer can layer on top of [`kind`](Self::kind) so a single tag renders
+    /// differently by context — see [`Modifiers`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub modifiers: Modifiers,
+    /// An ordered stack of TextMate-style scope names (most general first,
+    /// e.g. `["string", "string.quoted", "source.json"]`), letting a theme
+    /// target a context more specific than [`kind`](Self::kind) without
+    /// lexers having to enumerate a new [`TokenKind`] for every combination —
+    /// see [`Theme::get_style_for_token`](crate::syntax::Theme::get_style_for_token).
+    /// Empty for tokens that don't need it. Not part of the serde form (a
+    /// `&'static str` can't be produced by deserializing untrusted input).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub scopes: &'static [&'static str],
+    /// Line/column span of the token, populated only under the
+    /// `token-positions` feature and otherwise absent entirely, so byte-range
+    /// consumers pay no storage cost. Kept out of the JSON/serde form, which
+    /// stays byte-oriented.
+    #[cfg(feature = "token-positions")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub position: Option<crate::syntax::codemap::Span>,
 }
 
 /// A byte range in the source text.
 pub type TokenSpan = Range<usize>;
 
+/// A bitset of recoverable problems flagged on a [`Token`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TokenFlags(u8);
+
+impl TokenFlags {
+    /// No problems.
+    pub const NONE: Self = TokenFlags(0);
+    /// A string, comment, or here-string that ran to end-of-input unclosed.
+    pub const UNTERMINATED: Self = TokenFlags(1 << 0);
+    /// A numeric literal with a malformed suffix or body (e.g. `0x` with no hex
+    /// digits, or a bare trailing decimal point).
+    pub const INVALID_SUFFIX: Self = TokenFlags(1 << 1);
+
+    /// Whether no flags are set.
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Whether every bit in `other` is set.
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl std::ops::BitOr for TokenFlags {
+    type Output = Self;
+    fn bitor(self, rhs: Self) -> Self {
+        TokenFlags(self.0 | rhs.0)
+    }
+}
+
+impl std::ops::BitOrAssign for TokenFlags {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+/// A bitset of orthogonal semantic qualifiers a lexer can attach to a
+/// [`Token`] alongside its base [`TokenKind`] tag, the way semantic
+/// highlighters layer "declaration", "mutable", "static", etc. on top of a
+/// syntax-only classification.
+///
+/// [`Theme::get_style_with_modifiers`](crate::syntax::Theme::get_style_with_modifiers)
+/// resolves these against a theme's `(kind, modifiers)` overlay entries,
+/// falling back to a small built-in overlay and then the bare [`TokenKind`]
+/// style.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Modifiers(u16);
+
+impl Modifiers {
+    /// No modifiers.
+    pub const NONE: Self = Modifiers(0);
+    /// The defining occurrence of a name (as opposed to a reference to it).
+    pub const DECLARATION: Self = Modifiers(1 << 0);
+    /// A binding that can be reassigned (`let mut`, `var`, …).
+    pub const MUTABLE: Self = Modifiers(1 << 1);
+    /// A `static`/class-level member rather than an instance one.
+    pub const STATIC: Self = Modifiers(1 << 2);
+    /// Code only valid inside an `unsafe` context.
+    pub const UNSAFE: Self = Modifiers(1 << 3);
+    /// An item documented or annotated as deprecated.
+    pub const DEPRECATED: Self = Modifiers(1 << 4);
+    /// A control-flow keyword or the label it targets (`return`, `break`, `if`, …).
+    pub const CONTROL: Self = Modifiers(1 << 5);
+
+    /// Whether no modifiers are set.
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Whether every bit in `other` is set.
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// The number of modifiers set, used to rank overlapping theme overlays
+    /// by specificity (most bits matched wins).
+    pub fn count(self) -> u32 {
+        self.0.count_ones()
+    }
+}
+
+impl std::ops::BitOr for Modifiers {
+    type Output = Self;
+    fn bitor(self, rhs: Self) -> Self {
+        Modifiers(self.0 | rhs.0)
+    }
+}
+
+impl std::ops::BitOrAssign for Modifiers {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
 /// The kind of token.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum TokenKind {
     // Generic
     Whitespace,
     Comment,
+    LineComment,     // `//`, `#`, `--` style single-line comments
+    BlockComment,    // `/* ... */` style delimited comments
+    DocComment,      // doc comments: `///`, `//!`, `/** */`, `#`-doc conventions
     Error,
+    Indent,
+    Dedent,
 
     // Literals
     String,
@@ -31,6 +165,7 @@ pub enum TokenKind {
     Boolean,
     Null,
     Char,
+    DateTime,
 
     // Keywords
     Keyword,
@@ -60,6 +195,8 @@ pub enum TokenKind {
     Macro,           // macros
     Label,           // loop labels
     Escape,          // escape sequences in strings
+    StringInterpolationDelim, // the ${ } / { } markers around an interpolation hole
+    Inactive,        // code in a preprocessor branch compiled out by the current defines
 
     // JSON specific
     JsonKey,
@@ -74,17 +211,59 @@ pub enum TokenKind {
     RustAttribute,
 
     // Markdown specific
-    MarkdownHeading,
+    MarkdownHeading1,
+    MarkdownHeading2,
+    MarkdownHeading3,
+    MarkdownHeading4,
+    MarkdownHeading5,
+    MarkdownHeading6,
     MarkdownBold,
     MarkdownItalic,
     MarkdownCode,
     MarkdownLink,
+    MarkdownListMarker,     // -, *, +, 1., 2)
+    MarkdownTaskBox,        // [ ] / [x]
+    MarkdownBlockQuote,     // > possibly nested
+    MarkdownTableDelimiter, // | and the |:---:| alignment row
+}
+
+/// The flavor of a source comment, as reported by
+/// [`SyntaxHighlighter::comments_in_range`](crate::syntax::SyntaxHighlighter::comments_in_range).
+///
+/// Consumers use this to build folding regions (block/doc comments fold, trailing
+/// line comments usually don't) or to extract documentation ([`Doc`](Self::Doc)).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CommentKind {
+    /// A single-line comment running to the end of the line.
+    Line,
+    /// A delimited comment that may span multiple lines.
+    Block,
+    /// A documentation comment attached to the following (or enclosing) item.
+    Doc,
 }
 
 impl TokenKind {
     /// Returns true if this token is a whitespace or comment.
     pub fn is_trivia(self) -> bool {
-        matches!(self, TokenKind::Whitespace | TokenKind::Comment)
+        matches!(self, TokenKind::Whitespace) || self.is_comment()
+    }
+
+    /// Returns true if this token is any flavor of comment.
+    pub fn is_comment(self) -> bool {
+        self.comment_kind().is_some()
+    }
+
+    /// The [`CommentKind`] this token represents, or `None` if it is not a
+    /// comment. The generic [`Comment`](Self::Comment) kind — emitted by lexers
+    /// that do not distinguish flavors — reports as [`CommentKind::Line`].
+    pub fn comment_kind(self) -> Option<CommentKind> {
+        match self {
+            TokenKind::Comment | TokenKind::LineComment => Some(CommentKind::Line),
+            TokenKind::BlockComment => Some(CommentKind::Block),
+            TokenKind::DocComment => Some(CommentKind::Doc),
+            _ => None,
+        }
     }
 
     /// Returns true if this token represents an error.
@@ -106,6 +285,19 @@ impl TokenKind {
         )
     }
 
+    /// The ATX heading token for level `level` (1–6); levels outside that
+    /// range clamp to the nearest valid heading.
+    pub fn markdown_heading(level: usize) -> Self {
+        match level {
+            0 | 1 => TokenKind::MarkdownHeading1,
+            2 => TokenKind::MarkdownHeading2,
+            3 => TokenKind::MarkdownHeading3,
+            4 => TokenKind::MarkdownHeading4,
+            5 => TokenKind::MarkdownHeading5,
+            _ => TokenKind::MarkdownHeading6,
+        }
+    }
+
     /// Returns true if this token is a literal.
     pub fn is_literal(self) -> bool {
         matches!(
@@ -115,14 +307,82 @@ impl TokenKind {
                 | TokenKind::Boolean
                 | TokenKind::Null
                 | TokenKind::Char
+                | TokenKind::DateTime
         )
     }
 }
 
 impl Token {
-    /// Create a new token.
+    /// Create a new token with no flags, modifiers, or scopes set.
     pub fn new(kind: TokenKind, span: TokenSpan) -> Self {
-        Self { kind, span }
+        Self {
+            kind,
+            span,
+            flags: TokenFlags::NONE,
+            modifiers: Modifiers::NONE,
+            scopes: &[],
+            #[cfg(feature = "token-positions")]
+            position: None,
+        }
+    }
+
+    /// Create a token carrying the given recoverable-error `flags`.
+    pub fn with_flags(kind: TokenKind, span: TokenSpan, flags: TokenFlags) -> Self {
+        Self {
+            kind,
+            span,
+            flags,
+            modifiers: Modifiers::NONE,
+            scopes: &[],
+            #[cfg(feature = "token-positions")]
+            position: None,
+        }
+    }
+
+    /// Create a token carrying the given semantic `modifiers` (see [`Modifiers`]).
+    pub fn with_modifiers(kind: TokenKind, span: TokenSpan, modifiers: Modifiers) -> Self {
+        Self {
+            kind,
+            span,
+            flags: TokenFlags::NONE,
+            modifiers,
+            scopes: &[],
+            #[cfg(feature = "token-positions")]
+            position: None,
+        }
+    }
+
+    /// Add `modifier` to the token's existing [`Modifiers`], returning `self`
+    /// for chaining (mirroring [`TokenStyle`](crate::syntax::TokenStyle)'s
+    /// `.bold()`-style builders).
+    pub fn with_modifier(mut self, modifier: Modifiers) -> Self {
+        self.modifiers |= modifier;
+        self
+    }
+
+    /// Create a token carrying the given TextMate-style `scopes` stack (see
+    /// [`scopes`](Self::scopes)).
+    pub fn with_scopes(kind: TokenKind, span: TokenSpan, scopes: &'static [&'static str]) -> Self {
+        Self {
+            kind,
+            span,
+            flags: TokenFlags::NONE,
+            modifiers: Modifiers::NONE,
+            scopes,
+            #[cfg(feature = "token-positions")]
+            position: None,
+        }
+    }
+
+    /// Attach a line/column [`Span`](crate::syntax::codemap::Span) to the token.
+    ///
+    /// Available only under the `token-positions` feature; lexers call it as
+    /// they advance a running line/column counter so consumers get source
+    /// coordinates without rebuilding a line index.
+    #[cfg(feature = "token-positions")]
+    pub fn with_position(mut self, position: crate::syntax::codemap::Span) -> Self {
+        self.position = Some(position);
+        self
     }
 
     /// Get the length of the token in bytes.
@@ -135,3 +395,28 @@ impl Token {
         self.span.start == self.span.end
     }
 }
+
+/// Render a token stream as a JSON array of `{ "kind", "start", "end" }`
+/// objects, e.g. `[{"kind":"Keyword","start":0,"end":4}, ...]`.
+///
+/// This is always available and pulls in no dependencies, so tools and golden
+/// tests can capture lexer output as stable fixtures even in the default build.
+/// The `serde` feature additionally derives `Serialize`/`Deserialize` on
+/// [`Token`]/[`TokenKind`] for callers who want to deserialize it back.
+pub fn tokens_to_json(tokens: &[Token]) -> String {
+    let mut out = String::with_capacity(tokens.len() * 32 + 2);
+    out.push('[');
+    for (i, token) in tokens.iter().enumerate() {
+        if i != 0 {
+            out.push(',');
+        }
+        // Unit-variant `Debug` prints the bare variant name ("Keyword").
+        let _ = write!(
+            out,
+            "{{\"kind\":\"{:?}\",\"start\":{},\"end\":{}}}",
+            token.kind, token.span.start, token.span.end
+        );
+    }
+    out.push(']');
+    out
+}
@@ -109,13 +109,17 @@ fn test_shell_highlighting() {
 #[test]
 fn test_powershell_highlighting() {
     let theme = Theme::default();
-    let mut highlighter = SyntaxHighlighter::new(Language::Shell, theme);
-    
+    let mut highlighter = SyntaxHighlighter::new(Language::PowerShell, theme);
+
     let code = b"foreach ($item in $list) { Write-Host $item }";
     highlighter.update(code, false);
-    
+
     let tokens = highlighter.get_tokens_in_range(0..code.len());
     assert!(!tokens.is_empty(), "PowerShell lexer should produce tokens");
+    let has_keyword = tokens.iter().any(|t| matches!(t.kind, TokenKind::Keyword));
+    assert!(has_keyword, "PowerShell code should have keywords (foreach)");
+    let has_variable = tokens.iter().any(|t| matches!(t.kind, TokenKind::VariableName));
+    assert!(has_variable, "PowerShell code should have variables ($item, $list)");
 }
 
 #[test]